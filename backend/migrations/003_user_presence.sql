@@ -0,0 +1,7 @@
+-- 用户在线状态：用户可选状态和最后在线时间
+ALTER TABLE users
+    ADD COLUMN presence_status ENUM('online', 'away', 'dnd', 'invisible') DEFAULT 'online',
+    ADD COLUMN last_seen_at TIMESTAMP NULL;
+
+-- 在线状态由实时连接维护，清除历史遗留的在线标记
+UPDATE users SET is_online = FALSE;
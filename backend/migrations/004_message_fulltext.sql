@@ -0,0 +1,2 @@
+-- 消息内容全文索引，使用ngram分词以支持中文检索
+ALTER TABLE messages ADD FULLTEXT INDEX ft_messages_content (content) WITH PARSER ngram;
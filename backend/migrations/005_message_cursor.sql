@@ -0,0 +1,4 @@
+-- 历史消息游标分页：提高时间精度，并按 (created_at, id) 建立复合索引
+ALTER TABLE messages
+    MODIFY COLUMN created_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6),
+    ADD INDEX idx_room_created_id (room_id, created_at, id);
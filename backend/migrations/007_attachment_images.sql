@@ -0,0 +1,5 @@
+-- 图片附件的尺寸和缩略图（JSON数组：size/width/height/mime_type/storage_key）
+ALTER TABLE attachments
+    ADD COLUMN width INT NULL,
+    ADD COLUMN height INT NULL,
+    ADD COLUMN thumbnails TEXT NULL;
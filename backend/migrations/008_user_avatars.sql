@@ -0,0 +1,11 @@
+-- 头像改为服务端生成的版本号，旧的avatar地址保留给还没有上传新头像的用户
+ALTER TABLE users ADD COLUMN avatar_version VARCHAR(32) NULL;
+
+-- 被替换的头像版本，旧地址可能仍被客户端缓存，宽限期过后再删除文件
+CREATE TABLE IF NOT EXISTS retired_avatars (
+    user_id VARCHAR(36) NOT NULL,
+    version VARCHAR(32) NOT NULL,
+    retired_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (user_id, version),
+    INDEX idx_retired_at (retired_at)
+);
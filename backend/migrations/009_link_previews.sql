@@ -0,0 +1,13 @@
+-- 消息中链接的预览信息（OpenGraph）
+CREATE TABLE IF NOT EXISTS link_previews (
+    id VARCHAR(36) PRIMARY KEY,
+    message_id VARCHAR(36) NOT NULL,
+    url VARCHAR(2048) NOT NULL,
+    title VARCHAR(512) NULL,
+    description TEXT NULL,
+    image_url VARCHAR(2048) NULL,
+    site_name VARCHAR(255) NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_message_id (message_id),
+    FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- 服务端渲染并清洗后的消息HTML，旧消息为NULL时按需渲染
+ALTER TABLE messages ADD COLUMN content_html TEXT NULL;
@@ -0,0 +1,27 @@
+-- 房间级内容过滤规则，按name覆盖同名的全局规则
+CREATE TABLE IF NOT EXISTS room_filter_rules (
+    id VARCHAR(36) PRIMARY KEY,
+    room_id VARCHAR(36) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    pattern TEXT NOT NULL,
+    action VARCHAR(16) NOT NULL,
+    reason VARCHAR(255) NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_room_rule (room_id, name),
+    FOREIGN KEY (room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
+
+-- 被过滤规则标记、等待审核的消息
+CREATE TABLE IF NOT EXISTS message_flags (
+    id VARCHAR(36) PRIMARY KEY,
+    message_id VARCHAR(36) NOT NULL,
+    room_id VARCHAR(36) NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    rule_name VARCHAR(64) NOT NULL,
+    reason VARCHAR(255) NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_room_created (room_id, created_at),
+    FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
+);
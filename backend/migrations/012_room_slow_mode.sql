@@ -0,0 +1,2 @@
+-- 房间慢速模式：同一用户两次发言的最小间隔（秒），0表示关闭
+ALTER TABLE rooms ADD COLUMN slow_mode_seconds INT NOT NULL DEFAULT 0;
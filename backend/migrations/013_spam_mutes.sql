@@ -0,0 +1,31 @@
+-- 用户角色：user/moderator/admin，版主和管理员目前通过SQL设置
+ALTER TABLE users ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'user';
+
+-- 禁言记录，room_id为空表示全局禁言
+CREATE TABLE IF NOT EXISTS user_mutes (
+    id VARCHAR(36) PRIMARY KEY,
+    user_id VARCHAR(36) NOT NULL,
+    room_id VARCHAR(36) NULL,
+    reason VARCHAR(255) NOT NULL,
+    source VARCHAR(16) NOT NULL,
+    muted_by VARCHAR(36) NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_user_expires (user_id, expires_at),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- 反垃圾规则的触发记录，用于调整阈值
+CREATE TABLE IF NOT EXISTS spam_decisions (
+    id VARCHAR(36) PRIMARY KEY,
+    user_id VARCHAR(36) NOT NULL,
+    room_id VARCHAR(36) NOT NULL,
+    rule VARCHAR(32) NOT NULL,
+    observed INT NOT NULL,
+    threshold INT NOT NULL,
+    action VARCHAR(16) NOT NULL,
+    mute_seconds INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_rule_created (rule, created_at),
+    INDEX idx_user_created (user_id, created_at)
+);
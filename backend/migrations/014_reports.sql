@@ -0,0 +1,26 @@
+-- 封禁状态，banned_at为空表示未封禁
+ALTER TABLE users ADD COLUMN banned_at TIMESTAMP NULL;
+ALTER TABLE users ADD COLUMN ban_reason VARCHAR(255) NULL;
+
+-- 用户举报，message_id不设外键，消息删除后举报记录仍然保留
+CREATE TABLE IF NOT EXISTS reports (
+    id VARCHAR(36) PRIMARY KEY,
+    reporter_id VARCHAR(36) NOT NULL,
+    target_type VARCHAR(16) NOT NULL,
+    target_user_id VARCHAR(36) NOT NULL,
+    message_id VARCHAR(36) NULL,
+    room_id VARCHAR(36) NULL,
+    reason VARCHAR(500) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'open',
+    claimed_by VARCHAR(36) NULL,
+    claimed_at TIMESTAMP NULL,
+    resolution VARCHAR(32) NULL,
+    resolution_note VARCHAR(500) NULL,
+    resolved_by VARCHAR(36) NULL,
+    resolved_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_status_created (status, created_at),
+    INDEX idx_reporter (reporter_id),
+    FOREIGN KEY (reporter_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (target_user_id) REFERENCES users(id) ON DELETE CASCADE
+);
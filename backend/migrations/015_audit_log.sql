@@ -0,0 +1,21 @@
+-- 安全和审核审计日志，只允许追加
+CREATE TABLE IF NOT EXISTS audit_log (
+    id VARCHAR(36) PRIMARY KEY,
+    action VARCHAR(32) NOT NULL,
+    actor_id VARCHAR(36) NULL,
+    target_type VARCHAR(16) NULL,
+    target_id VARCHAR(64) NULL,
+    ip VARCHAR(45) NULL,
+    details TEXT NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_created (created_at, id),
+    INDEX idx_action_created (action, created_at),
+    INDEX idx_actor_created (actor_id, created_at),
+    INDEX idx_target_created (target_id, created_at)
+);
+
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+FOR EACH ROW SIGNAL SQLSTATE '45000' SET MESSAGE_TEXT = 'audit_log is append-only';
+
+CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+FOR EACH ROW SIGNAL SQLSTATE '45000' SET MESSAGE_TEXT = 'audit_log is append-only';
@@ -0,0 +1,49 @@
+-- 投票作为一种消息类型，消息内容为投票问题
+ALTER TABLE messages MODIFY COLUMN message_type ENUM('text', 'image', 'file', 'system', 'poll') DEFAULT 'text';
+
+-- 投票，closes_at为空表示不自动截止，closed_at为提前手动结束的时间
+CREATE TABLE IF NOT EXISTS polls (
+    id VARCHAR(36) PRIMARY KEY,
+    message_id VARCHAR(36) NOT NULL,
+    room_id VARCHAR(36) NOT NULL,
+    created_by VARCHAR(36) NOT NULL,
+    question VARCHAR(300) NOT NULL,
+    multi_choice BOOLEAN NOT NULL DEFAULT FALSE,
+    anonymous BOOLEAN NOT NULL DEFAULT FALSE,
+    closes_at TIMESTAMP NULL,
+    closed_at TIMESTAMP NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    UNIQUE KEY unique_message (message_id),
+    FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE,
+    FOREIGN KEY (room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS poll_options (
+    id VARCHAR(36) PRIMARY KEY,
+    poll_id VARCHAR(36) NOT NULL,
+    position INT NOT NULL,
+    text VARCHAR(200) NOT NULL,
+    INDEX idx_poll_position (poll_id, position),
+    FOREIGN KEY (poll_id) REFERENCES polls(id) ON DELETE CASCADE
+);
+
+-- 每个用户在每个投票中只有一张选票，由主键保证
+CREATE TABLE IF NOT EXISTS poll_votes (
+    poll_id VARCHAR(36) NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (poll_id, user_id),
+    FOREIGN KEY (poll_id) REFERENCES polls(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- 选票选中的选项，单选投票只有一行
+CREATE TABLE IF NOT EXISTS poll_vote_options (
+    poll_id VARCHAR(36) NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    option_id VARCHAR(36) NOT NULL,
+    PRIMARY KEY (poll_id, user_id, option_id),
+    INDEX idx_option (option_id),
+    FOREIGN KEY (poll_id, user_id) REFERENCES poll_votes(poll_id, user_id) ON DELETE CASCADE,
+    FOREIGN KEY (option_id) REFERENCES poll_options(id) ON DELETE CASCADE
+);
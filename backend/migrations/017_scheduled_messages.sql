@@ -0,0 +1,22 @@
+-- 定时消息，message_id在创建时生成，发送时作为消息ID，保证同一条定时消息最多发送一次
+CREATE TABLE IF NOT EXISTS scheduled_messages (
+    id VARCHAR(36) PRIMARY KEY,
+    message_id VARCHAR(36) NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    room_id VARCHAR(36) NOT NULL,
+    content TEXT NOT NULL,
+    send_at TIMESTAMP NOT NULL,
+    -- pending/sending/sent/failed/cancelled
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    claimed_by VARCHAR(64) NULL,
+    claimed_at TIMESTAMP NULL,
+    error VARCHAR(255) NULL,
+    sent_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY unique_message (message_id),
+    INDEX idx_status_send_at (status, send_at),
+    INDEX idx_user_status (user_id, status),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
@@ -0,0 +1,7 @@
+-- 阅后即焚：消息过期时间，为空表示永不过期
+ALTER TABLE messages
+    ADD COLUMN expires_at TIMESTAMP(6) NULL,
+    ADD INDEX idx_expires_at (expires_at);
+
+-- 房间默认的消息有效期（秒），0表示不过期
+ALTER TABLE rooms ADD COLUMN message_ttl_seconds INT NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- 房间消息保留天数：NULL表示使用全局默认值，0表示永久保留
+ALTER TABLE rooms ADD COLUMN retention_days INT NULL;
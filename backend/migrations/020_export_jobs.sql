@@ -0,0 +1,25 @@
+-- 房间历史导出任务，导出文件按分片保存在存储后端中
+CREATE TABLE IF NOT EXISTS export_jobs (
+    id VARCHAR(36) PRIMARY KEY,
+    room_id VARCHAR(36) NOT NULL,
+    requested_by VARCHAR(36) NOT NULL,
+    -- jsonl/csv/html
+    format VARCHAR(8) NOT NULL,
+    from_time TIMESTAMP(6) NULL,
+    to_time TIMESTAMP(6) NULL,
+    -- pending/running/completed/failed/expired
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    claimed_by VARCHAR(64) NULL,
+    total_messages BIGINT NOT NULL DEFAULT 0,
+    processed_messages BIGINT NOT NULL DEFAULT 0,
+    part_count INT NOT NULL DEFAULT 0,
+    size_bytes BIGINT NOT NULL DEFAULT 0,
+    error VARCHAR(255) NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP NULL,
+    INDEX idx_status_created (status, created_at),
+    INDEX idx_requested_by (requested_by),
+    FOREIGN KEY (room_id) REFERENCES rooms(id) ON DELETE CASCADE,
+    FOREIGN KEY (requested_by) REFERENCES users(id) ON DELETE CASCADE
+);
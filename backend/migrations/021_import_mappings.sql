@@ -0,0 +1,12 @@
+-- 外部系统导入时的ID映射，重复导入时据此跳过已导入的用户、房间和消息
+CREATE TABLE IF NOT EXISTS import_mappings (
+    -- 来源系统，如slack
+    source VARCHAR(16) NOT NULL,
+    -- user/room/message
+    kind VARCHAR(16) NOT NULL,
+    external_id VARCHAR(64) NOT NULL,
+    local_id VARCHAR(36) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (source, kind, external_id),
+    INDEX idx_local_id (local_id)
+);
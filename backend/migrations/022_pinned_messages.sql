@@ -0,0 +1,10 @@
+-- 房间置顶消息，消息删除时置顶记录一并删除
+CREATE TABLE IF NOT EXISTS pinned_messages (
+    message_id VARCHAR(36) PRIMARY KEY,
+    room_id VARCHAR(36) NOT NULL,
+    pinned_by VARCHAR(36) NOT NULL,
+    pinned_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_room_pinned (room_id, pinned_at),
+    FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE,
+    FOREIGN KEY (room_id) REFERENCES rooms(id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- 个人收藏的消息，消息或房间删除后保留收藏记录，列表中显示为已删除
+CREATE TABLE IF NOT EXISTS bookmarks (
+    id VARCHAR(36) PRIMARY KEY,
+    user_id VARCHAR(36) NOT NULL,
+    message_id VARCHAR(36) NOT NULL,
+    room_id VARCHAR(36) NOT NULL,
+    note VARCHAR(500) NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    UNIQUE KEY unique_user_message (user_id, message_id),
+    INDEX idx_user_created (user_id, created_at),
+    INDEX idx_user_room (user_id, room_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- 转发和引用回复：保存原消息的快照（JSON），原消息被删除后仍能显示来源
+ALTER TABLE messages ADD COLUMN reference TEXT NULL;
@@ -0,0 +1,3 @@
+-- 更正011中的说明：房间规则只能在全局规则之外追加，不能覆盖、关闭或削弱同名的全局规则
+ALTER TABLE room_filter_rules
+    COMMENT = '房间级内容过滤规则，只能在全局规则之外追加，不能覆盖同名的全局规则';
@@ -0,0 +1,89 @@
+use crate::database::AuditRepository;
+use crate::models::AuditEntry;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 等待写入的审计条目上限
+const QUEUE_CAPACITY: usize = 10000;
+/// 每批最多写入的条目数
+const MAX_BATCH_SIZE: usize = 100;
+/// 写入失败后的重试间隔
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_ATTEMPTS: u32 = 3;
+
+/// 审计日志入口，`record`只把条目放入队列，由后台任务批量写入数据库
+#[derive(Clone)]
+pub struct AuditLogger {
+    sender: mpsc::Sender<AuditEntry>,
+}
+
+/// 审计日志后台写入任务
+pub struct AuditWorker {
+    receiver: mpsc::Receiver<AuditEntry>,
+    audit_repo: Arc<AuditRepository>,
+}
+
+impl AuditLogger {
+    pub fn new(audit_repo: Arc<AuditRepository>) -> (Self, AuditWorker) {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let worker = AuditWorker {
+            receiver,
+            audit_repo,
+        };
+        (Self { sender }, worker)
+    }
+
+    /// 记录审计事件，不等待写入；队列满时打印条目，避免阻塞调用方
+    pub fn record(&self, entry: AuditEntry) {
+        if let Err(e) = self.sender.try_send(entry) {
+            let entry = match e {
+                mpsc::error::TrySendError::Full(entry)
+                | mpsc::error::TrySendError::Closed(entry) => entry,
+            };
+            eprintln!(
+                "审计日志队列不可用，丢弃条目: {}",
+                serde_json::to_string(&entry).unwrap_or_default()
+            );
+        }
+    }
+}
+
+impl AuditWorker {
+    pub async fn run(mut self) {
+        let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+        while let Some(entry) = self.receiver.recv().await {
+            batch.push(entry);
+            while batch.len() < MAX_BATCH_SIZE {
+                match self.receiver.try_recv() {
+                    Ok(entry) => batch.push(entry),
+                    Err(_) => break,
+                }
+            }
+
+            self.write_batch(&batch).await;
+            batch.clear();
+        }
+    }
+
+    async fn write_batch(&self, batch: &[AuditEntry]) {
+        for attempt in 1..=MAX_ATTEMPTS {
+            match self.audit_repo.create_batch(batch).await {
+                Ok(()) => return,
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    eprintln!("写入审计日志失败（第{}次）: {}", attempt, e);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+                Err(e) => {
+                    eprintln!("写入审计日志失败，丢弃{}条记录: {}", batch.len(), e);
+                    for entry in batch {
+                        eprintln!(
+                            "丢弃的审计条目: {}",
+                            serde_json::to_string(entry).unwrap_or_default()
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::database::{
+    AttachmentRepository, BookmarkRepository, LinkPreviewRepository, MessageRepository,
+    PollRepository, RoomRepository,
+};
+use crate::models::{
+    Bookmark, BookmarkInfo, BookmarkPage, BookmarkStatus, MAX_BOOKMARK_NOTE_LENGTH, SaveBookmark,
+    User,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 每页最多返回的收藏数
+const MAX_BOOKMARK_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BookmarkError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("消息不存在")]
+    NotFound,
+    #[error("没有收藏这条消息")]
+    NotBookmarked,
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type BookmarkResult<T> = Result<T, BookmarkError>;
+
+/// 个人收藏，只能收藏可读房间中的消息
+///
+/// 之后失去房间访问权限时收藏保留，列表中只返回占位；消息被删除或过期时返回已删除标记。
+#[derive(Clone)]
+pub struct BookmarkService {
+    bookmark_repo: Arc<BookmarkRepository>,
+    message_repo: Arc<MessageRepository>,
+    room_repo: Arc<RoomRepository>,
+    attachment_repo: Arc<AttachmentRepository>,
+    link_preview_repo: Arc<LinkPreviewRepository>,
+    poll_repo: Arc<PollRepository>,
+}
+
+impl BookmarkService {
+    pub fn new(
+        bookmark_repo: Arc<BookmarkRepository>,
+        message_repo: Arc<MessageRepository>,
+        room_repo: Arc<RoomRepository>,
+        attachment_repo: Arc<AttachmentRepository>,
+        link_preview_repo: Arc<LinkPreviewRepository>,
+        poll_repo: Arc<PollRepository>,
+    ) -> Self {
+        Self {
+            bookmark_repo,
+            message_repo,
+            room_repo,
+            attachment_repo,
+            link_preview_repo,
+            poll_repo,
+        }
+    }
+
+    /// 收藏消息，已收藏时更新备注
+    pub async fn save(
+        &self,
+        user: &User,
+        message_id: &str,
+        req: SaveBookmark,
+    ) -> BookmarkResult<BookmarkInfo> {
+        let note = validate_note(req.note)?;
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await?
+            .ok_or(BookmarkError::NotFound)?;
+        if !self.room_repo.can_read(&user.id, &message.room_id).await? {
+            return Err(BookmarkError::NotFound);
+        }
+
+        let bookmark = Bookmark::new(user.id.clone(), message.id, message.room_id, note);
+        self.bookmark_repo.save(&bookmark).await?;
+        let bookmark = self
+            .bookmark_repo
+            .find(&user.id, message_id)
+            .await?
+            .ok_or(BookmarkError::NotFound)?;
+
+        self.to_infos(user, vec![bookmark])
+            .await?
+            .pop()
+            .ok_or(BookmarkError::NotFound)
+    }
+
+    pub async fn remove(&self, user: &User, message_id: &str) -> BookmarkResult<()> {
+        if !self.bookmark_repo.delete(&user.id, message_id).await? {
+            return Err(BookmarkError::NotBookmarked);
+        }
+        Ok(())
+    }
+
+    /// 用户的收藏，最近收藏的在前
+    pub async fn list(
+        &self,
+        user: &User,
+        room_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> BookmarkResult<BookmarkPage> {
+        let room_id = room_id.filter(|id| !id.is_empty());
+        let limit = if limit <= 0 {
+            50
+        } else {
+            limit.min(MAX_BOOKMARK_PAGE_SIZE)
+        };
+
+        let bookmarks = self
+            .bookmark_repo
+            .list_by_user(&user.id, room_id, limit, offset.max(0))
+            .await?;
+        let total = self.bookmark_repo.count_by_user(&user.id, room_id).await?;
+
+        Ok(BookmarkPage {
+            bookmarks: self.to_infos(user, bookmarks).await?,
+            total,
+        })
+    }
+
+    /// 按当前的房间权限和消息状态组装收藏，只为可以查看的消息加载内容
+    async fn to_infos(
+        &self,
+        user: &User,
+        bookmarks: Vec<Bookmark>,
+    ) -> Result<Vec<BookmarkInfo>, sqlx::Error> {
+        let readable: HashSet<String> = self
+            .room_repo
+            .get_readable_room_ids(&user.id)
+            .await?
+            .into_iter()
+            .collect();
+        let message_ids: Vec<String> = bookmarks
+            .iter()
+            .filter(|b| readable.contains(&b.room_id))
+            .map(|b| b.message_id.clone())
+            .collect();
+
+        let messages = self.message_repo.find_by_ids(&message_ids).await?;
+        let message_ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+        let attachments = self
+            .attachment_repo
+            .find_by_message_ids(&message_ids)
+            .await?;
+        let link_previews = self
+            .link_preview_repo
+            .find_by_message_ids(&message_ids)
+            .await?;
+        let polls = self
+            .poll_repo
+            .find_infos_by_message_ids(&message_ids)
+            .await?;
+
+        Ok(bookmarks
+            .into_iter()
+            .map(|bookmark| {
+                if !readable.contains(&bookmark.room_id) {
+                    return BookmarkInfo::new(bookmark, BookmarkStatus::Inaccessible, None);
+                }
+                match messages.iter().find(|m| m.id == bookmark.message_id) {
+                    Some(message) => {
+                        let message =
+                            message.to_grpc_with_details(&attachments, &link_previews, &polls);
+                        BookmarkInfo::new(bookmark, BookmarkStatus::Available, Some(message))
+                    }
+                    None => BookmarkInfo::new(bookmark, BookmarkStatus::Deleted, None),
+                }
+            })
+            .collect())
+    }
+}
+
+/// 空备注视为没有备注
+fn validate_note(note: Option<String>) -> BookmarkResult<Option<String>> {
+    let Some(note) = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) else {
+        return Ok(None);
+    };
+    if note.chars().count() > MAX_BOOKMARK_NOTE_LENGTH {
+        return Err(BookmarkError::Invalid(format!(
+            "备注不能超过{}个字符",
+            MAX_BOOKMARK_NOTE_LENGTH
+        )));
+    }
+    Ok(Some(note))
+}
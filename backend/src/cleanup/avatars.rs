@@ -0,0 +1,87 @@
+use crate::database::UserRepository;
+use crate::models::{AVATAR_SIZES, avatar_storage_key};
+use crate::storage::{BlobStore, StorageError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 检查被替换头像的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// 每批删除的头像版本数
+const SWEEP_BATCH_SIZE: i64 = 100;
+/// 旧头像地址以immutable缓存，替换后保留一段时间，让客户端有机会刷新用户资料
+pub const AVATAR_GRACE_PERIOD: chrono::Duration = chrono::Duration::days(7);
+
+/// 删除某个头像版本的所有尺寸，文件不存在视为已删除，返回是否全部删除成功
+pub async fn delete_avatar_files(
+    blob_store: &Arc<dyn BlobStore>,
+    user_id: &str,
+    version: &str,
+) -> bool {
+    let mut deleted = true;
+    for size in AVATAR_SIZES {
+        let key = avatar_storage_key(user_id, version, size);
+        match blob_store.delete(&key).await {
+            Ok(()) | Err(StorageError::NotFound(_)) => {}
+            Err(e) => {
+                eprintln!("删除头像文件 {} 失败: {}", key, e);
+                deleted = false;
+            }
+        }
+    }
+    deleted
+}
+
+/// 定期删除超过宽限期的旧头像文件
+///
+/// 待删除的版本记录在数据库中，服务重启后仍会继续清理；文件删除失败时保留记录，下次重试。
+pub struct AvatarCleanup {
+    user_repo: Arc<UserRepository>,
+    blob_store: Arc<dyn BlobStore>,
+}
+
+impl AvatarCleanup {
+    pub fn new(user_repo: Arc<UserRepository>, blob_store: Arc<dyn BlobStore>) -> Self {
+        Self {
+            user_repo,
+            blob_store,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.sweep().await {
+                eprintln!("清理旧头像失败: {}", e);
+            }
+        }
+    }
+
+    async fn sweep(&self) -> Result<(), sqlx::Error> {
+        let before = chrono::Utc::now() - AVATAR_GRACE_PERIOD;
+        loop {
+            let retired = self
+                .user_repo
+                .find_retired_avatars(before, SWEEP_BATCH_SIZE)
+                .await?;
+            let batch_size = retired.len() as i64;
+
+            let mut failed = false;
+            for avatar in retired {
+                if delete_avatar_files(&self.blob_store, &avatar.user_id, &avatar.version).await {
+                    self.user_repo
+                        .delete_retired_avatar(&avatar.user_id, &avatar.version)
+                        .await?;
+                } else {
+                    failed = true;
+                }
+            }
+
+            // 有删除失败的记录时停止本轮，避免反复查到同一批
+            if failed || batch_size < SWEEP_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::MessagePurger;
+use crate::database::MessageRepository;
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 检查过期消息的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+/// 每批删除的过期消息数
+const SWEEP_BATCH_SIZE: i64 = 100;
+
+/// 定期删除已过期的消息并通知房间
+///
+/// 历史查询本身会过滤过期消息，清理慢一些也不会把过期内容返回给客户端。
+pub struct ExpirySweeper {
+    message_repo: Arc<MessageRepository>,
+    purger: MessagePurger,
+    event_bus: RoomEventBus,
+}
+
+impl ExpirySweeper {
+    pub fn new(
+        message_repo: Arc<MessageRepository>,
+        purger: MessagePurger,
+        event_bus: RoomEventBus,
+    ) -> Self {
+        Self {
+            message_repo,
+            purger,
+            event_bus,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.sweep().await {
+                eprintln!("清理过期消息失败: {}", e);
+            }
+        }
+    }
+
+    async fn sweep(&self) -> Result<(), sqlx::Error> {
+        loop {
+            let expired = self.message_repo.find_expired(SWEEP_BATCH_SIZE).await?;
+            let batch_size = expired.len() as i64;
+            if batch_size == 0 {
+                return Ok(());
+            }
+
+            for message in self.purger.purge(expired).await? {
+                let event = WebSocketMessage::MessageDeleted {
+                    room_id: message.room_id.clone(),
+                    message_id: message.id.clone(),
+                };
+                if let Err(e) = self.event_bus.publish(&message.room_id, event).await {
+                    eprintln!("广播过期消息删除失败: {}", e);
+                }
+            }
+
+            if batch_size < SWEEP_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod avatars;
+pub mod expiry;
+pub mod purger;
+pub mod retention;
+
+pub use avatars::*;
+pub use expiry::*;
+pub use purger::*;
+pub use retention::*;
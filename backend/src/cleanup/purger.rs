@@ -0,0 +1,64 @@
+use crate::database::{AttachmentRepository, MessageRepository};
+use crate::models::{Attachment, Message};
+use crate::storage::{BlobStore, StorageError};
+use std::sync::Arc;
+
+/// 删除消息及其附件文件
+///
+/// 附件、链接预览等数据库记录随外键级联删除，存储后端中的文件需要单独删除。
+/// 先删文件再删消息，中途失败时下次重试即可，不会留下找不到的文件。
+#[derive(Clone)]
+pub struct MessagePurger {
+    message_repo: Arc<MessageRepository>,
+    attachment_repo: Arc<AttachmentRepository>,
+    blob_store: Arc<dyn BlobStore>,
+}
+
+impl MessagePurger {
+    pub fn new(
+        message_repo: Arc<MessageRepository>,
+        attachment_repo: Arc<AttachmentRepository>,
+        blob_store: Arc<dyn BlobStore>,
+    ) -> Self {
+        Self {
+            message_repo,
+            attachment_repo,
+            blob_store,
+        }
+    }
+
+    /// 返回实际删除的消息，已被其他实例删除的不包含在内
+    pub async fn purge(&self, messages: Vec<Message>) -> Result<Vec<Message>, sqlx::Error> {
+        let message_ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+        for attachment in self
+            .attachment_repo
+            .find_by_message_ids(&message_ids)
+            .await?
+        {
+            self.delete_files(&attachment).await;
+        }
+
+        let mut deleted = Vec::with_capacity(messages.len());
+        for message in messages {
+            if self.message_repo.delete(&message.id).await? {
+                deleted.push(message);
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn delete_files(&self, attachment: &Attachment) {
+        let keys = std::iter::once(attachment.storage_key.clone()).chain(
+            attachment
+                .thumbnail_list()
+                .into_iter()
+                .map(|thumbnail| thumbnail.storage_key),
+        );
+        for key in keys {
+            match self.blob_store.delete(&key).await {
+                Ok(()) | Err(StorageError::NotFound(_)) => {}
+                Err(e) => eprintln!("删除附件文件 {} 失败: {}", key, e),
+            }
+        }
+    }
+}
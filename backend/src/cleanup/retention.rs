@@ -0,0 +1,106 @@
+use super::MessagePurger;
+use crate::database::{MessageRepository, RoomRepository};
+use crate::models::{RetentionConfig, RetentionReport, Room};
+use std::sync::Arc;
+
+/// 按房间保留策略删除过期历史
+///
+/// 每批只删除少量消息并在批次之间停顿，避免长时间锁表。
+/// 删除的是很久以前的历史，不逐条广播删除事件，客户端重新加载历史即可。
+#[derive(Clone)]
+pub struct RetentionJob {
+    config: RetentionConfig,
+    room_repo: Arc<RoomRepository>,
+    message_repo: Arc<MessageRepository>,
+    purger: MessagePurger,
+}
+
+impl RetentionJob {
+    pub fn new(
+        config: RetentionConfig,
+        room_repo: Arc<RoomRepository>,
+        message_repo: Arc<MessageRepository>,
+        purger: MessagePurger,
+    ) -> Self {
+        Self {
+            config,
+            room_repo,
+            message_repo,
+            purger,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.purge_all().await {
+                eprintln!("按保留策略清理消息失败: {}", e);
+            }
+        }
+    }
+
+    /// 预演：统计每个房间按当前策略会删除的数据，不做任何修改
+    pub async fn report(&self) -> Result<Vec<RetentionReport>, sqlx::Error> {
+        let mut reports = Vec::new();
+        for room in self.room_repo.list_all().await? {
+            let Some((retention_days, source)) = self.config.policy_for(&room) else {
+                continue;
+            };
+            let cutoff = cutoff_for(retention_days);
+            let stats = self.message_repo.count_older_than(&room.id, cutoff).await?;
+            reports.push(RetentionReport {
+                room_id: room.id,
+                room_name: room.name,
+                retention_days,
+                source,
+                cutoff,
+                stats,
+            });
+        }
+        Ok(reports)
+    }
+
+    async fn purge_all(&self) -> Result<(), sqlx::Error> {
+        for room in self.room_repo.list_all().await? {
+            if let Some((retention_days, _)) = self.config.policy_for(&room) {
+                self.purge_room(&room, retention_days).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn purge_room(&self, room: &Room, retention_days: u32) -> Result<(), sqlx::Error> {
+        let cutoff = cutoff_for(retention_days);
+        let mut total = 0;
+        loop {
+            let messages = self
+                .message_repo
+                .find_older_than(&room.id, cutoff, self.config.batch_size)
+                .await?;
+            let batch_size = messages.len() as i64;
+            if batch_size == 0 {
+                break;
+            }
+
+            total += self.purger.purge(messages).await?.len();
+            if batch_size < self.config.batch_size {
+                break;
+            }
+            tokio::time::sleep(self.config.batch_pause).await;
+        }
+
+        if total > 0 {
+            println!(
+                "房间 {} 按保留策略（{}天）删除了 {} 条消息",
+                room.id, retention_days, total
+            );
+        }
+        Ok(())
+    }
+}
+
+fn cutoff_for(retention_days: u32) -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now() - chrono::Duration::days(retention_days as i64)
+}
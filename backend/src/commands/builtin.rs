@@ -0,0 +1,256 @@
+use super::{
+    CommandArg, CommandArgs, CommandContext, CommandError, CommandOutcome, CommandPermission,
+    CommandResult, CommandSpec, RoomNotifier, SlashCommand,
+};
+use crate::audit::AuditLogger;
+use crate::database::{RoomRepository, UserRepository};
+use crate::models::{AuditAction, AuditEntry, UpdateUser};
+use std::sync::Arc;
+
+/// 用户名的最大长度（字符），与users表一致
+const MAX_USERNAME_LENGTH: usize = 50;
+
+static ME: CommandSpec = CommandSpec {
+    name: "me",
+    args: &[CommandArg::text("动作")],
+    permission: CommandPermission::Anyone,
+    posts_notice: false,
+    help: "以第三人称描述自己的动作",
+};
+
+static NICK: CommandSpec = CommandSpec {
+    name: "nick",
+    args: &[CommandArg::required("新昵称")],
+    permission: CommandPermission::Anyone,
+    posts_notice: true,
+    help: "修改自己的昵称",
+};
+
+static HELP: CommandSpec = CommandSpec {
+    name: "help",
+    args: &[CommandArg::optional("命令")],
+    permission: CommandPermission::Anyone,
+    posts_notice: false,
+    help: "列出可用命令，或查看某个命令的用法",
+};
+
+static WHOIS: CommandSpec = CommandSpec {
+    name: "whois",
+    args: &[CommandArg::required("用户名")],
+    permission: CommandPermission::Anyone,
+    posts_notice: false,
+    help: "查看用户的资料和在线状态",
+};
+
+/// 去掉用户名前面的@
+pub(super) fn normalize_username(name: &str) -> &str {
+    name.strip_prefix('@').unwrap_or(name)
+}
+
+/// /me，改写为斜体的动作描述后按普通消息发送
+pub struct MeCommand;
+
+#[async_trait::async_trait]
+impl SlashCommand for MeCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &ME
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        Ok(CommandOutcome::Send(format!(
+            "*{} {}*",
+            context.user.username,
+            args.required(0)
+        )))
+    }
+}
+
+/// /nick，修改昵称并在当前房间通知
+pub struct NickCommand {
+    user_repo: Arc<UserRepository>,
+    notifier: RoomNotifier,
+    audit_logger: AuditLogger,
+}
+
+impl NickCommand {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        notifier: RoomNotifier,
+        audit_logger: AuditLogger,
+    ) -> Self {
+        Self {
+            user_repo,
+            notifier,
+            audit_logger,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashCommand for NickCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &NICK
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        let username = args.required(0);
+        if username.chars().count() > MAX_USERNAME_LENGTH {
+            return Err(CommandError::Invalid(format!(
+                "昵称不能超过{}个字符",
+                MAX_USERNAME_LENGTH
+            )));
+        }
+        if username == context.user.username {
+            return Err(CommandError::Invalid("新昵称与当前昵称相同".to_string()));
+        }
+        if self.user_repo.find_by_username(username).await?.is_some() {
+            return Err(CommandError::Invalid("昵称已被使用".to_string()));
+        }
+
+        let update = UpdateUser {
+            username: Some(username.to_string()),
+        };
+        match self.user_repo.update(&context.user.id, update).await {
+            Ok(_) => {}
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(CommandError::Invalid("昵称已被使用".to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::ProfileUpdated)
+                .actor(&context.user.id)
+                .target("user", &context.user.id)
+                .ip(context.ip)
+                .details(serde_json::json!({
+                    "fields": ["username"],
+                    "username": username,
+                    "via": "command",
+                })),
+        );
+        self.notifier
+            .notify(
+                &context.room.id,
+                format!("{} 改名为 {}", context.user.username, username),
+            )
+            .await;
+
+        Ok(CommandOutcome::Reply(format!("昵称已改为 {}", username)))
+    }
+}
+
+/// /help，只列出当前用户在这个房间可以执行的命令
+pub struct HelpCommand;
+
+#[async_trait::async_trait]
+impl SlashCommand for HelpCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &HELP
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        if let Some(name) = args.get(0) {
+            let name = name.trim_start_matches('/').to_lowercase();
+            let command = context
+                .registry
+                .find(&name)
+                .ok_or(CommandError::Unknown(name))?;
+            let spec = command.spec();
+            return Ok(CommandOutcome::Reply(format!(
+                "{}\n{}",
+                spec.usage(),
+                spec.help
+            )));
+        }
+
+        let lines: Vec<String> = context
+            .registry
+            .specs()
+            .into_iter()
+            .filter(|spec| spec.permission.allows(context.user, context.room))
+            .map(|spec| format!("{} - {}", spec.usage(), spec.help))
+            .collect();
+        Ok(CommandOutcome::Reply(format!(
+            "可用命令:\n{}\n以//开头的消息会作为普通消息发送",
+            lines.join("\n")
+        )))
+    }
+}
+
+/// /whois，查看用户资料，在线状态按隐身规则对外显示
+pub struct WhoisCommand {
+    user_repo: Arc<UserRepository>,
+    room_repo: Arc<RoomRepository>,
+}
+
+impl WhoisCommand {
+    pub fn new(user_repo: Arc<UserRepository>, room_repo: Arc<RoomRepository>) -> Self {
+        Self {
+            user_repo,
+            room_repo,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashCommand for WhoisCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &WHOIS
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        let username = normalize_username(args.required(0));
+        let user = self
+            .user_repo
+            .find_by_username(username)
+            .await?
+            .ok_or_else(|| CommandError::Invalid(format!("用户不存在: {}", username)))?;
+        let public = user.to_public();
+        let is_member = self.room_repo.is_member(&user.id, &context.room.id).await?;
+
+        let mut lines = vec![
+            format!("{}（{}）", user.username, user.role().as_str()),
+            format!("状态: {}", public.status),
+            format!(
+                "注册时间: {}",
+                user.created_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+            ),
+            format!("本房间成员: {}", if is_member { "是" } else { "否" }),
+        ];
+        if let Some(last_seen_at) = user.last_seen_at.filter(|_| public.status == "offline") {
+            lines.push(format!(
+                "最后在线: {}",
+                last_seen_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+            ));
+        }
+        // 封禁信息只对版主和管理员显示
+        if context.user.is_moderator() && user.is_banned() {
+            lines.push(format!(
+                "已封禁: {}",
+                user.ban_reason.as_deref().unwrap_or_default()
+            ));
+        }
+        Ok(CommandOutcome::Reply(lines.join("\n")))
+    }
+}
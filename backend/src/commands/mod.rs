@@ -0,0 +1,9 @@
+pub mod builtin;
+pub mod moderation;
+pub mod notice;
+pub mod registry;
+
+pub use builtin::*;
+pub use moderation::*;
+pub use notice::*;
+pub use registry::*;
@@ -0,0 +1,346 @@
+use super::builtin::normalize_username;
+use super::{
+    CommandArg, CommandArgs, CommandContext, CommandError, CommandOutcome, CommandPermission,
+    CommandResult, CommandSpec, RoomNotifier, SlashCommand,
+};
+use crate::audit::AuditLogger;
+use crate::database::{ModerationRepository, RoomRepository, UserRepository};
+use crate::models::{
+    AuditAction, AuditEntry, MAX_MUTE_SECONDS, MuteSource, User, UserMute, UserRole,
+};
+use crate::redis::SessionManager;
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+
+/// 话题的最大长度（字符）
+const MAX_TOPIC_LENGTH: usize = 200;
+
+static TOPIC: CommandSpec = CommandSpec {
+    name: "topic",
+    args: &[CommandArg::text("话题")],
+    permission: CommandPermission::RoomModerator,
+    posts_notice: true,
+    help: "修改房间话题",
+};
+
+static KICK: CommandSpec = CommandSpec {
+    name: "kick",
+    args: &[
+        CommandArg::required("用户名"),
+        CommandArg::optional_text("原因"),
+    ],
+    permission: CommandPermission::RoomModerator,
+    posts_notice: true,
+    help: "把用户移出房间",
+};
+
+static MUTE: CommandSpec = CommandSpec {
+    name: "mute",
+    args: &[
+        CommandArg::required("用户名"),
+        CommandArg::required("时长"),
+        CommandArg::optional_text("原因"),
+    ],
+    permission: CommandPermission::RoomModerator,
+    posts_notice: true,
+    help: "在本房间禁言用户，时长如30s、10m、2h、1d",
+};
+
+/// 查找处罚对象：不能是自己和房间创建者，版主和管理员只能由管理员处罚
+async fn find_target(
+    user_repo: &UserRepository,
+    context: &CommandContext<'_>,
+    username: &str,
+) -> CommandResult<User> {
+    let username = normalize_username(username);
+    let target = user_repo
+        .find_by_username(username)
+        .await?
+        .ok_or_else(|| CommandError::Invalid(format!("用户不存在: {}", username)))?;
+    if target.id == context.user.id {
+        return Err(CommandError::Invalid("不能对自己执行该命令".to_string()));
+    }
+    if target.id == context.room.created_by {
+        return Err(CommandError::Invalid("不能处罚房间创建者".to_string()));
+    }
+    if target.is_moderator() && context.user.role() != UserRole::Admin {
+        return Err(CommandError::Forbidden);
+    }
+    Ok(target)
+}
+
+/// 解析禁言时长，支持s、m、h、d后缀，没有后缀按秒处理
+fn parse_duration(value: &str) -> Option<i64> {
+    let (number, unit) = match value.char_indices().last()? {
+        (index, unit) if unit.is_ascii_alphabetic() => (&value[..index], unit),
+        _ => (value, 's'),
+    };
+    let multiplier = match unit.to_ascii_lowercase() {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    number.parse::<i64>().ok()?.checked_mul(multiplier)
+}
+
+/// 把秒数格式化为便于阅读的时长
+fn format_duration(seconds: i64) -> String {
+    match seconds {
+        s if s % 86400 == 0 => format!("{}天", s / 86400),
+        s if s % 3600 == 0 => format!("{}小时", s / 3600),
+        s if s % 60 == 0 => format!("{}分钟", s / 60),
+        s => format!("{}秒", s),
+    }
+}
+
+/// /topic，修改房间描述并在房间内通知
+pub struct TopicCommand {
+    room_repo: Arc<RoomRepository>,
+    notifier: RoomNotifier,
+    audit_logger: AuditLogger,
+}
+
+impl TopicCommand {
+    pub fn new(
+        room_repo: Arc<RoomRepository>,
+        notifier: RoomNotifier,
+        audit_logger: AuditLogger,
+    ) -> Self {
+        Self {
+            room_repo,
+            notifier,
+            audit_logger,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashCommand for TopicCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &TOPIC
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        let topic = args.required(0);
+        if topic.chars().count() > MAX_TOPIC_LENGTH {
+            return Err(CommandError::Invalid(format!(
+                "话题不能超过{}个字符",
+                MAX_TOPIC_LENGTH
+            )));
+        }
+
+        self.room_repo
+            .set_description(&context.room.id, topic)
+            .await?;
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::TopicChanged)
+                .actor(&context.user.id)
+                .target("room", &context.room.id)
+                .ip(context.ip)
+                .details(serde_json::json!({
+                    "old": context.room.description,
+                    "new": topic,
+                })),
+        );
+        self.notifier
+            .notify(
+                &context.room.id,
+                format!("{} 把话题修改为: {}", context.user.username, topic),
+            )
+            .await;
+
+        Ok(CommandOutcome::Reply("话题已修改".to_string()))
+    }
+}
+
+/// /kick，移出房间成员并断开其在该房间的会话
+pub struct KickCommand {
+    user_repo: Arc<UserRepository>,
+    room_repo: Arc<RoomRepository>,
+    session_manager: Arc<SessionManager>,
+    event_bus: RoomEventBus,
+    notifier: RoomNotifier,
+    audit_logger: AuditLogger,
+}
+
+impl KickCommand {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        room_repo: Arc<RoomRepository>,
+        session_manager: Arc<SessionManager>,
+        event_bus: RoomEventBus,
+        notifier: RoomNotifier,
+        audit_logger: AuditLogger,
+    ) -> Self {
+        Self {
+            user_repo,
+            room_repo,
+            session_manager,
+            event_bus,
+            notifier,
+            audit_logger,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashCommand for KickCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &KICK
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        let target = find_target(&self.user_repo, context, args.required(0)).await?;
+        let reason = args.get(1).unwrap_or_default().to_string();
+
+        if !self
+            .room_repo
+            .remove_member(&context.room.id, &target.id)
+            .await?
+        {
+            return Err(CommandError::Invalid(format!(
+                "{} 不是本房间成员",
+                target.username
+            )));
+        }
+        if let Err(e) = self
+            .session_manager
+            .remove_user_from_room(&target.id, &context.room.id)
+            .await
+        {
+            eprintln!("移除房间会话失败: {}", e);
+        }
+
+        // 被移出的用户收到事件后离开房间
+        let event = WebSocketMessage::UserKicked {
+            room_id: context.room.id.clone(),
+            user_id: target.id.clone(),
+            kicked_by: context.user.id.clone(),
+            reason: reason.clone(),
+        };
+        if let Err(e) = self.event_bus.publish(&context.room.id, event).await {
+            eprintln!("广播移出成员失败: {}", e);
+        }
+
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::MemberKicked)
+                .actor(&context.user.id)
+                .target("user", &target.id)
+                .ip(context.ip)
+                .details(serde_json::json!({
+                    "room_id": context.room.id,
+                    "reason": reason,
+                })),
+        );
+        let notice = if reason.is_empty() {
+            format!("{} 被 {} 移出房间", target.username, context.user.username)
+        } else {
+            format!(
+                "{} 被 {} 移出房间，原因: {}",
+                target.username, context.user.username, reason
+            )
+        };
+        self.notifier.notify(&context.room.id, notice).await;
+
+        Ok(CommandOutcome::Reply(format!(
+            "已将 {} 移出房间",
+            target.username
+        )))
+    }
+}
+
+/// /mute，在当前房间禁言用户
+pub struct MuteCommand {
+    user_repo: Arc<UserRepository>,
+    moderation_repo: Arc<ModerationRepository>,
+    notifier: RoomNotifier,
+    audit_logger: AuditLogger,
+}
+
+impl MuteCommand {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        moderation_repo: Arc<ModerationRepository>,
+        notifier: RoomNotifier,
+        audit_logger: AuditLogger,
+    ) -> Self {
+        Self {
+            user_repo,
+            moderation_repo,
+            notifier,
+            audit_logger,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashCommand for MuteCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &MUTE
+    }
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome> {
+        let target = find_target(&self.user_repo, context, args.required(0)).await?;
+        let seconds = parse_duration(args.required(1))
+            .filter(|seconds| (1..=MAX_MUTE_SECONDS).contains(seconds))
+            .ok_or_else(|| {
+                CommandError::Invalid(format!(
+                    "禁言时长必须在1秒到{}之间，如30s、10m、2h、1d",
+                    format_duration(MAX_MUTE_SECONDS)
+                ))
+            })?;
+
+        let mute = UserMute::new(
+            target.id.clone(),
+            Some(context.room.id.clone()),
+            args.get(2).unwrap_or_default().to_string(),
+            MuteSource::Moderator,
+            context.user.id.clone(),
+            chrono::Duration::seconds(seconds),
+        );
+        self.moderation_repo.create_mute(&mute).await?;
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::UserMuted)
+                .actor(&context.user.id)
+                .target("user", &target.id)
+                .ip(context.ip)
+                .details(serde_json::json!({
+                    "mute_id": mute.id,
+                    "room_id": context.room.id,
+                    "seconds": seconds,
+                    "reason": mute.reason,
+                    "via": "command",
+                })),
+        );
+
+        let duration = format_duration(seconds);
+        self.notifier
+            .notify(
+                &context.room.id,
+                format!(
+                    "{} 被 {} 禁言{}",
+                    target.username, context.user.username, duration
+                ),
+            )
+            .await;
+
+        Ok(CommandOutcome::Reply(format!(
+            "已禁言 {} {}",
+            target.username, duration
+        )))
+    }
+}
@@ -0,0 +1,57 @@
+use crate::database::MessageRepository;
+use crate::models::{Message, MessageType, SYSTEM_ACTOR};
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+
+/// 以系统用户身份在房间中发送通知，如改名、修改话题和移出成员
+#[derive(Clone)]
+pub struct RoomNotifier {
+    message_repo: Arc<MessageRepository>,
+    event_bus: RoomEventBus,
+}
+
+impl RoomNotifier {
+    pub fn new(message_repo: Arc<MessageRepository>, event_bus: RoomEventBus) -> Self {
+        Self {
+            message_repo,
+            event_bus,
+        }
+    }
+
+    /// 通知保存为系统消息，发送失败只记录日志
+    pub async fn notify(&self, room_id: &str, content: String) {
+        let message = Message::new(
+            SYSTEM_ACTOR.to_string(),
+            SYSTEM_ACTOR.to_string(),
+            content,
+            room_id.to_string(),
+            MessageType::System,
+        );
+        let message = match self.message_repo.create(message).await {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("保存房间通知失败: {}", e);
+                return;
+            }
+        };
+
+        let event = WebSocketMessage::ChatMessage {
+            message_id: Some(message.id.clone()),
+            room_id: message.room_id.clone(),
+            user_id: message.user_id.clone(),
+            username: message.username.clone(),
+            content: message.content.clone(),
+            content_html: message.content_html.clone(),
+            message_type: message.message_type.to_string(),
+            attachment_ids: Vec::new(),
+            attachments: Vec::new(),
+            poll: None,
+            ttl_seconds: None,
+            expires_at: message.expires_at.map(|t| t.timestamp()),
+            reference: None,
+        };
+        if let Err(e) = self.event_bus.publish(&message.room_id, event).await {
+            eprintln!("广播房间通知失败: {}", e);
+        }
+    }
+}
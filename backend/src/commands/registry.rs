@@ -0,0 +1,312 @@
+use crate::database::RoomRepository;
+use crate::models::{Room, User};
+use crate::moderation::{SendGuard, SendRejection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// 命令前缀，以两个斜杠开头的消息按普通消息发送并去掉一个斜杠
+pub const COMMAND_PREFIX: char = '/';
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("未知命令: /{0}，输入 /help 查看可用命令")]
+    Unknown(String),
+    #[error("用法: {0}")]
+    Usage(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("没有权限执行该命令")]
+    Forbidden,
+    #[error("{}", .0.message())]
+    Rejected(SendRejection),
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+/// 执行命令需要的权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandPermission {
+    Anyone,
+    /// 房间创建者、版主和管理员
+    RoomModerator,
+}
+
+impl CommandPermission {
+    pub fn allows(&self, user: &User, room: &Room) -> bool {
+        match self {
+            CommandPermission::Anyone => true,
+            CommandPermission::RoomModerator => room.created_by == user.id || user.is_moderator(),
+        }
+    }
+}
+
+/// 命令参数的声明
+#[derive(Debug, Clone, Copy)]
+pub struct CommandArg {
+    pub name: &'static str,
+    pub required: bool,
+    /// 接收剩余的全部文本，只能是最后一个参数
+    pub rest: bool,
+}
+
+impl CommandArg {
+    pub const fn required(name: &'static str) -> Self {
+        Self {
+            name,
+            required: true,
+            rest: false,
+        }
+    }
+
+    pub const fn optional(name: &'static str) -> Self {
+        Self {
+            name,
+            required: false,
+            rest: false,
+        }
+    }
+
+    pub const fn text(name: &'static str) -> Self {
+        Self {
+            name,
+            required: true,
+            rest: true,
+        }
+    }
+
+    pub const fn optional_text(name: &'static str) -> Self {
+        Self {
+            name,
+            required: false,
+            rest: true,
+        }
+    }
+}
+
+/// 命令的声明：名称、参数、权限和帮助文本
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub args: &'static [CommandArg],
+    pub permission: CommandPermission,
+    /// 执行时会在房间中发送通知，需要和普通消息一样检查禁言和慢速模式
+    pub posts_notice: bool,
+    pub help: &'static str,
+}
+
+impl CommandSpec {
+    /// 用法说明，如`/kick <用户名> [原因]`
+    pub fn usage(&self) -> String {
+        let mut usage = format!("{}{}", COMMAND_PREFIX, self.name);
+        for arg in self.args {
+            if arg.required {
+                usage.push_str(&format!(" <{}>", arg.name));
+            } else {
+                usage.push_str(&format!(" [{}]", arg.name));
+            }
+        }
+        usage
+    }
+
+    /// 按声明解析参数，必填参数缺失或参数过多时返回用法说明
+    fn parse_args(&self, input: &str) -> CommandResult<CommandArgs> {
+        let mut values = Vec::with_capacity(self.args.len());
+        let mut remaining = input.trim();
+        for arg in self.args {
+            let value = if arg.rest {
+                std::mem::take(&mut remaining)
+            } else {
+                let (word, rest) = remaining
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((remaining, ""));
+                remaining = rest.trim_start();
+                word
+            };
+
+            if value.is_empty() {
+                if arg.required {
+                    return Err(CommandError::Usage(self.usage()));
+                }
+                values.push(None);
+            } else {
+                values.push(Some(value.to_string()));
+            }
+        }
+
+        if !remaining.is_empty() {
+            return Err(CommandError::Usage(self.usage()));
+        }
+        Ok(CommandArgs(values))
+    }
+}
+
+/// 解析后的参数，按声明顺序排列，没有提供的可选参数为None
+#[derive(Debug)]
+pub struct CommandArgs(Vec<Option<String>>);
+
+impl CommandArgs {
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.0.get(index).and_then(|value| value.as_deref())
+    }
+
+    /// 必填参数，解析时已经检查过
+    pub fn required(&self, index: usize) -> &str {
+        self.get(index).unwrap_or_default()
+    }
+}
+
+/// 执行命令时的上下文
+pub struct CommandContext<'a> {
+    pub user: &'a User,
+    pub room: &'a Room,
+    pub ip: Option<&'a str>,
+    pub registry: &'a CommandRegistry,
+}
+
+/// 命令的执行结果
+#[derive(Debug)]
+pub enum CommandOutcome {
+    /// 只回复给执行者
+    Reply(String),
+    /// 把改写后的内容作为普通消息继续发送，如/me
+    Send(String),
+}
+
+/// 斜杠命令，通过`CommandRegistry::register`注册
+#[async_trait::async_trait]
+pub trait SlashCommand: Send + Sync {
+    fn spec(&self) -> &'static CommandSpec;
+
+    async fn execute(
+        &self,
+        context: &CommandContext<'_>,
+        args: CommandArgs,
+    ) -> CommandResult<CommandOutcome>;
+}
+
+/// 回复给执行者的命令结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandReply {
+    pub command: String,
+    pub message: String,
+}
+
+/// 消息经过命令拦截后的处理方式
+#[derive(Debug)]
+pub enum CommandDispatch {
+    /// 按普通消息继续发送
+    Message(String),
+    /// 命令已处理，结果只回复给发送者
+    Reply(CommandReply),
+}
+
+/// 斜杠命令注册表，WebSocket、HTTP和gRPC发送消息前共用
+///
+/// 内置命令由`EventHandlerFactory`注册，注册表可以克隆，克隆之间共享已注册的命令。
+#[derive(Clone)]
+pub struct CommandRegistry {
+    commands: Arc<RwLock<HashMap<&'static str, Arc<dyn SlashCommand>>>>,
+    room_repo: Arc<RoomRepository>,
+    send_guard: SendGuard,
+}
+
+impl CommandRegistry {
+    pub fn new(room_repo: Arc<RoomRepository>, send_guard: SendGuard) -> Self {
+        Self {
+            commands: Arc::new(RwLock::new(HashMap::new())),
+            room_repo,
+            send_guard,
+        }
+    }
+
+    /// 注册命令，同名命令会被替换
+    pub fn register(&self, command: impl SlashCommand + 'static) {
+        let name = command.spec().name;
+        if let Ok(mut commands) = self.commands.write() {
+            commands.insert(name, Arc::new(command));
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<Arc<dyn SlashCommand>> {
+        self.commands
+            .read()
+            .ok()
+            .and_then(|commands| commands.get(name).cloned())
+    }
+
+    /// 已注册命令的声明，按名称排序
+    pub fn specs(&self) -> Vec<&'static CommandSpec> {
+        let mut specs: Vec<&'static CommandSpec> = self
+            .commands
+            .read()
+            .map(|commands| commands.values().map(|command| command.spec()).collect())
+            .unwrap_or_default();
+        specs.sort_by_key(|spec| spec.name);
+        specs
+    }
+
+    /// 拦截以/开头的消息并执行对应命令，其他消息原样返回
+    ///
+    /// 已封禁的用户不能执行命令，命令按用户限流。
+    pub async fn dispatch(
+        &self,
+        user: &User,
+        room_id: &str,
+        content: &str,
+        ip: Option<&str>,
+    ) -> CommandResult<CommandDispatch> {
+        let Some(text) = content.strip_prefix(COMMAND_PREFIX) else {
+            return Ok(CommandDispatch::Message(content.to_string()));
+        };
+        if text.starts_with(COMMAND_PREFIX) {
+            return Ok(CommandDispatch::Message(text.to_string()));
+        }
+        let (name, input) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        if name.is_empty() {
+            return Ok(CommandDispatch::Message(content.to_string()));
+        }
+
+        let name = name.to_lowercase();
+        let command = self
+            .find(&name)
+            .ok_or_else(|| CommandError::Unknown(name.clone()))?;
+        let spec = command.spec();
+        if let Some(rejection) = self
+            .send_guard
+            .check_command(user, room_id, spec.posts_notice)
+            .await?
+        {
+            return Err(CommandError::Rejected(rejection));
+        }
+        let room = self
+            .room_repo
+            .find_by_id(room_id)
+            .await?
+            .ok_or_else(|| CommandError::Invalid("房间不存在".to_string()))?;
+        if !spec.permission.allows(user, &room) {
+            return Err(CommandError::Forbidden);
+        }
+        let args = spec.parse_args(input)?;
+
+        println!(
+            "用户 {} 在房间 {} 执行命令 /{}",
+            user.id, room.id, spec.name
+        );
+        let context = CommandContext {
+            user,
+            room: &room,
+            ip,
+            registry: self,
+        };
+        Ok(match command.execute(&context, args).await? {
+            CommandOutcome::Send(content) => CommandDispatch::Message(content),
+            CommandOutcome::Reply(message) => CommandDispatch::Reply(CommandReply {
+                command: spec.name.to_string(),
+                message,
+            }),
+        })
+    }
+}
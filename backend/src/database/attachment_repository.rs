@@ -0,0 +1,122 @@
+use crate::database::DbPool;
+use crate::models::Attachment;
+use sqlx::Error;
+
+pub struct AttachmentRepository {
+    pool: DbPool,
+}
+
+impl AttachmentRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, attachment: &Attachment) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO attachments (id, room_id, uploader_id, message_id, file_name, mime_type, size_bytes, checksum, storage_key, created_at, width, height, thumbnails)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            attachment.id,
+            attachment.room_id,
+            attachment.uploader_id,
+            attachment.message_id,
+            attachment.file_name,
+            attachment.mime_type,
+            attachment.size_bytes,
+            attachment.checksum,
+            attachment.storage_key,
+            attachment.created_at,
+            attachment.width,
+            attachment.height,
+            attachment.thumbnails
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<Attachment>, Error> {
+        let attachment = sqlx::query_as!(Attachment, "SELECT * FROM attachments WHERE id = ?", id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(attachment)
+    }
+
+    /// 查找用户在该房间上传、尚未发送的附件
+    ///
+    /// 任意一个ID不满足条件时返回None，调用方应拒绝发送消息。
+    pub async fn find_pending(
+        &self,
+        ids: &[String],
+        uploader_id: &str,
+        room_id: &str,
+    ) -> Result<Option<Vec<Attachment>>, Error> {
+        let mut unique_ids = ids.to_vec();
+        unique_ids.sort();
+        unique_ids.dedup();
+
+        let attachments = sqlx::query_as!(
+            Attachment,
+            r#"
+            SELECT * FROM attachments
+            WHERE FIND_IN_SET(id, ?) > 0
+              AND uploader_id = ?
+              AND room_id = ?
+              AND message_id IS NULL
+            "#,
+            unique_ids.join(","),
+            uploader_id,
+            room_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        if attachments.len() == unique_ids.len() {
+            Ok(Some(attachments))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 把附件关联到已发送的消息
+    pub async fn attach_to_message(&self, message_id: &str, ids: &[String]) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE attachments SET message_id = ?
+            WHERE FIND_IN_SET(id, ?) > 0 AND message_id IS NULL
+            "#,
+            message_id,
+            ids.join(",")
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_message_ids(
+        &self,
+        message_ids: &[String],
+    ) -> Result<Vec<Attachment>, Error> {
+        if message_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let attachments = sqlx::query_as!(
+            Attachment,
+            r#"
+            SELECT * FROM attachments
+            WHERE FIND_IN_SET(message_id, ?) > 0
+            ORDER BY created_at ASC
+            "#,
+            message_ids.join(",")
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(attachments)
+    }
+}
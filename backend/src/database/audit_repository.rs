@@ -0,0 +1,94 @@
+use crate::database::DbPool;
+use crate::models::{AuditEntry, AuditQuery};
+use sqlx::Error;
+
+/// 审计日志只提供写入和查询，不提供修改和删除
+pub struct AuditRepository {
+    pool: DbPool,
+}
+
+impl AuditRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 在一个事务中批量写入
+    pub async fn create_batch(&self, entries: &[AuditEntry]) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            sqlx::query!(
+                r#"
+                INSERT INTO audit_log (id, action, actor_id, target_type, target_id, ip, details, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                entry.id,
+                entry.action,
+                entry.actor_id,
+                entry.target_type,
+                entry.target_id,
+                entry.ip,
+                entry.details,
+                entry.created_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// 按条件查询，结果按时间从新到旧排列，before_id之前（更早）的记录
+    pub async fn query(&self, query: &AuditQuery, limit: i64) -> Result<Vec<AuditEntry>, Error> {
+        let before = match query.before_id.as_deref() {
+            Some(id) => {
+                sqlx::query_scalar!("SELECT created_at FROM audit_log WHERE id = ?", id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            None => None,
+        };
+        let from = query
+            .from
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+        let to = query
+            .to
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+
+        let entries = sqlx::query_as!(
+            AuditEntry,
+            r#"
+            SELECT * FROM audit_log
+            WHERE (? IS NULL OR action = ?)
+              AND (? IS NULL OR actor_id = ?)
+              AND (? IS NULL OR target_id = ?)
+              AND (? IS NULL OR ip = ?)
+              AND (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR (created_at, id) < (?, ?))
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+            query.action,
+            query.action,
+            query.actor_id,
+            query.actor_id,
+            query.target_id,
+            query.target_id,
+            query.ip,
+            query.ip,
+            from,
+            from,
+            to,
+            to,
+            before,
+            before,
+            query.before_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
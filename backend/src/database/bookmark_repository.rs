@@ -0,0 +1,101 @@
+use crate::database::DbPool;
+use crate::models::Bookmark;
+use sqlx::Error;
+
+pub struct BookmarkRepository {
+    pool: DbPool,
+}
+
+impl BookmarkRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 保存收藏，已收藏时只更新备注
+    pub async fn save(&self, bookmark: &Bookmark) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO bookmarks (id, user_id, message_id, room_id, note, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE note = VALUES(note)
+            "#,
+            bookmark.id,
+            bookmark.user_id,
+            bookmark.message_id,
+            bookmark.room_id,
+            bookmark.note,
+            bookmark.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find(&self, user_id: &str, message_id: &str) -> Result<Option<Bookmark>, Error> {
+        let bookmark = sqlx::query_as!(
+            Bookmark,
+            "SELECT * FROM bookmarks WHERE user_id = ? AND message_id = ?",
+            user_id,
+            message_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(bookmark)
+    }
+
+    /// 取消收藏，没有收藏时返回false
+    pub async fn delete(&self, user_id: &str, message_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmarks WHERE user_id = ? AND message_id = ?",
+            user_id,
+            message_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 用户的收藏，最近收藏的在前，room_id为空时不限房间
+    pub async fn list_by_user(
+        &self,
+        user_id: &str,
+        room_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Bookmark>, Error> {
+        let bookmarks = sqlx::query_as!(
+            Bookmark,
+            r#"
+            SELECT * FROM bookmarks
+            WHERE user_id = ? AND (? IS NULL OR room_id = ?)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+            user_id,
+            room_id,
+            room_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(bookmarks)
+    }
+
+    pub async fn count_by_user(&self, user_id: &str, room_id: Option<&str>) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM bookmarks WHERE user_id = ? AND (? IS NULL OR room_id = ?)",
+            user_id,
+            room_id,
+            room_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+}
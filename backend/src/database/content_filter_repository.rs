@@ -0,0 +1,80 @@
+use crate::database::DbPool;
+use crate::models::{MessageFlag, RoomFilterRule};
+use sqlx::Error;
+
+pub struct ContentFilterRepository {
+    pool: DbPool,
+}
+
+impl ContentFilterRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_room_rules(&self, room_id: &str) -> Result<Vec<RoomFilterRule>, Error> {
+        let rules = sqlx::query_as!(
+            RoomFilterRule,
+            "SELECT * FROM room_filter_rules WHERE room_id = ? ORDER BY created_at ASC",
+            room_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// 用新的规则整体替换房间规则
+    pub async fn replace_room_rules(
+        &self,
+        room_id: &str,
+        rules: &[RoomFilterRule],
+    ) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM room_filter_rules WHERE room_id = ?", room_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for rule in rules {
+            sqlx::query!(
+                r#"
+                INSERT INTO room_filter_rules (id, room_id, name, kind, pattern, action, reason, enabled, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                rule.id,
+                rule.room_id,
+                rule.name,
+                rule.kind,
+                rule.pattern,
+                rule.action,
+                rule.reason,
+                rule.enabled,
+                rule.created_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn create_flag(&self, flag: &MessageFlag) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO message_flags (id, message_id, room_id, user_id, rule_name, reason, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            flag.id,
+            flag.message_id,
+            flag.room_id,
+            flag.user_id,
+            flag.rule_name,
+            flag.reason,
+            flag.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::database::DbPool;
+use crate::models::ExportJob;
+use sqlx::Error;
+
+pub struct ExportJobRepository {
+    pool: DbPool,
+}
+
+impl ExportJobRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, job: &ExportJob) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO export_jobs (id, room_id, requested_by, format, from_time, to_time, status, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            job.id,
+            job.room_id,
+            job.requested_by,
+            job.format,
+            job.from_time,
+            job.to_time,
+            job.status,
+            job.created_at,
+            job.updated_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<ExportJob>, Error> {
+        let job = sqlx::query_as!(ExportJob, "SELECT * FROM export_jobs WHERE id = ?", id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(job)
+    }
+
+    /// 最早提交的待处理任务
+    pub async fn find_next_pending(&self) -> Result<Option<ExportJob>, Error> {
+        let job = sqlx::query_as!(
+            ExportJob,
+            r#"
+            SELECT * FROM export_jobs
+            WHERE status = 'pending'
+            ORDER BY created_at ASC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(job)
+    }
+
+    /// 认领任务，多个实例同时认领时只有一个会成功
+    pub async fn claim(
+        &self,
+        id: &str,
+        instance_id: &str,
+        total_messages: i64,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE export_jobs
+            SET status = 'running', claimed_by = ?, total_messages = ?, processed_messages = 0,
+                part_count = 0, size_bytes = 0
+            WHERE id = ? AND status = 'pending'
+            "#,
+            instance_id,
+            total_messages,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 更新进度，同时刷新updated_at作为心跳
+    pub async fn update_progress(
+        &self,
+        id: &str,
+        processed_messages: i64,
+        part_count: i32,
+        size_bytes: i64,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE export_jobs SET processed_messages = ?, part_count = ?, size_bytes = ?
+            WHERE id = ? AND status = 'running'
+            "#,
+            processed_messages,
+            part_count,
+            size_bytes,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_completed(
+        &self,
+        id: &str,
+        processed_messages: i64,
+        part_count: i32,
+        size_bytes: i64,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE export_jobs
+            SET status = 'completed', processed_messages = ?, part_count = ?, size_bytes = ?,
+                completed_at = ?
+            WHERE id = ? AND status = 'running'
+            "#,
+            processed_messages,
+            part_count,
+            size_bytes,
+            chrono::Utc::now(),
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_failed(&self, id: &str, error: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE export_jobs SET status = 'failed', error = ? WHERE id = ? AND status = 'running'",
+            error,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 心跳超时的运行中任务重新放回队列，通常是实例在导出途中退出
+    pub async fn requeue_stale(
+        &self,
+        updated_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE export_jobs SET status = 'pending', claimed_by = NULL
+            WHERE status = 'running' AND updated_at < ?
+            "#,
+            updated_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 完成时间早于指定时间的任务，文件需要删除
+    pub async fn find_completed_before(
+        &self,
+        completed_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ExportJob>, Error> {
+        let jobs = sqlx::query_as!(
+            ExportJob,
+            r#"
+            SELECT * FROM export_jobs
+            WHERE status = 'completed' AND completed_at < ?
+            "#,
+            completed_before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn mark_expired(&self, id: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE export_jobs SET status = 'expired' WHERE id = ? AND status = 'completed'",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::database::DbPool;
+use crate::models::Message;
+use sqlx::Error;
+
+/// 外部系统ID到本地ID的映射，用于重复导入时去重
+pub struct ImportMappingRepository {
+    pool: DbPool,
+}
+
+impl ImportMappingRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(
+        &self,
+        source: &str,
+        kind: &str,
+        external_id: &str,
+    ) -> Result<Option<String>, Error> {
+        let local_id = sqlx::query_scalar!(
+            "SELECT local_id FROM import_mappings WHERE source = ? AND kind = ? AND external_id = ?",
+            source,
+            kind,
+            external_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(local_id)
+    }
+
+    /// 保存映射，已存在时保留原来的
+    pub async fn save(
+        &self,
+        source: &str,
+        kind: &str,
+        external_id: &str,
+        local_id: &str,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT IGNORE INTO import_mappings (source, kind, external_id, local_id)
+            VALUES (?, ?, ?, ?)
+            "#,
+            source,
+            kind,
+            external_id,
+            local_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 在一个事务中保存导入的消息和映射，消息已导入过时返回false
+    ///
+    /// 保留消息原来的发送时间，不使用房间默认有效期。
+    pub async fn insert_message(
+        &self,
+        source: &str,
+        external_id: &str,
+        message: &Message,
+    ) -> Result<bool, Error> {
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query!(
+            r#"
+            INSERT IGNORE INTO import_mappings (source, kind, external_id, local_id)
+            VALUES (?, 'message', ?, ?)
+            "#,
+            source,
+            external_id,
+            message.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO messages (id, user_id, username, content, content_html, room_id, message_type, created_at, expires_at, reference)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            message.id,
+            message.user_id,
+            message.username,
+            message.content,
+            message.content_html,
+            message.room_id,
+            message.message_type.to_string(),
+            message.created_at,
+            message.expires_at,
+            message.reference
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+}
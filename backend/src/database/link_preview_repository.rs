@@ -0,0 +1,57 @@
+use crate::database::DbPool;
+use crate::models::LinkPreview;
+use sqlx::Error;
+
+pub struct LinkPreviewRepository {
+    pool: DbPool,
+}
+
+impl LinkPreviewRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, preview: &LinkPreview) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO link_previews (id, message_id, url, title, description, image_url, site_name, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            preview.id,
+            preview.message_id,
+            preview.url,
+            preview.title,
+            preview.description,
+            preview.image_url,
+            preview.site_name,
+            preview.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_message_ids(
+        &self,
+        message_ids: &[String],
+    ) -> Result<Vec<LinkPreview>, Error> {
+        if message_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let previews = sqlx::query_as!(
+            LinkPreview,
+            r#"
+            SELECT * FROM link_previews
+            WHERE FIND_IN_SET(message_id, ?) > 0
+            ORDER BY created_at ASC
+            "#,
+            message_ids.join(",")
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(previews)
+    }
+}
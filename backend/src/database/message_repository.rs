@@ -1,5 +1,9 @@
 use crate::database::DbPool;
-use crate::models::{Message, MessageType};
+use crate::models::{
+    HistoryAnchor, Message, MessageCursor, MessageHistoryQuery, MessagePage, MessageSearchHit,
+    MessageSearchPage, MessageSearchQuery, MessageType, RetentionStats, highlight_snippet,
+};
+use futures::stream::BoxStream;
 use sqlx::Error;
 
 pub struct MessageRepository {
@@ -11,19 +15,36 @@ impl MessageRepository {
         Self { pool }
     }
 
-    pub async fn create(&self, message: Message) -> Result<Message, Error> {
+    /// 保存消息，没有单独设置有效期时使用房间默认值
+    pub async fn create(&self, mut message: Message) -> Result<Message, Error> {
+        if message.expires_at.is_none() {
+            let ttl_seconds = sqlx::query_scalar!(
+                "SELECT message_ttl_seconds FROM rooms WHERE id = ?",
+                message.room_id
+            )
+            .fetch_optional(&self.pool)
+            .await?
+            .unwrap_or(0);
+            if ttl_seconds > 0 {
+                message = message.with_ttl(ttl_seconds as u32);
+            }
+        }
+
         sqlx::query!(
             r#"
-            INSERT INTO messages (id, user_id, username, content, room_id, message_type, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO messages (id, user_id, username, content, content_html, room_id, message_type, created_at, expires_at, reference)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             message.id,
             message.user_id,
             message.username,
             message.content,
+            message.content_html,
             message.room_id,
             message.message_type.to_string(),
-            message.created_at
+            message.created_at,
+            message.expires_at,
+            message.reference
         )
         .execute(&self.pool)
         .await?;
@@ -31,54 +52,348 @@ impl MessageRepository {
         Ok(message)
     }
 
-    pub async fn get_messages_by_room(
+    /// 已过期但尚未清理的消息按不存在处理
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<Message>, Error> {
+        let message = sqlx::query_as!(
+            Message,
+            "SELECT * FROM messages WHERE id = ? AND (expires_at IS NULL OR expires_at > ?)",
+            id,
+            chrono::Utc::now()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(message)
+    }
+
+    /// 消息是否存在，包括已过期尚未清理的消息
+    pub async fn exists(&self, id: &str) -> Result<bool, Error> {
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM messages WHERE id = ?", id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    /// 批量加载未过期的消息，不存在的ID直接忽略
+    pub async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Message>, Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE FIND_IN_SET(id, ?) > 0
+              AND (expires_at IS NULL OR expires_at > ?)
+            "#,
+            ids.join(","),
+            chrono::Utc::now()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    /// 删除消息，附件、链接预览等关联记录随外键级联删除
+    pub async fn delete(&self, id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM messages WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 已过期的消息，按过期时间从早到晚
+    pub async fn find_expired(&self, limit: i64) -> Result<Vec<Message>, Error> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE expires_at IS NOT NULL AND expires_at <= ?
+            ORDER BY expires_at ASC
+            LIMIT ?
+            "#,
+            chrono::Utc::now(),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    /// 房间中早于截止时间的消息，按时间从旧到新，用于按保留策略分批删除
+    pub async fn find_older_than(
         &self,
         room_id: &str,
-        limit: i32,
-        before_timestamp: Option<i64>,
+        cutoff: chrono::DateTime<chrono::Utc>,
+        limit: i64,
     ) -> Result<Vec<Message>, Error> {
-        println!(
-            "数据库查询 - 房间ID: {}, 限制: {}, before_timestamp: {:?}",
-            room_id, limit, before_timestamp
-        );
-
-        let messages = if let Some(timestamp) = before_timestamp {
-            println!("使用 before_timestamp 查询，时间戳: {}", timestamp);
-            sqlx::query_as!(
-                Message,
-                r#"
-                SELECT * FROM messages 
-                WHERE room_id = ? AND created_at < FROM_UNIXTIME(?)
-                ORDER BY created_at ASC 
-                LIMIT ?
-                "#,
-                room_id,
-                timestamp,
-                limit
-            )
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            println!("不使用 before_timestamp 查询，获取所有消息");
-            sqlx::query_as!(
-                Message,
-                r#"
-                SELECT * FROM messages 
-                WHERE room_id = ? 
-                ORDER BY created_at ASC 
-                LIMIT ?
-                "#,
-                room_id,
-                limit
-            )
-            .fetch_all(&self.pool)
-            .await?
-        };
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE room_id = ? AND created_at < ?
+            ORDER BY created_at ASC, id ASC
+            LIMIT ?
+            "#,
+            room_id,
+            cutoff,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-        println!("数据库返回 {} 条消息", messages.len());
         Ok(messages)
     }
 
+    /// 房间中早于截止时间的消息和附件数量
+    pub async fn count_older_than(
+        &self,
+        room_id: &str,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<RetentionStats, Error> {
+        let messages = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM messages WHERE room_id = ? AND created_at < ?",
+            room_id,
+            cutoff
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let attachments = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!: i64",
+                   CAST(COALESCE(SUM(a.size_bytes), 0) AS SIGNED) as "bytes!: i64"
+            FROM attachments a
+            JOIN messages m ON m.id = a.message_id
+            WHERE m.room_id = ? AND m.created_at < ?
+            "#,
+            room_id,
+            cutoff
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(RetentionStats {
+            messages,
+            attachments: attachments.count,
+            attachment_bytes: attachments.bytes,
+        })
+    }
+
+    /// 时间范围内的消息数，用于显示导出进度
+    pub async fn count_range(
+        &self,
+        room_id: &str,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM messages
+            WHERE room_id = ?
+              AND (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at < ?)
+              AND (expires_at IS NULL OR expires_at > ?)
+            "#,
+            room_id,
+            from,
+            from,
+            to,
+            to,
+            chrono::Utc::now()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// 按时间顺序逐条读取时间范围内的消息，不会一次性加载到内存
+    pub fn stream_range<'a>(
+        &'a self,
+        room_id: &'a str,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'a, Result<Message, Error>> {
+        sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE room_id = ?
+              AND (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at < ?)
+              AND (expires_at IS NULL OR expires_at > ?)
+            ORDER BY created_at ASC, id ASC
+            "#,
+            room_id,
+            from,
+            from,
+            to,
+            to,
+            chrono::Utc::now()
+        )
+        .fetch(&self.pool)
+    }
+
+    /// 按游标分页获取房间历史消息，结果按时间从旧到新排列
+    ///
+    /// 已过期但还没被清理的消息不会返回。
+    pub async fn get_messages_by_room(
+        &self,
+        room_id: &str,
+        query: &MessageHistoryQuery,
+    ) -> Result<MessagePage, Error> {
+        let limit = query.limit;
+
+        let page = match &query.anchor {
+            HistoryAnchor::Latest => {
+                let (messages, has_more) = self.fetch_latest(room_id, limit).await?;
+                MessagePage {
+                    messages,
+                    has_more_before: has_more,
+                    has_more_after: false,
+                    attachments: Vec::new(),
+                    link_previews: Vec::new(),
+                    polls: Vec::new(),
+                }
+            }
+            HistoryAnchor::Before(cursor) => {
+                let (messages, has_more) = self.fetch_before(room_id, cursor, limit).await?;
+                MessagePage {
+                    messages,
+                    has_more_before: has_more,
+                    has_more_after: true,
+                    attachments: Vec::new(),
+                    link_previews: Vec::new(),
+                    polls: Vec::new(),
+                }
+            }
+            HistoryAnchor::After(cursor) => {
+                let (messages, has_more) = self.fetch_after(room_id, cursor, limit, false).await?;
+                MessagePage {
+                    messages,
+                    has_more_before: true,
+                    has_more_after: has_more,
+                    attachments: Vec::new(),
+                    link_previews: Vec::new(),
+                    polls: Vec::new(),
+                }
+            }
+            HistoryAnchor::Around(cursor) => {
+                let before_limit = limit / 2;
+                let (mut messages, has_more_before) =
+                    self.fetch_before(room_id, cursor, before_limit).await?;
+                let (after, has_more_after) = self
+                    .fetch_after(room_id, cursor, limit - before_limit, true)
+                    .await?;
+                messages.extend(after);
+                MessagePage {
+                    messages,
+                    has_more_before,
+                    has_more_after,
+                    attachments: Vec::new(),
+                    link_previews: Vec::new(),
+                    polls: Vec::new(),
+                }
+            }
+        };
+
+        Ok(page)
+    }
+
+    /// 多取一条用于判断是否还有更多消息
+    async fn fetch_latest(&self, room_id: &str, limit: u32) -> Result<(Vec<Message>, bool), Error> {
+        let mut messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE room_id = ?
+              AND (expires_at IS NULL OR expires_at > ?)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+            room_id,
+            chrono::Utc::now(),
+            limit + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = truncate_page(&mut messages, limit);
+        messages.reverse();
+        Ok((messages, has_more))
+    }
+
+    async fn fetch_before(
+        &self,
+        room_id: &str,
+        cursor: &MessageCursor,
+        limit: u32,
+    ) -> Result<(Vec<Message>, bool), Error> {
+        let mut messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE room_id = ?
+              AND (created_at < ? OR (created_at = ? AND id < ?))
+              AND (expires_at IS NULL OR expires_at > ?)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+            room_id,
+            cursor.created_at,
+            cursor.created_at,
+            cursor.id,
+            chrono::Utc::now(),
+            limit + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = truncate_page(&mut messages, limit);
+        messages.reverse();
+        Ok((messages, has_more))
+    }
+
+    /// inclusive为true时结果包含游标指向的消息
+    async fn fetch_after(
+        &self,
+        room_id: &str,
+        cursor: &MessageCursor,
+        limit: u32,
+        inclusive: bool,
+    ) -> Result<(Vec<Message>, bool), Error> {
+        let mut messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE room_id = ?
+              AND (created_at > ? OR (created_at = ? AND (id > ? OR (? AND id = ?))))
+              AND (expires_at IS NULL OR expires_at > ?)
+            ORDER BY created_at ASC, id ASC
+            LIMIT ?
+            "#,
+            room_id,
+            cursor.created_at,
+            cursor.created_at,
+            cursor.id,
+            inclusive,
+            cursor.id,
+            chrono::Utc::now(),
+            limit + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = truncate_page(&mut messages, limit);
+        Ok((messages, has_more))
+    }
+
     pub async fn get_recent_messages(
         &self,
         room_id: &str,
@@ -89,10 +404,12 @@ impl MessageRepository {
             r#"
             SELECT * FROM messages 
             WHERE room_id = ? 
+              AND (expires_at IS NULL OR expires_at > ?)
             ORDER BY created_at ASC 
             LIMIT ?
             "#,
             room_id,
+            chrono::Utc::now(),
             limit
         )
         .fetch_all(&self.pool)
@@ -100,4 +417,109 @@ impl MessageRepository {
 
         Ok(messages)
     }
+
+    /// 全文搜索消息，支持房间、作者、消息类型和时间范围过滤
+    ///
+    /// 调用方负责把`query.room_ids`限制为用户可读的房间。
+    pub async fn search_messages(
+        &self,
+        query: &MessageSearchQuery,
+    ) -> Result<MessageSearchPage, Error> {
+        let terms = query.terms();
+        if terms.is_empty() || query.room_ids.is_empty() {
+            return Ok(MessageSearchPage {
+                hits: Vec::new(),
+                total: 0,
+                page: query.page,
+                page_size: query.page_size,
+            });
+        }
+
+        let expression = query.boolean_expression();
+        // 房间ID不包含逗号，使用FIND_IN_SET传递可变长度的房间列表
+        let room_ids = query.room_ids.join(",");
+        let message_type = query.message_type.map(|t| t.to_string());
+        // 两次查询使用同一时间，保证总数和结果一致
+        let now = chrono::Utc::now();
+
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM messages
+            WHERE MATCH(content) AGAINST (? IN BOOLEAN MODE)
+              AND FIND_IN_SET(room_id, ?) > 0
+              AND (? IS NULL OR user_id = ?)
+              AND (? IS NULL OR message_type = ?)
+              AND (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at < ?)
+              AND (expires_at IS NULL OR expires_at > ?)
+            "#,
+            expression,
+            room_ids,
+            query.user_id,
+            query.user_id,
+            message_type,
+            message_type,
+            query.from,
+            query.from,
+            query.to,
+            query.to,
+            now
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT * FROM messages
+            WHERE MATCH(content) AGAINST (? IN BOOLEAN MODE)
+              AND FIND_IN_SET(room_id, ?) > 0
+              AND (? IS NULL OR user_id = ?)
+              AND (? IS NULL OR message_type = ?)
+              AND (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at < ?)
+              AND (expires_at IS NULL OR expires_at > ?)
+            ORDER BY MATCH(content) AGAINST (? IN BOOLEAN MODE) DESC, created_at DESC
+            LIMIT ? OFFSET ?
+            "#,
+            expression,
+            room_ids,
+            query.user_id,
+            query.user_id,
+            message_type,
+            message_type,
+            query.from,
+            query.from,
+            query.to,
+            query.to,
+            now,
+            expression,
+            query.page_size,
+            query.offset()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let hits = messages
+            .into_iter()
+            .map(|message| MessageSearchHit {
+                snippet: highlight_snippet(&message.content, &terms),
+                message,
+            })
+            .collect();
+
+        Ok(MessageSearchPage {
+            hits,
+            total,
+            page: query.page,
+            page_size: query.page_size,
+        })
+    }
+}
+
+/// 去掉多取的一条，返回是否还有更多消息
+fn truncate_page(messages: &mut Vec<Message>, limit: u32) -> bool {
+    let has_more = messages.len() > limit as usize;
+    messages.truncate(limit as usize);
+    has_more
 }
@@ -1,9 +1,33 @@
+pub mod attachment_repository;
+pub mod audit_repository;
+pub mod bookmark_repository;
 pub mod connection;
+pub mod content_filter_repository;
+pub mod export_job_repository;
+pub mod import_mapping_repository;
+pub mod link_preview_repository;
 pub mod message_repository;
+pub mod moderation_repository;
+pub mod pin_repository;
+pub mod poll_repository;
+pub mod report_repository;
 pub mod room_repository;
+pub mod scheduled_message_repository;
 pub mod user_repository;
 
+pub use attachment_repository::*;
+pub use audit_repository::*;
+pub use bookmark_repository::*;
 pub use connection::*;
+pub use content_filter_repository::*;
+pub use export_job_repository::*;
+pub use import_mapping_repository::*;
+pub use link_preview_repository::*;
 pub use message_repository::*;
+pub use moderation_repository::*;
+pub use pin_repository::*;
+pub use poll_repository::*;
+pub use report_repository::*;
 pub use room_repository::*;
+pub use scheduled_message_repository::*;
 pub use user_repository::*;
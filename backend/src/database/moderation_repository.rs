@@ -0,0 +1,100 @@
+use crate::database::DbPool;
+use crate::models::{SpamDecision, UserMute};
+use sqlx::Error;
+
+pub struct ModerationRepository {
+    pool: DbPool,
+}
+
+impl ModerationRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_mute(&self, mute: &UserMute) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_mutes (id, user_id, room_id, reason, source, muted_by, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            mute.id,
+            mute.user_id,
+            mute.room_id,
+            mute.reason,
+            mute.source,
+            mute.muted_by,
+            mute.expires_at,
+            mute.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 查找对该房间生效的禁言（全局禁言或该房间的禁言），有多条时返回最晚结束的
+    pub async fn find_active_mute(
+        &self,
+        user_id: &str,
+        room_id: &str,
+    ) -> Result<Option<UserMute>, Error> {
+        let mute = sqlx::query_as!(
+            UserMute,
+            r#"
+            SELECT * FROM user_mutes
+            WHERE user_id = ?
+              AND (room_id IS NULL OR room_id = ?)
+              AND expires_at > NOW()
+            ORDER BY expires_at DESC
+            LIMIT 1
+            "#,
+            user_id,
+            room_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(mute)
+    }
+
+    /// 统计一段时间内某个来源的禁言次数，用于递增禁言时长
+    pub async fn count_mutes_since(
+        &self,
+        user_id: &str,
+        source: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM user_mutes WHERE user_id = ? AND source = ? AND created_at >= ?",
+            user_id,
+            source,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn create_spam_decision(&self, decision: &SpamDecision) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO spam_decisions (id, user_id, room_id, rule, observed, threshold, action, mute_seconds, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            decision.id,
+            decision.user_id,
+            decision.room_id,
+            decision.rule,
+            decision.observed,
+            decision.threshold,
+            decision.action,
+            decision.mute_seconds,
+            decision.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use crate::database::DbPool;
+use crate::models::{MAX_PINS_PER_ROOM, Message, MessagePin, PinOutcome};
+use sqlx::Error;
+
+pub struct PinRepository {
+    pool: DbPool,
+}
+
+impl PinRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 置顶消息，锁住房间记录后再检查数量，并发置顶时也不会超过上限
+    pub async fn pin(&self, pin: &MessagePin) -> Result<PinOutcome, Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!("SELECT id FROM rooms WHERE id = ? FOR UPDATE", pin.room_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        // 已过期尚未清理的置顶不占用名额
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM pinned_messages p
+            JOIN messages m ON m.id = p.message_id
+            WHERE p.room_id = ?
+              AND (m.expires_at IS NULL OR m.expires_at > ?)
+            "#,
+            pin.room_id,
+            chrono::Utc::now()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if count >= MAX_PINS_PER_ROOM {
+            tx.rollback().await?;
+            return Ok(PinOutcome::LimitReached);
+        }
+
+        let result = sqlx::query!(
+            r#"
+            INSERT IGNORE INTO pinned_messages (message_id, room_id, pinned_by, pinned_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+            pin.message_id,
+            pin.room_id,
+            pin.pinned_by,
+            pin.pinned_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(PinOutcome::AlreadyPinned);
+        }
+        Ok(PinOutcome::Pinned)
+    }
+
+    /// 取消置顶，消息没有置顶时返回false
+    pub async fn unpin(&self, message_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM pinned_messages WHERE message_id = ?",
+            message_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 房间的置顶记录，最近置顶的在前
+    pub async fn find_by_room(&self, room_id: &str) -> Result<Vec<MessagePin>, Error> {
+        let pins = sqlx::query_as!(
+            MessagePin,
+            r#"
+            SELECT message_id, room_id, pinned_by, pinned_at FROM pinned_messages
+            WHERE room_id = ?
+            ORDER BY pinned_at DESC
+            "#,
+            room_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pins)
+    }
+
+    /// 房间中被置顶且未过期的消息
+    pub async fn find_pinned_messages(&self, room_id: &str) -> Result<Vec<Message>, Error> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT m.* FROM messages m
+            JOIN pinned_messages p ON p.message_id = m.id
+            WHERE p.room_id = ?
+              AND (m.expires_at IS NULL OR m.expires_at > ?)
+            "#,
+            room_id,
+            chrono::Utc::now()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+}
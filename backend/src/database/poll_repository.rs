@@ -0,0 +1,209 @@
+use crate::database::DbPool;
+use crate::models::{Poll, PollInfo, PollOption, PollVote, VoteOutcome};
+use sqlx::Error;
+
+pub struct PollRepository {
+    pool: DbPool,
+}
+
+impl PollRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 在一个事务中保存投票和选项
+    pub async fn create(&self, poll: &Poll, options: &[PollOption]) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO polls (id, message_id, room_id, created_by, question, multi_choice, anonymous, closes_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            poll.id,
+            poll.message_id,
+            poll.room_id,
+            poll.created_by,
+            poll.question,
+            poll.multi_choice,
+            poll.anonymous,
+            poll.closes_at,
+            poll.created_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for option in options {
+            sqlx::query!(
+                "INSERT INTO poll_options (id, poll_id, position, text) VALUES (?, ?, ?, ?)",
+                option.id,
+                option.poll_id,
+                option.position,
+                option.text
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<Poll>, Error> {
+        let poll = sqlx::query_as!(
+            Poll,
+            r#"
+            SELECT id, message_id, room_id, created_by, question,
+                   multi_choice as "multi_choice: bool", anonymous as "anonymous: bool",
+                   closes_at, closed_at, created_at
+            FROM polls WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(poll)
+    }
+
+    pub async fn find_options(&self, poll_id: &str) -> Result<Vec<PollOption>, Error> {
+        let options = sqlx::query_as!(
+            PollOption,
+            "SELECT * FROM poll_options WHERE poll_id = ? ORDER BY position ASC",
+            poll_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(options)
+    }
+
+    /// 投票及当前票数
+    pub async fn find_info(&self, poll: &Poll) -> Result<PollInfo, Error> {
+        let options = self.find_options(&poll.id).await?;
+        let votes = self.find_votes(&poll.id).await?;
+        Ok(poll.to_info(&options, &votes))
+    }
+
+    /// 批量加载消息对应的投票信息，用于历史消息
+    pub async fn find_infos_by_message_ids(
+        &self,
+        message_ids: &[String],
+    ) -> Result<Vec<PollInfo>, Error> {
+        if message_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let polls = sqlx::query_as!(
+            Poll,
+            r#"
+            SELECT id, message_id, room_id, created_by, question,
+                   multi_choice as "multi_choice: bool", anonymous as "anonymous: bool",
+                   closes_at, closed_at, created_at
+            FROM polls WHERE FIND_IN_SET(message_id, ?) > 0
+            "#,
+            message_ids.join(",")
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        if polls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let poll_ids = polls
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+        let options = sqlx::query_as!(
+            PollOption,
+            "SELECT * FROM poll_options WHERE FIND_IN_SET(poll_id, ?) > 0",
+            poll_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let votes = sqlx::query_as!(
+            PollVote,
+            "SELECT poll_id, user_id, option_id FROM poll_vote_options WHERE FIND_IN_SET(poll_id, ?) > 0",
+            poll_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(polls
+            .iter()
+            .map(|poll| poll.to_info(&options, &votes))
+            .collect())
+    }
+
+    async fn find_votes(&self, poll_id: &str) -> Result<Vec<PollVote>, Error> {
+        let votes = sqlx::query_as!(
+            PollVote,
+            "SELECT poll_id, user_id, option_id FROM poll_vote_options WHERE poll_id = ?",
+            poll_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(votes)
+    }
+
+    /// 投票，只有投票未结束时才会写入选票，重复投票由主键拒绝
+    pub async fn vote(
+        &self,
+        poll_id: &str,
+        user_id: &str,
+        option_ids: &[String],
+    ) -> Result<VoteOutcome, Error> {
+        let now = chrono::Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO poll_votes (poll_id, user_id, created_at)
+            SELECT id, ?, ? FROM polls
+            WHERE id = ? AND closed_at IS NULL AND (closes_at IS NULL OR closes_at > ?)
+            "#,
+            user_id,
+            now,
+            poll_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await;
+        match inserted {
+            Ok(result) if result.rows_affected() == 0 => return Ok(VoteOutcome::Closed),
+            Ok(_) => {}
+            Err(Error::Database(e)) if e.is_unique_violation() => {
+                return Ok(VoteOutcome::AlreadyVoted);
+            }
+            Err(e) => return Err(e),
+        }
+
+        for option_id in option_ids {
+            sqlx::query!(
+                "INSERT INTO poll_vote_options (poll_id, user_id, option_id) VALUES (?, ?, ?)",
+                poll_id,
+                user_id,
+                option_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(VoteOutcome::Recorded)
+    }
+
+    /// 提前结束投票，已结束的返回false
+    pub async fn close(&self, poll_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "UPDATE polls SET closed_at = ? WHERE id = ? AND closed_at IS NULL",
+            chrono::Utc::now(),
+            poll_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::database::DbPool;
+use crate::models::{Report, ReportStatus};
+use sqlx::Error;
+
+pub struct ReportRepository {
+    pool: DbPool,
+}
+
+impl ReportRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, report: &Report) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO reports (id, reporter_id, target_type, target_user_id, message_id, room_id, reason, status, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            report.id,
+            report.reporter_id,
+            report.target_type,
+            report.target_user_id,
+            report.message_id,
+            report.room_id,
+            report.reason,
+            report.status,
+            report.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<Report>, Error> {
+        let report = sqlx::query_as!(Report, "SELECT * FROM reports WHERE id = ?", id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(report)
+    }
+
+    /// 按状态列出举报，status为空时列出全部，最早提交的排在前面
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Report>, Error> {
+        let reports = sqlx::query_as!(
+            Report,
+            r#"
+            SELECT * FROM reports
+            WHERE (? IS NULL OR status = ?)
+            ORDER BY created_at ASC
+            LIMIT ? OFFSET ?
+            "#,
+            status,
+            status,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reports)
+    }
+
+    /// 认领举报，只能认领未被其他人认领的未处理举报
+    pub async fn claim(&self, id: &str, moderator_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE reports SET status = ?, claimed_by = ?, claimed_at = ?
+            WHERE id = ? AND status != ? AND (claimed_by IS NULL OR claimed_by = ?)
+            "#,
+            ReportStatus::Claimed.as_str(),
+            moderator_id,
+            chrono::Utc::now(),
+            id,
+            ReportStatus::Resolved.as_str(),
+            moderator_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 处理举报，已被其他人认领的举报不能处理
+    pub async fn resolve(
+        &self,
+        id: &str,
+        moderator_id: &str,
+        resolution: &str,
+        note: Option<&str>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE reports SET status = ?, resolution = ?, resolution_note = ?, resolved_by = ?, resolved_at = ?
+            WHERE id = ? AND status != ? AND (claimed_by IS NULL OR claimed_by = ?)
+            "#,
+            ReportStatus::Resolved.as_str(),
+            resolution,
+            note,
+            moderator_id,
+            chrono::Utc::now(),
+            id,
+            ReportStatus::Resolved.as_str(),
+            moderator_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
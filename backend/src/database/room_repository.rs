@@ -39,6 +39,104 @@ impl RoomRepository {
         Ok(room)
     }
 
+    /// 同名房间中最早创建的一个
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<Room>, Error> {
+        let room = sqlx::query_as!(
+            Room,
+            "SELECT * FROM rooms WHERE name = ? ORDER BY created_at ASC LIMIT 1",
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(room)
+    }
+
+    pub async fn set_slow_mode(&self, room_id: &str, seconds: u32) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE rooms SET slow_mode_seconds = ?, updated_at = NOW() WHERE id = ?",
+            seconds,
+            room_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_message_ttl(&self, room_id: &str, seconds: u32) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE rooms SET message_ttl_seconds = ?, updated_at = NOW() WHERE id = ?",
+            seconds,
+            room_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_retention_days(&self, room_id: &str, days: Option<u32>) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE rooms SET retention_days = ?, updated_at = NOW() WHERE id = ?",
+            days,
+            room_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 房间描述同时作为话题显示
+    pub async fn set_description(&self, room_id: &str, description: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE rooms SET description = ?, updated_at = NOW() WHERE id = ?",
+            description,
+            room_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_all(&self) -> Result<Vec<Room>, Error> {
+        let rooms = sqlx::query_as!(Room, "SELECT * FROM rooms ORDER BY created_at ASC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rooms)
+    }
+
+    /// 记录房间成员，重复加入时忽略
+    pub async fn add_member(&self, room_id: &str, user_id: &str) -> Result<(), Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        sqlx::query!(
+            "INSERT IGNORE INTO room_members (id, room_id, user_id) VALUES (?, ?, ?)",
+            id,
+            room_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 移除房间成员，返回是否确实是成员
+    pub async fn remove_member(&self, room_id: &str, user_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM room_members WHERE room_id = ? AND user_id = ?",
+            room_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn get_public_rooms(&self) -> Result<Vec<Room>, Error> {
         let rooms = sqlx::query_as!(
             Room,
@@ -67,4 +165,69 @@ impl RoomRepository {
 
         Ok(rooms)
     }
+
+    /// 用户可读的房间ID：公开房间、用户创建的房间以及用户所在的房间
+    pub async fn get_readable_room_ids(&self, user_id: &str) -> Result<Vec<String>, Error> {
+        let room_ids = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT r.id FROM rooms r
+            LEFT JOIN room_members rm ON r.id = rm.room_id AND rm.user_id = ?
+            WHERE r.is_public = 1 OR r.created_by = ? OR rm.user_id IS NOT NULL
+            "#,
+            user_id,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(room_ids)
+    }
+
+    /// 用户是否可以读取房间消息
+    pub async fn can_read(&self, user_id: &str, room_id: &str) -> Result<bool, Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM rooms r
+            WHERE r.id = ?
+              AND (r.is_public = 1 OR r.created_by = ? OR EXISTS (
+                  SELECT 1 FROM room_members rm WHERE rm.room_id = r.id AND rm.user_id = ?
+              ))
+            "#,
+            room_id,
+            user_id,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    /// 用户是否可以加入房间：公开房间任何人可加入，私有房间只允许创建者和已有成员
+    pub async fn can_join(&self, user_id: &str, room: &Room) -> Result<bool, Error> {
+        if room.is_public_room() {
+            return Ok(true);
+        }
+        self.is_member(user_id, &room.id).await
+    }
+
+    /// 用户是否是房间成员（房间创建者视为成员）
+    pub async fn is_member(&self, user_id: &str, room_id: &str) -> Result<bool, Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM rooms r
+            WHERE r.id = ?
+              AND (r.created_by = ? OR EXISTS (
+                  SELECT 1 FROM room_members rm WHERE rm.room_id = r.id AND rm.user_id = ?
+              ))
+            "#,
+            room_id,
+            user_id,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
 }
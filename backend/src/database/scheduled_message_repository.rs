@@ -0,0 +1,231 @@
+use crate::database::DbPool;
+use crate::models::ScheduledMessage;
+use sqlx::Error;
+
+pub struct ScheduledMessageRepository {
+    pool: DbPool,
+}
+
+impl ScheduledMessageRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, scheduled: &ScheduledMessage) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO scheduled_messages (id, message_id, user_id, room_id, content, send_at, status, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            scheduled.id,
+            scheduled.message_id,
+            scheduled.user_id,
+            scheduled.room_id,
+            scheduled.content,
+            scheduled.send_at,
+            scheduled.status,
+            scheduled.created_at,
+            scheduled.updated_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<ScheduledMessage>, Error> {
+        let scheduled = sqlx::query_as!(
+            ScheduledMessage,
+            "SELECT * FROM scheduled_messages WHERE id = ?",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(scheduled)
+    }
+
+    pub async fn count_pending(&self, user_id: &str) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM scheduled_messages WHERE user_id = ? AND status = 'pending'",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// 用户的定时消息，按发送时间排列，status为空时列出全部
+    pub async fn list_by_user(
+        &self,
+        user_id: &str,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ScheduledMessage>, Error> {
+        let scheduled = sqlx::query_as!(
+            ScheduledMessage,
+            r#"
+            SELECT * FROM scheduled_messages
+            WHERE user_id = ? AND (? IS NULL OR status = ?)
+            ORDER BY send_at ASC
+            LIMIT ? OFFSET ?
+            "#,
+            user_id,
+            status,
+            status,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(scheduled)
+    }
+
+    /// 修改待发送的定时消息，已被认领或已发送的返回false
+    pub async fn update_pending(
+        &self,
+        id: &str,
+        user_id: &str,
+        content: &str,
+        send_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE scheduled_messages SET content = ?, send_at = ?
+            WHERE id = ? AND user_id = ? AND status = 'pending'
+            "#,
+            content,
+            send_at,
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn cancel(&self, id: &str, user_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE scheduled_messages SET status = 'cancelled'
+            WHERE id = ? AND user_id = ? AND status = 'pending'
+            "#,
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 已到发送时间的定时消息
+    pub async fn find_due(&self, limit: i64) -> Result<Vec<ScheduledMessage>, Error> {
+        let scheduled = sqlx::query_as!(
+            ScheduledMessage,
+            r#"
+            SELECT * FROM scheduled_messages
+            WHERE status = 'pending' AND send_at <= ?
+            ORDER BY send_at ASC
+            LIMIT ?
+            "#,
+            chrono::Utc::now(),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(scheduled)
+    }
+
+    /// 认领定时消息，多个实例同时认领时只有一个会成功
+    pub async fn claim(&self, id: &str, instance_id: &str) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE scheduled_messages SET status = 'sending', claimed_by = ?, claimed_at = ?
+            WHERE id = ? AND status = 'pending'
+            "#,
+            instance_id,
+            chrono::Utc::now(),
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 认领后超过指定时间仍未完成的定时消息，通常是实例在发送途中退出
+    pub async fn find_stale_sending(
+        &self,
+        claimed_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ScheduledMessage>, Error> {
+        let scheduled = sqlx::query_as!(
+            ScheduledMessage,
+            r#"
+            SELECT * FROM scheduled_messages
+            WHERE status = 'sending' AND claimed_at < ?
+            "#,
+            claimed_before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(scheduled)
+    }
+
+    pub async fn mark_sent(&self, id: &str) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE scheduled_messages SET status = 'sent', sent_at = ?, error = NULL
+            WHERE id = ? AND status = 'sending'
+            "#,
+            chrono::Utc::now(),
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_failed(&self, id: &str, error: &str) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE scheduled_messages SET status = 'failed', error = ?
+            WHERE id = ? AND status = 'sending'
+            "#,
+            error,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 放回待发送队列，send_at之后再重试
+    pub async fn release(
+        &self,
+        id: &str,
+        send_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE scheduled_messages
+            SET status = 'pending', send_at = ?, claimed_by = NULL, claimed_at = NULL
+            WHERE id = ? AND status = 'sending'
+            "#,
+            send_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::database::DbPool;
-use crate::models::{CreateUser, UpdateUser, User};
+use crate::models::{CreateUser, RetiredAvatar, UpdateUser, User};
 use sqlx::Error;
 
 pub struct UserRepository {
@@ -23,8 +23,8 @@ impl UserRepository {
 
         sqlx::query!(
             r#"
-            INSERT INTO users (id, username, email, password_hash, avatar, is_online, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO users (id, username, email, password_hash, avatar, is_online, created_at, updated_at, role)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             user.id,
             user.username,
@@ -33,7 +33,8 @@ impl UserRepository {
             user.avatar,
             user.is_online,
             user.created_at,
-            user.updated_at
+            user.updated_at,
+            user.role
         )
         .execute(&self.pool)
         .await?;
@@ -49,6 +50,14 @@ impl UserRepository {
         Ok(user)
     }
 
+    pub async fn find_by_username(&self, username: &str) -> Result<Option<User>, Error> {
+        let user = sqlx::query_as!(User, "SELECT * FROM users WHERE username = ?", username)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user)
+    }
+
     pub async fn find_by_id(&self, id: &str) -> Result<Option<User>, Error> {
         let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = ?", id)
             .fetch_optional(&self.pool)
@@ -66,19 +75,15 @@ impl UserRepository {
         if let Some(username) = update_user.username {
             user.username = username;
         }
-        if let Some(avatar) = update_user.avatar {
-            user.avatar = Some(avatar);
-        }
         user.updated_at = chrono::Utc::now();
 
         sqlx::query!(
             r#"
             UPDATE users 
-            SET username = ?, avatar = ?, updated_at = ?
+            SET username = ?, updated_at = ?
             WHERE id = ?
             "#,
             user.username,
-            user.avatar,
             user.updated_at,
             id
         )
@@ -88,6 +93,78 @@ impl UserRepository {
         Ok(user)
     }
 
+    /// 更新头像版本号，之前的版本记入retired_avatars，返回之前的版本号
+    pub async fn set_avatar(&self, id: &str, version: &str) -> Result<Option<String>, Error> {
+        let mut tx = self.pool.begin().await?;
+        let previous = sqlx::query_scalar!(
+            "SELECT avatar_version FROM users WHERE id = ? FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::RowNotFound)?;
+
+        let now = chrono::Utc::now();
+        sqlx::query!(
+            "UPDATE users SET avatar_version = ?, updated_at = ? WHERE id = ?",
+            version,
+            now,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(previous) = &previous {
+            sqlx::query!(
+                "INSERT IGNORE INTO retired_avatars (user_id, version, retired_at) VALUES (?, ?, ?)",
+                id,
+                previous,
+                now
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(previous)
+    }
+
+    /// 在before之前被替换的头像版本，最早的在前
+    pub async fn find_retired_avatars(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<RetiredAvatar>, Error> {
+        let avatars = sqlx::query_as!(
+            RetiredAvatar,
+            r#"
+            SELECT user_id, version, retired_at FROM retired_avatars
+            WHERE retired_at < ?
+            ORDER BY retired_at
+            LIMIT ?
+            "#,
+            before,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(avatars)
+    }
+
+    /// 头像文件删除后移除记录
+    pub async fn delete_retired_avatar(&self, user_id: &str, version: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "DELETE FROM retired_avatars WHERE user_id = ? AND version = ?",
+            user_id,
+            version
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn set_online_status(&self, id: &str, is_online: bool) -> Result<(), Error> {
         sqlx::query!("UPDATE users SET is_online = ? WHERE id = ?", is_online, id)
             .execute(&self.pool)
@@ -96,6 +173,75 @@ impl UserRepository {
         Ok(())
     }
 
+    pub async fn set_presence_status(&self, id: &str, status: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE users SET presence_status = ? WHERE id = ?",
+            status,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 用户最后一个连接断开时调用，记录最后在线时间
+    pub async fn mark_offline(
+        &self,
+        id: &str,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE users SET is_online = 0, last_seen_at = ? WHERE id = ?",
+            last_seen_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 获取联系人：与该用户同属至少一个房间的其他成员
+    pub async fn get_contact_ids(&self, id: &str) -> Result<Vec<String>, Error> {
+        let contact_ids = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT other.user_id FROM room_members me
+            JOIN room_members other ON me.room_id = other.room_id
+            WHERE me.user_id = ? AND other.user_id <> ?
+            "#,
+            id,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(contact_ids)
+    }
+
+    /// 封禁用户
+    pub async fn ban(&self, user_id: &str, reason: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE users SET banned_at = ?, ban_reason = ? WHERE id = ?",
+            chrono::Utc::now(),
+            reason,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 获取所有版主和管理员的ID
+    pub async fn get_moderator_ids(&self) -> Result<Vec<String>, Error> {
+        let ids = sqlx::query_scalar!("SELECT id FROM users WHERE role IN ('moderator', 'admin')")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(ids)
+    }
+
     pub async fn get_online_users(&self) -> Result<Vec<User>, Error> {
         let users = sqlx::query_as!(
             User,
@@ -0,0 +1,5 @@
+pub mod service;
+pub mod writer;
+
+pub use service::*;
+pub use writer::*;
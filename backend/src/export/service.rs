@@ -0,0 +1,282 @@
+use super::ExportWriter;
+use crate::audit::AuditLogger;
+use crate::database::{ExportJobRepository, MessageRepository, RoomRepository};
+use crate::models::{
+    AuditAction, AuditEntry, CreateExport, EXPORT_FILE_RETENTION_DAYS, ExportFormat, ExportJob,
+    User, UserRole,
+};
+use crate::storage::{BlobStore, StorageError, StorageResult};
+use bytes::Bytes;
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// 没有新任务通知时检查待处理任务和过期文件的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// 每处理多少条消息更新一次进度
+const PROGRESS_INTERVAL: i64 = 1000;
+/// 运行中的任务超过该时间没有更新进度，认为所在实例已经退出
+const STALE_JOB_SECONDS: i64 = 10 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("导出任务不存在")]
+    NotFound,
+    #[error("只有管理员和房间创建者可以导出聊天记录")]
+    Forbidden,
+    #[error("导出尚未完成")]
+    NotReady,
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("存储错误: {0}")]
+    Storage(#[from] StorageError),
+}
+
+pub type ExportResult<T> = Result<T, ExportError>;
+
+/// 房间历史导出，创建任务后由`ExportWorker`在后台生成文件
+#[derive(Clone)]
+pub struct ExportService {
+    job_repo: Arc<ExportJobRepository>,
+    room_repo: Arc<RoomRepository>,
+    blob_store: Arc<dyn BlobStore>,
+    notify: Arc<Notify>,
+    audit_logger: AuditLogger,
+}
+
+/// 导出后台任务
+///
+/// 任务保存在数据库中，多个实例通过条件更新认领，进度长时间不更新的任务会重新放回队列。
+pub struct ExportWorker {
+    instance_id: String,
+    job_repo: Arc<ExportJobRepository>,
+    room_repo: Arc<RoomRepository>,
+    message_repo: Arc<MessageRepository>,
+    blob_store: Arc<dyn BlobStore>,
+    notify: Arc<Notify>,
+}
+
+impl ExportService {
+    pub fn new(
+        job_repo: Arc<ExportJobRepository>,
+        room_repo: Arc<RoomRepository>,
+        message_repo: Arc<MessageRepository>,
+        blob_store: Arc<dyn BlobStore>,
+        audit_logger: AuditLogger,
+    ) -> (Self, ExportWorker) {
+        let notify = Arc::new(Notify::new());
+        let worker = ExportWorker {
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            job_repo: job_repo.clone(),
+            room_repo: room_repo.clone(),
+            message_repo,
+            blob_store: blob_store.clone(),
+            notify: notify.clone(),
+        };
+        let service = Self {
+            job_repo,
+            room_repo,
+            blob_store,
+            notify,
+            audit_logger,
+        };
+        (service, worker)
+    }
+
+    pub async fn create(
+        &self,
+        user: &User,
+        room_id: &str,
+        req: CreateExport,
+        ip: Option<&str>,
+    ) -> ExportResult<ExportJob> {
+        let format = ExportFormat::parse(&req.format)
+            .ok_or_else(|| ExportError::Invalid(format!("不支持的导出格式: {}", req.format)))?;
+        let from = parse_time(req.from)?;
+        let to = parse_time(req.to)?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from >= to {
+                return Err(ExportError::Invalid("开始时间必须早于结束时间".to_string()));
+            }
+        }
+
+        let room = self
+            .room_repo
+            .find_by_id(room_id)
+            .await?
+            .ok_or_else(|| ExportError::Invalid("房间不存在".to_string()))?;
+        if room.created_by != user.id && user.role() != UserRole::Admin {
+            return Err(ExportError::Forbidden);
+        }
+
+        let job = ExportJob::new(room.id, user.id.clone(), format, from, to);
+        self.job_repo.create(&job).await?;
+        self.notify.notify_one();
+
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::HistoryExported)
+                .actor(&user.id)
+                .target("room", &job.room_id)
+                .ip(ip)
+                .details(serde_json::json!({
+                    "export_id": job.id,
+                    "format": job.format,
+                    "from": req.from,
+                    "to": req.to,
+                })),
+        );
+
+        Ok(job)
+    }
+
+    /// 只有发起人和管理员可以查看，其他人按不存在处理
+    pub async fn find(&self, user: &User, job_id: &str) -> ExportResult<ExportJob> {
+        match self.job_repo.find_by_id(job_id).await? {
+            Some(job) if job.requested_by == user.id || user.role() == UserRole::Admin => Ok(job),
+            _ => Err(ExportError::NotFound),
+        }
+    }
+
+    /// 按顺序读取各个分片，返回任务信息和文件内容
+    pub async fn download(
+        &self,
+        user: &User,
+        job_id: &str,
+    ) -> ExportResult<(ExportJob, BoxStream<'static, StorageResult<Bytes>>)> {
+        let job = self.find(user, job_id).await?;
+        if !job.is_completed() {
+            return Err(ExportError::NotReady);
+        }
+
+        let blob_store = self.blob_store.clone();
+        let parts = job.clone();
+        let stream = futures::stream::unfold(0, move |index| {
+            let blob_store = blob_store.clone();
+            let key = parts.part_key(index);
+            let part_count = parts.part_count;
+            async move {
+                if index >= part_count {
+                    return None;
+                }
+                Some((blob_store.get(&key).await, index + 1))
+            }
+        })
+        .boxed();
+
+        Ok((job, stream))
+    }
+}
+
+impl ExportWorker {
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = self.notify.notified() => {}
+                _ = interval.tick() => {
+                    if let Err(e) = self.maintain().await {
+                        eprintln!("维护导出任务失败: {}", e);
+                    }
+                }
+            }
+
+            if let Err(e) = self.process_pending().await {
+                eprintln!("处理导出任务失败: {}", e);
+            }
+        }
+    }
+
+    async fn process_pending(&self) -> Result<(), sqlx::Error> {
+        while let Some(job) = self.job_repo.find_next_pending().await? {
+            let total = self
+                .message_repo
+                .count_range(&job.room_id, job.from_time, job.to_time)
+                .await?;
+            if !self
+                .job_repo
+                .claim(&job.id, &self.instance_id, total)
+                .await?
+            {
+                continue;
+            }
+
+            println!("开始导出房间 {} 的聊天记录，任务 {}", job.room_id, job.id);
+            match self.export(&job).await {
+                Ok(processed) => println!("导出任务 {} 完成，共 {} 条消息", job.id, processed),
+                Err(e) => {
+                    eprintln!("导出任务 {} 失败: {}", job.id, e);
+                    // error列最长255个字符
+                    let error: String = e.to_string().chars().take(200).collect();
+                    self.job_repo.mark_failed(&job.id, &error).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn export(&self, job: &ExportJob) -> ExportResult<i64> {
+        let title = match self.room_repo.find_by_id(&job.room_id).await? {
+            Some(room) => format!("{} 聊天记录", room.name),
+            None => "聊天记录".to_string(),
+        };
+        let mut writer = ExportWriter::new(job.clone(), self.blob_store.clone(), &title);
+
+        let mut processed = 0;
+        let mut rows = self
+            .message_repo
+            .stream_range(&job.room_id, job.from_time, job.to_time);
+        while let Some(message) = rows.try_next().await? {
+            writer.write(&message).await?;
+            processed += 1;
+            if processed % PROGRESS_INTERVAL == 0 {
+                self.job_repo
+                    .update_progress(&job.id, processed, writer.part_count(), writer.size_bytes())
+                    .await?;
+            }
+        }
+        drop(rows);
+
+        let (part_count, size_bytes) = writer.finish().await?;
+        self.job_repo
+            .mark_completed(&job.id, processed, part_count, size_bytes)
+            .await?;
+        Ok(processed)
+    }
+
+    /// 重新排队中断的任务，删除超过保留期的导出文件
+    async fn maintain(&self) -> Result<(), sqlx::Error> {
+        let stale_before = chrono::Utc::now() - chrono::Duration::seconds(STALE_JOB_SECONDS);
+        let requeued = self.job_repo.requeue_stale(stale_before).await?;
+        if requeued > 0 {
+            println!("{} 个导出任务长时间没有进度，已重新排队", requeued);
+        }
+
+        let completed_before =
+            chrono::Utc::now() - chrono::Duration::days(EXPORT_FILE_RETENTION_DAYS);
+        for job in self
+            .job_repo
+            .find_completed_before(completed_before)
+            .await?
+        {
+            for index in 0..job.part_count {
+                if let Err(e) = self.blob_store.delete(&job.part_key(index)).await {
+                    eprintln!("删除导出文件 {} 失败: {}", job.part_key(index), e);
+                }
+            }
+            self.job_repo.mark_expired(&job.id).await?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_time(timestamp: Option<i64>) -> ExportResult<Option<chrono::DateTime<chrono::Utc>>> {
+    timestamp
+        .map(|secs| {
+            chrono::DateTime::from_timestamp(secs, 0)
+                .ok_or_else(|| ExportError::Invalid(format!("无效的时间: {}", secs)))
+        })
+        .transpose()
+}
@@ -0,0 +1,157 @@
+use crate::models::{ExportFormat, ExportJob, Message};
+use crate::storage::{BlobStore, StorageResult};
+use bytes::Bytes;
+use std::sync::Arc;
+
+/// 单个分片的大小，写满后上传到存储后端
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,'Segoe UI',sans-serif;max-width:860px;\
+margin:24px auto;padding:0 16px;color:#222}h1{font-size:20px}.meta{color:#888;font-size:13px}\
+.message{padding:8px 0;border-bottom:1px solid #eee}.user{font-weight:600;color:#333;margin-right:8px}\
+.content p{margin:4px 0}pre{background:#f6f8fa;padding:8px;overflow:auto}";
+
+/// 把消息按导出格式写出，内容按分片上传，内存中最多保留一个分片
+pub struct ExportWriter {
+    job: ExportJob,
+    format: ExportFormat,
+    blob_store: Arc<dyn BlobStore>,
+    buffer: Vec<u8>,
+    part_count: i32,
+    size_bytes: i64,
+}
+
+impl ExportWriter {
+    /// title只用于HTML格式的标题
+    pub fn new(job: ExportJob, blob_store: Arc<dyn BlobStore>, title: &str) -> Self {
+        let format = job.export_format();
+        let mut writer = Self {
+            job,
+            format,
+            blob_store,
+            buffer: Vec::with_capacity(PART_SIZE),
+            part_count: 0,
+            size_bytes: 0,
+        };
+        writer.write_header(title);
+        writer
+    }
+
+    pub fn part_count(&self) -> i32 {
+        self.part_count
+    }
+
+    pub fn size_bytes(&self) -> i64 {
+        self.size_bytes + self.buffer.len() as i64
+    }
+
+    pub async fn write(&mut self, message: &Message) -> StorageResult<()> {
+        match self.format {
+            ExportFormat::Jsonl => {
+                if let Ok(line) = serde_json::to_string(message) {
+                    self.buffer.extend_from_slice(line.as_bytes());
+                    self.buffer.push(b'\n');
+                }
+            }
+            ExportFormat::Csv => {
+                let row = [
+                    message.id.clone(),
+                    message.created_at.to_rfc3339(),
+                    message.user_id.clone(),
+                    message.username.clone(),
+                    message.message_type.to_string(),
+                    message.content.clone(),
+                ]
+                .iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",");
+                self.buffer.extend_from_slice(row.as_bytes());
+                self.buffer.extend_from_slice(b"\r\n");
+            }
+            ExportFormat::Html => {
+                // content_html已经过服务端清洗，可以直接嵌入
+                let entry = format!(
+                    "<div class=\"message\"><div class=\"meta\"><span class=\"user\">{}</span>\
+                     <time datetime=\"{}\">{}</time></div><div class=\"content\">{}</div></div>\n",
+                    escape_html(&message.username),
+                    message.created_at.to_rfc3339(),
+                    message.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    message.rendered_content()
+                );
+                self.buffer.extend_from_slice(entry.as_bytes());
+            }
+        }
+
+        if self.buffer.len() >= PART_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// 写入结尾并上传剩余内容，返回分片数和文件大小
+    pub async fn finish(mut self) -> StorageResult<(i32, i64)> {
+        if self.format == ExportFormat::Html {
+            self.buffer.extend_from_slice(b"</body>\n</html>\n");
+        }
+        if !self.buffer.is_empty() || self.part_count == 0 {
+            self.flush().await?;
+        }
+        Ok((self.part_count, self.size_bytes))
+    }
+
+    fn write_header(&mut self, title: &str) {
+        match self.format {
+            ExportFormat::Jsonl => {}
+            ExportFormat::Csv => {
+                self.buffer
+                    .extend_from_slice(b"id,created_at,user_id,username,message_type,content\r\n");
+            }
+            ExportFormat::Html => {
+                let header = format!(
+                    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+                     <style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+                     <p class=\"meta\">导出时间: {}</p>\n",
+                    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+                    title = escape_html(title),
+                );
+                self.buffer.extend_from_slice(header.as_bytes());
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> StorageResult<()> {
+        let data = Bytes::from(std::mem::replace(
+            &mut self.buffer,
+            Vec::with_capacity(PART_SIZE),
+        ));
+        let len = data.len() as i64;
+        self.blob_store
+            .put(
+                &self.job.part_key(self.part_count),
+                data,
+                self.format.content_type(),
+            )
+            .await?;
+        self.part_count += 1;
+        self.size_bytes += len;
+        Ok(())
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
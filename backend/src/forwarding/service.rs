@@ -0,0 +1,214 @@
+use crate::database::{MessageRepository, RoomRepository};
+use crate::models::{
+    ForwardMessage, Message, MessageReference, MessageType, QuoteMessage, ReferenceKind, User,
+};
+use crate::moderation::{ContentFilter, FilterDecision, SendGuard, SendRejection};
+use crate::unfurl::LinkUnfurler;
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ForwardError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("消息不存在")]
+    NotFound,
+    #[error("目标房间不存在")]
+    RoomNotFound,
+    #[error("只能发送到已加入的房间")]
+    NotMember,
+    #[error("{}", .0.message())]
+    Rejected(SendRejection),
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type ForwardResult<T> = Result<T, ForwardError>;
+
+/// 转发和引用回复，WebSocket、HTTP和gRPC共用
+///
+/// 原消息需要当前用户可读，目标房间必须已加入，并按普通发消息检查禁言、限流和内容过滤。
+/// 新消息带上原消息的快照，原消息被删除后仍能显示作者、房间和发送时间。
+#[derive(Clone)]
+pub struct ForwardService {
+    message_repo: Arc<MessageRepository>,
+    room_repo: Arc<RoomRepository>,
+    send_guard: SendGuard,
+    content_filter: ContentFilter,
+    link_unfurler: LinkUnfurler,
+    event_bus: RoomEventBus,
+}
+
+impl ForwardService {
+    pub fn new(
+        message_repo: Arc<MessageRepository>,
+        room_repo: Arc<RoomRepository>,
+        send_guard: SendGuard,
+        content_filter: ContentFilter,
+        link_unfurler: LinkUnfurler,
+        event_bus: RoomEventBus,
+    ) -> Self {
+        Self {
+            message_repo,
+            room_repo,
+            send_guard,
+            content_filter,
+            link_unfurler,
+            event_bus,
+        }
+    }
+
+    /// 把消息原样转发到另一个房间，发送者是转发人
+    pub async fn forward(
+        &self,
+        user: &User,
+        message_id: &str,
+        req: ForwardMessage,
+    ) -> ForwardResult<Message> {
+        let (source, room_name) = self.find_readable(user, message_id).await?;
+        if !matches!(source.message_type, MessageType::Text) {
+            return Err(ForwardError::Invalid("只能转发文字消息".to_string()));
+        }
+        if source.room_id == req.room_id {
+            return Err(ForwardError::Invalid(
+                "不能转发到原消息所在的房间".to_string(),
+            ));
+        }
+        self.check_target(user, &req.room_id).await?;
+
+        let reference = MessageReference::forward_of(&source, &room_name);
+        let message = self
+            .send(user, &req.room_id, &source.content, reference)
+            .await?;
+        println!(
+            "用户 {} 把消息 {} 转发到房间 {}",
+            user.id, source.id, message.room_id
+        );
+        Ok(message)
+    }
+
+    /// 引用回复，目标房间可以是原消息所在的房间
+    pub async fn quote(
+        &self,
+        user: &User,
+        message_id: &str,
+        req: QuoteMessage,
+    ) -> ForwardResult<Message> {
+        let content = req.content.trim();
+        if content.is_empty() {
+            return Err(ForwardError::Invalid("回复内容不能为空".to_string()));
+        }
+
+        let (source, room_name) = self.find_readable(user, message_id).await?;
+        if matches!(source.message_type, MessageType::System) {
+            return Err(ForwardError::Invalid("系统消息不能引用".to_string()));
+        }
+        self.check_target(user, &req.room_id).await?;
+
+        let mut reference = MessageReference::new(ReferenceKind::Quote, &source, &room_name);
+        if reference.room_id != req.room_id {
+            // 快照会显示在目标房间，同样要经过目标房间的内容过滤
+            reference.content = self.filter(&req.room_id, &reference.content).await?;
+        }
+        self.send(user, &req.room_id, content, reference).await
+    }
+
+    /// 看不到所在房间的消息按不存在处理，同时返回所在房间的名称
+    async fn find_readable(
+        &self,
+        user: &User,
+        message_id: &str,
+    ) -> ForwardResult<(Message, String)> {
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await?
+            .ok_or(ForwardError::NotFound)?;
+        if !self.room_repo.can_read(&user.id, &message.room_id).await? {
+            return Err(ForwardError::NotFound);
+        }
+
+        let room = self
+            .room_repo
+            .find_by_id(&message.room_id)
+            .await?
+            .ok_or(ForwardError::NotFound)?;
+        Ok((message, room.name))
+    }
+
+    /// 只能发到自己所在的房间（创建者视为成员），版主和管理员不受限制；禁言和限流在发送时检查
+    async fn check_target(&self, user: &User, room_id: &str) -> ForwardResult<()> {
+        if !self.room_repo.can_read(&user.id, room_id).await? {
+            return Err(ForwardError::RoomNotFound);
+        }
+        if !user.is_moderator() && !self.room_repo.is_member(&user.id, room_id).await? {
+            return Err(ForwardError::NotMember);
+        }
+        Ok(())
+    }
+
+    /// 与普通消息走同样的检查、过滤和广播
+    async fn send(
+        &self,
+        user: &User,
+        room_id: &str,
+        content: &str,
+        reference: MessageReference,
+    ) -> ForwardResult<Message> {
+        if let Some(rejection) = self.send_guard.check(user, room_id, content).await? {
+            return Err(ForwardError::Rejected(rejection));
+        }
+
+        let (content, flags) = match self.content_filter.check(room_id, content).await? {
+            FilterDecision::Allow { content, flags } => (content, flags),
+            FilterDecision::Reject { reason, .. } => {
+                return Err(ForwardError::Invalid(format!("消息被拒绝: {}", reason)));
+            }
+        };
+
+        let message = Message::new(
+            user.id.clone(),
+            user.username.clone(),
+            content,
+            room_id.to_string(),
+            MessageType::Text,
+        )
+        .with_reference(&reference);
+        let message = self.message_repo.create(message).await?;
+
+        if let Err(e) = self.content_filter.record_flags(&message, &flags).await {
+            eprintln!("保存过滤标记失败: {}", e);
+        }
+        self.link_unfurler.enqueue(&message);
+
+        let event = WebSocketMessage::ChatMessage {
+            message_id: Some(message.id.clone()),
+            room_id: message.room_id.clone(),
+            user_id: message.user_id.clone(),
+            username: message.username.clone(),
+            content: message.content.clone(),
+            content_html: message.content_html.clone(),
+            message_type: message.message_type.to_string(),
+            attachment_ids: Vec::new(),
+            attachments: Vec::new(),
+            poll: None,
+            ttl_seconds: None,
+            expires_at: message.expires_at.map(|t| t.timestamp()),
+            reference: Some(reference),
+        };
+        if let Err(e) = self.event_bus.publish(&message.room_id, event).await {
+            eprintln!("广播消息失败: {}", e);
+        }
+        Ok(message)
+    }
+
+    /// 快照内容按目标房间的规则过滤，被拒绝时不能引用
+    async fn filter(&self, room_id: &str, content: &str) -> ForwardResult<String> {
+        match self.content_filter.check(room_id, content).await? {
+            FilterDecision::Allow { content, .. } => Ok(content),
+            FilterDecision::Reject { .. } => Err(ForwardError::Invalid(
+                "被引用的消息不符合目标房间的内容规则".to_string(),
+            )),
+        }
+    }
+}
@@ -51,4 +51,18 @@ impl AuthService {
 
         Ok(token_data.claims)
     }
+
+    /// 从gRPC请求的authorization元数据中校验Bearer令牌，返回令牌中的用户ID
+    pub fn authenticate<T>(&self, request: &tonic::Request<T>) -> Result<String, tonic::Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| tonic::Status::unauthenticated("Missing bearer token"))?;
+
+        self.verify_token(token)
+            .map(|claims| claims.user_id)
+            .map_err(|_| tonic::Status::unauthenticated("Invalid token"))
+    }
 }
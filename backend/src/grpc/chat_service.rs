@@ -1,26 +1,87 @@
+use crate::audit::AuditLogger;
+use crate::bookmarks::{BookmarkError, BookmarkService};
 use crate::chat::{chat_service_server::ChatService, *};
-use crate::database::{DbPool, MessageRepository, UserRepository};
+use crate::commands::{CommandDispatch, CommandError, CommandRegistry};
+use crate::database::{
+    AttachmentRepository, DbPool, LinkPreviewRepository, MessageRepository, PollRepository,
+    RoomRepository, UserRepository,
+};
+use crate::forwarding::{ForwardError, ForwardService};
 use crate::grpc::auth::AuthService;
-use crate::models::{Message, MessageType};
-use crate::redis::SessionManager;
+use crate::grpc::{check_ip_rate_limit, remote_ip, resource_exhausted};
+use crate::models::{
+    AuditAction, AuditEntry, CreatePoll, ForwardMessage, Message, MessageHistoryQuery,
+    MessageSearchQuery, MessageType, QuoteMessage, ResolveReport, SaveBookmark, ScheduleMessage,
+    SubmitReport, UpdateScheduledMessage, User, validate_message_ttl,
+};
+use crate::moderation::{
+    ContentFilter, FilterDecision, ReportError, ReportService, SendGuard, SendRejection,
+};
+use crate::pins::{PinError, PinService};
+use crate::polls::{PollError, PollService};
+use crate::redis::{RateLimiter, SessionManager};
+use crate::scheduling::{ScheduleError, ScheduledMessageService};
+use crate::unfurl::LinkUnfurler;
+use crate::websocket::{BroadcastHandler, TypingTracker, WebSocketMessage};
 use redis::Client as RedisClient;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::broadcast;
-use tonic::{Request, Response, Status, Streaming};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
 
 pub struct ChatServiceImpl {
     message_repo: MessageRepository,
     user_repo: UserRepository,
+    room_repo: RoomRepository,
+    attachment_repo: AttachmentRepository,
+    link_preview_repo: LinkPreviewRepository,
+    poll_repo: PollRepository,
     session_manager: SessionManager,
     auth_service: AuthService,
     // 广播通道用于实时消息推送
     message_senders: Arc<tokio::sync::Mutex<HashMap<String, broadcast::Sender<ChatMessage>>>>,
+    // 与WebSocket共享的房间广播，用于推送房间事件
+    broadcast_handler: Arc<tokio::sync::Mutex<BroadcastHandler>>,
+    typing_tracker: TypingTracker,
+    link_unfurler: LinkUnfurler,
+    content_filter: ContentFilter,
+    send_guard: SendGuard,
+    rate_limiter: RateLimiter,
+    report_service: ReportService,
+    audit_logger: AuditLogger,
+    poll_service: PollService,
+    scheduled_service: ScheduledMessageService,
+    pin_service: PinService,
+    bookmark_service: BookmarkService,
+    forward_service: ForwardService,
+    command_registry: CommandRegistry,
 }
 
 impl ChatServiceImpl {
-    pub fn new(pool: DbPool, redis_client: RedisClient) -> Self {
+    pub fn new(
+        pool: DbPool,
+        redis_client: RedisClient,
+        broadcast_handler: Arc<tokio::sync::Mutex<BroadcastHandler>>,
+        typing_tracker: TypingTracker,
+        link_unfurler: LinkUnfurler,
+        content_filter: ContentFilter,
+        send_guard: SendGuard,
+        rate_limiter: RateLimiter,
+        report_service: ReportService,
+        audit_logger: AuditLogger,
+        poll_service: PollService,
+        scheduled_service: ScheduledMessageService,
+        pin_service: PinService,
+        bookmark_service: BookmarkService,
+        forward_service: ForwardService,
+        command_registry: CommandRegistry,
+    ) -> Self {
         let message_repo = MessageRepository::new(pool.clone());
+        let room_repo = RoomRepository::new(pool.clone());
+        let attachment_repo = AttachmentRepository::new(pool.clone());
+        let link_preview_repo = LinkPreviewRepository::new(pool.clone());
+        let poll_repo = PollRepository::new(pool.clone());
         let user_repo = UserRepository::new(pool);
         let session_manager = SessionManager::new(redis_client);
         let auth_service = AuthService::new(
@@ -30,12 +91,74 @@ impl ChatServiceImpl {
         Self {
             message_repo,
             user_repo,
+            room_repo,
+            attachment_repo,
+            link_preview_repo,
+            poll_repo,
             session_manager,
             auth_service,
             message_senders: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            broadcast_handler,
+            typing_tracker,
+            link_unfurler,
+            content_filter,
+            send_guard,
+            rate_limiter,
+            report_service,
+            audit_logger,
+            poll_service,
+            scheduled_service,
+            pin_service,
+            bookmark_service,
+            forward_service,
+            command_registry,
         }
     }
 
+    async fn find_user(&self, user_id: &str) -> Result<User, Status> {
+        self.user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| Status::internal(format!("Database error: {}", e)))?
+            .ok_or_else(|| Status::not_found("User not found"))
+    }
+
+    /// 调用方以authorization令牌中的用户为准，请求中的user_id被忽略
+    async fn authenticated_user<T>(&self, request: &Request<T>) -> Result<User, Status> {
+        let user_id = self.auth_service.authenticate(request)?;
+        let user = self.find_user(&user_id).await?;
+        if user.is_banned() {
+            return Err(Status::permission_denied("User is banned"));
+        }
+        Ok(user)
+    }
+
+    /// 审核接口的操作者以令牌为准，请求中的moderator_id必须与令牌一致
+    async fn authenticated_moderator<T>(
+        &self,
+        request: &Request<T>,
+        moderator_id: &str,
+    ) -> Result<User, Status> {
+        let moderator = self.authenticated_user(request).await?;
+        if !moderator_id.is_empty() && moderator_id != moderator.id {
+            return Err(Status::permission_denied("Moderator does not match token"));
+        }
+        Ok(moderator)
+    }
+
+    /// 只能在可读的房间中发送输入状态和订阅房间事件
+    async fn check_readable(&self, user_id: &str, room_id: &str) -> Result<(), Status> {
+        let readable = self
+            .room_repo
+            .can_read(user_id, room_id)
+            .await
+            .map_err(|e| Status::internal(format!("Database error: {}", e)))?;
+        if !readable {
+            return Err(Status::permission_denied("No access to room"));
+        }
+        Ok(())
+    }
+
     fn get_or_create_room_sender(&self, room_id: &str) -> broadcast::Sender<ChatMessage> {
         let mut senders = futures::executor::block_on(self.message_senders.lock());
         senders
@@ -51,25 +174,82 @@ impl ChatService for ChatServiceImpl {
         &self,
         request: Request<SendMessageRequest>,
     ) -> Result<Response<SendMessageResponse>, Status> {
+        let ip = remote_ip(&request);
+        let user = self.authenticated_user(&request).await?;
         let req = request.into_inner();
 
-        // 获取用户信息
-        let user = self
-            .user_repo
-            .find_by_id(&req.user_id)
+        // 以/开头的消息先交给命令注册表，命令结果直接返回给调用方
+        let content = match self
+            .command_registry
+            .dispatch(&user, &req.room_id, &req.content, Some(&ip))
+            .await
+            .map_err(command_status)?
+        {
+            CommandDispatch::Message(content) => content,
+            CommandDispatch::Reply(reply) => {
+                return Ok(Response::new(SendMessageResponse {
+                    success: true,
+                    message: reply.message,
+                    chat_message: None,
+                    command: reply.command,
+                }));
+            }
+        };
+
+        let message_type = MessageType::from(req.message_type);
+        if matches!(message_type, MessageType::Poll) {
+            return Err(Status::invalid_argument("Use CreatePoll to create polls"));
+        }
+        validate_message_ttl(req.ttl_seconds).map_err(Status::invalid_argument)?;
+
+        // 附件必须是该用户在该房间上传且尚未发送的
+        let attachments = if req.attachment_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.attachment_repo
+                .find_pending(&req.attachment_ids, &user.id, &req.room_id)
+                .await
+                .map_err(|e| Status::internal(format!("Database error: {}", e)))?
+                .ok_or_else(|| Status::invalid_argument("Invalid or already used attachment"))?
+        };
+
+        // 禁言、发送频率、房间慢速模式和垃圾消息检测
+        match self
+            .send_guard
+            .check(&user, &req.room_id, &content)
+            .await
+            .map_err(|e| Status::internal(format!("Database error: {}", e)))?
+        {
+            Some(SendRejection::RateLimited {
+                reason,
+                retry_after_secs,
+            }) => return Err(resource_exhausted(&reason, retry_after_secs)),
+            Some(rejection) => {
+                return Err(Status::permission_denied(rejection.message()));
+            }
+            None => {}
+        }
+
+        // 保存前执行内容过滤
+        let (content, flags) = match self
+            .content_filter
+            .check(&req.room_id, &content)
             .await
             .map_err(|e| Status::internal(format!("Database error: {}", e)))?
-            .ok_or_else(|| Status::not_found("User not found"))?;
+        {
+            FilterDecision::Allow { content, flags } => (content, flags),
+            FilterDecision::Reject { reason, .. } => {
+                return Err(Status::invalid_argument(format!(
+                    "Message rejected: {}",
+                    reason
+                )));
+            }
+        };
 
         // 创建消息
         let room_id = req.room_id.clone();
-        let message = Message::new(
-            req.user_id,
-            user.username,
-            req.content,
-            req.room_id,
-            MessageType::from(req.message_type),
-        );
+        let message = Message::new(user.id, user.username, content, req.room_id, message_type)
+            .with_ttl(req.ttl_seconds);
 
         // 保存消息到数据库
         let saved_message = self
@@ -78,8 +258,24 @@ impl ChatService for ChatServiceImpl {
             .await
             .map_err(|e| Status::internal(format!("Failed to save message: {}", e)))?;
 
+        if !attachments.is_empty() {
+            self.attachment_repo
+                .attach_to_message(&saved_message.id, &req.attachment_ids)
+                .await
+                .map_err(|e| Status::internal(format!("Failed to attach files: {}", e)))?;
+        }
+        if let Err(e) = self
+            .content_filter
+            .record_flags(&saved_message, &flags)
+            .await
+        {
+            eprintln!("保存过滤标记失败: {}", e);
+        }
+        self.link_unfurler.enqueue(&saved_message);
+
         // 广播消息到房间
-        let grpc_message = saved_message.to_grpc();
+        let mut grpc_message = saved_message.to_grpc();
+        grpc_message.attachments = attachments.iter().map(|a| a.to_grpc()).collect();
         let sender = self.get_or_create_room_sender(&room_id);
         let _ = sender.send(grpc_message.clone());
 
@@ -87,47 +283,60 @@ impl ChatService for ChatServiceImpl {
             success: true,
             message: "Message sent successfully".to_string(),
             chat_message: Some(grpc_message),
+            command: String::new(),
         }))
     }
 
-    type GetMessagesStream = Streaming<ChatMessage>;
-
     async fn get_messages(
         &self,
         request: Request<GetMessagesRequest>,
-    ) -> Result<Response<Streaming<ChatMessage>>, Status> {
+    ) -> Result<Response<GetMessagesResponse>, Status> {
         let req = request.into_inner();
 
-        // 获取历史消息
-        let messages = self
+        let query = MessageHistoryQuery::from_params(
+            Some(&req.before),
+            Some(&req.after),
+            Some(&req.around),
+            Some(req.limit),
+        )
+        .map_err(Status::invalid_argument)?;
+
+        let mut page = self
             .message_repo
-            .get_messages_by_room(
-                &req.room_id,
-                req.limit,
-                if req.before_timestamp > 0 {
-                    Some(req.before_timestamp)
-                } else {
-                    None
-                },
-            )
+            .get_messages_by_room(&req.room_id, &query)
             .await
             .map_err(|e| Status::internal(format!("Failed to get messages: {}", e)))?;
+        page.attachments = self
+            .attachment_repo
+            .find_by_message_ids(&page.message_ids())
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get attachments: {}", e)))?;
+        page.link_previews = self
+            .link_preview_repo
+            .find_by_message_ids(&page.message_ids())
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get link previews: {}", e)))?;
+        page.polls = self
+            .poll_repo
+            .find_infos_by_message_ids(&page.message_ids())
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get polls: {}", e)))?;
 
-        // 创建流
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-
-        // 发送历史消息
-        tokio::spawn(async move {
-            for message in messages.into_iter().rev() {
-                // 按时间顺序发送
-                if tx.send(message.to_grpc()).await.is_err() {
-                    break;
-                }
-            }
-        });
-
-        // 暂时返回错误，稍后修复gRPC Streaming
-        Err(Status::unimplemented("gRPC Streaming not implemented yet"))
+        Ok(Response::new(GetMessagesResponse {
+            success: true,
+            message: "Messages retrieved".to_string(),
+            messages: page
+                .messages
+                .iter()
+                .map(|m| {
+                    m.to_grpc_with_details(&page.attachments, &page.link_previews, &page.polls)
+                })
+                .collect(),
+            before_cursor: page.before_cursor().unwrap_or_default(),
+            after_cursor: page.after_cursor().unwrap_or_default(),
+            has_more_before: page.has_more_before,
+            has_more_after: page.has_more_after,
+        }))
     }
 
     async fn get_online_users(
@@ -162,30 +371,68 @@ impl ChatService for ChatServiceImpl {
         &self,
         request: Request<JoinRoomRequest>,
     ) -> Result<Response<JoinRoomResponse>, Status> {
+        let ip = remote_ip(&request);
+        let user = self.authenticated_user(&request).await?;
         let req = request.into_inner();
 
+        // 私有房间只允许创建者和已有成员加入，检查通过后才记录成员关系
+        let room = self
+            .room_repo
+            .find_by_id(&req.room_id)
+            .await
+            .map_err(|e| Status::internal(format!("Database error: {}", e)))?
+            .ok_or_else(|| Status::not_found("Room not found"))?;
+        if !self
+            .room_repo
+            .can_join(&user.id, &room)
+            .await
+            .map_err(|e| Status::internal(format!("Database error: {}", e)))?
+        {
+            return Err(Status::permission_denied("Not allowed to join this room"));
+        }
+
         // 将用户添加到房间
         self.session_manager
-            .add_user_to_room(&req.user_id, &req.room_id)
+            .add_user_to_room(&user.id, &req.room_id)
             .await
             .map_err(|e| Status::internal(format!("Failed to join room: {}", e)))?;
 
+        // 记录房间成员关系
+        self.room_repo
+            .add_member(&req.room_id, &user.id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to save room member: {}", e)))?;
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::MemberJoined)
+                .actor(&user.id)
+                .target("room", &req.room_id)
+                .ip(Some(&ip))
+                .details(serde_json::json!({ "via": "grpc" })),
+        );
+
         // 更新用户会话中的房间信息
         if let Some(session) = self
             .session_manager
-            .get_session(&req.user_id)
+            .get_session(&user.id)
             .await
             .map_err(|e| Status::internal(format!("Failed to get session: {}", e)))?
         {
             self.session_manager
-                .update_session_room(&req.user_id, Some(req.room_id.clone()))
+                .update_session_room(&user.id, Some(req.room_id.clone()))
                 .await
                 .map_err(|e| Status::internal(format!("Failed to update session: {}", e)))?;
         }
 
+        let pins = self
+            .pin_service
+            .room_pins(&req.room_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get pinned messages: {}", e)))?;
+
         Ok(Response::new(JoinRoomResponse {
             success: true,
             message: "Joined room successfully".to_string(),
+            pins: pins.into_iter().map(|pin| pin.into_grpc()).collect(),
         }))
     }
 
@@ -193,17 +440,26 @@ impl ChatService for ChatServiceImpl {
         &self,
         request: Request<LeaveRoomRequest>,
     ) -> Result<Response<LeaveRoomResponse>, Status> {
+        let ip = remote_ip(&request);
+        let user = self.authenticated_user(&request).await?;
         let req = request.into_inner();
 
         // 从房间移除用户
         self.session_manager
-            .remove_user_from_room(&req.user_id, &req.room_id)
+            .remove_user_from_room(&user.id, &req.room_id)
             .await
             .map_err(|e| Status::internal(format!("Failed to leave room: {}", e)))?;
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::MemberLeft)
+                .actor(&user.id)
+                .target("room", &req.room_id)
+                .ip(Some(&ip))
+                .details(serde_json::json!({ "via": "grpc" })),
+        );
 
         // 更新用户会话
         self.session_manager
-            .update_session_room(&req.user_id, None)
+            .update_session_room(&user.id, None)
             .await
             .map_err(|e| Status::internal(format!("Failed to update session: {}", e)))?;
 
@@ -212,4 +468,726 @@ impl ChatService for ChatServiceImpl {
             message: "Left room successfully".to_string(),
         }))
     }
+
+    async fn set_typing(
+        &self,
+        request: Request<SetTypingRequest>,
+    ) -> Result<Response<SetTypingResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+        self.check_readable(&user.id, &req.room_id).await?;
+
+        let result = if req.is_typing {
+            self.typing_tracker
+                .start_typing(&req.room_id, &user.id, &user.username)
+                .await
+        } else {
+            self.typing_tracker
+                .stop_typing(&req.room_id, &user.id)
+                .await
+        };
+
+        result.map_err(|e| Status::internal(format!("Failed to update typing state: {}", e)))?;
+
+        Ok(Response::new(SetTypingResponse {
+            success: true,
+            message: "Typing state updated".to_string(),
+        }))
+    }
+
+    async fn search_messages(
+        &self,
+        request: Request<SearchMessagesRequest>,
+    ) -> Result<Response<SearchMessagesResponse>, Status> {
+        check_ip_rate_limit(&self.rate_limiter, "search", &request).await?;
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        // 只在用户可读的房间内搜索
+        let room_ids = if req.room_id.is_empty() {
+            self.room_repo
+                .get_readable_room_ids(&user.id)
+                .await
+                .map_err(|e| Status::internal(format!("Database error: {}", e)))?
+        } else {
+            let readable = self
+                .room_repo
+                .can_read(&user.id, &req.room_id)
+                .await
+                .map_err(|e| Status::internal(format!("Database error: {}", e)))?;
+            if !readable {
+                return Err(Status::permission_denied("No access to room"));
+            }
+            vec![req.room_id]
+        };
+
+        let message_type = if req.message_type.is_empty() {
+            None
+        } else {
+            Some(
+                MessageType::parse(&req.message_type)
+                    .ok_or_else(|| Status::invalid_argument("Invalid message type"))?,
+            )
+        };
+
+        let mut query =
+            MessageSearchQuery::new(req.query, room_ids).with_page(req.page, req.page_size);
+        query.user_id = (!req.author_id.is_empty()).then_some(req.author_id);
+        query.message_type = message_type;
+        query.from = (req.from_timestamp > 0)
+            .then(|| chrono::DateTime::from_timestamp(req.from_timestamp, 0))
+            .flatten();
+        query.to = (req.to_timestamp > 0)
+            .then(|| chrono::DateTime::from_timestamp(req.to_timestamp, 0))
+            .flatten();
+
+        let page = self
+            .message_repo
+            .search_messages(&query)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to search messages: {}", e)))?;
+
+        Ok(Response::new(SearchMessagesResponse {
+            success: true,
+            message: "Search completed".to_string(),
+            hits: page
+                .hits
+                .into_iter()
+                .map(|hit| SearchHit {
+                    message: Some(hit.message.to_grpc()),
+                    snippet: hit.snippet,
+                })
+                .collect(),
+            total: page.total,
+            page: page.page,
+            page_size: page.page_size,
+        }))
+    }
+
+    type SubscribeRoomEventsStream = ReceiverStream<Result<RoomEvent, Status>>;
+
+    async fn subscribe_room_events(
+        &self,
+        request: Request<SubscribeRoomEventsRequest>,
+    ) -> Result<Response<Self::SubscribeRoomEventsStream>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+        self.check_readable(&user.id, &req.room_id).await?;
+
+        let mut receiver = {
+            let mut broadcast_handler = self.broadcast_handler.lock().await;
+            broadcast_handler
+                .get_or_create_room_channel(&req.room_id)
+                .subscribe()
+        };
+
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) => {
+                        // 不回显订阅者自己的事件
+                        if message_user_id(&message) == Some(user.id.as_str()) {
+                            continue;
+                        }
+                        if let Some(event) = to_room_event(&req.room_id, message) {
+                            if tx.send(Ok(event)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn submit_report(
+        &self,
+        request: Request<SubmitReportRequest>,
+    ) -> Result<Response<SubmitReportResponse>, Status> {
+        let reporter = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let report = self
+            .report_service
+            .submit(
+                &reporter,
+                SubmitReport {
+                    target_type: req.target_type,
+                    message_id: Some(req.message_id),
+                    user_id: Some(req.target_user_id),
+                    reason: req.reason,
+                },
+            )
+            .await
+            .map_err(report_status)?;
+
+        Ok(Response::new(SubmitReportResponse {
+            success: true,
+            message: "Report submitted".to_string(),
+            report: Some(report.to_grpc()),
+        }))
+    }
+
+    async fn list_reports(
+        &self,
+        request: Request<ListReportsRequest>,
+    ) -> Result<Response<ListReportsResponse>, Status> {
+        let moderator = self
+            .authenticated_moderator(&request, &request.get_ref().moderator_id)
+            .await?;
+        let req = request.into_inner();
+
+        let reports = self
+            .report_service
+            .list(&moderator, Some(&req.status), req.limit, req.offset)
+            .await
+            .map_err(report_status)?;
+
+        Ok(Response::new(ListReportsResponse {
+            reports: reports.iter().map(|r| r.to_grpc()).collect(),
+        }))
+    }
+
+    async fn claim_report(
+        &self,
+        request: Request<ClaimReportRequest>,
+    ) -> Result<Response<ClaimReportResponse>, Status> {
+        let ip = remote_ip(&request);
+        let moderator = self
+            .authenticated_moderator(&request, &request.get_ref().moderator_id)
+            .await?;
+        let req = request.into_inner();
+
+        let report = self
+            .report_service
+            .claim(&moderator, &req.report_id, Some(&ip))
+            .await
+            .map_err(report_status)?;
+
+        Ok(Response::new(ClaimReportResponse {
+            report: Some(report.to_grpc()),
+        }))
+    }
+
+    async fn resolve_report(
+        &self,
+        request: Request<ResolveReportRequest>,
+    ) -> Result<Response<ResolveReportResponse>, Status> {
+        let ip = remote_ip(&request);
+        let moderator = self
+            .authenticated_moderator(&request, &request.get_ref().moderator_id)
+            .await?;
+        let req = request.into_inner();
+
+        let report = self
+            .report_service
+            .resolve(
+                &moderator,
+                &req.report_id,
+                ResolveReport {
+                    action: req.action,
+                    note: Some(req.note),
+                    mute_seconds: (req.mute_seconds > 0).then_some(req.mute_seconds),
+                },
+                Some(&ip),
+            )
+            .await
+            .map_err(report_status)?;
+
+        Ok(Response::new(ResolveReportResponse {
+            report: Some(report.to_grpc()),
+        }))
+    }
+
+    async fn create_poll(
+        &self,
+        request: Request<CreatePollRequest>,
+    ) -> Result<Response<CreatePollResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let (message, poll) = self
+            .poll_service
+            .create(
+                &user,
+                &req.room_id,
+                CreatePoll {
+                    question: req.question,
+                    options: req.options,
+                    multi_choice: req.multi_choice,
+                    anonymous: req.anonymous,
+                    closes_at: (req.closes_at > 0).then_some(req.closes_at),
+                },
+            )
+            .await
+            .map_err(poll_status)?;
+
+        let mut grpc_message = message.to_grpc();
+        grpc_message.poll = Some(poll.into_grpc());
+        let sender = self.get_or_create_room_sender(&req.room_id);
+        let _ = sender.send(grpc_message.clone());
+
+        Ok(Response::new(CreatePollResponse {
+            chat_message: Some(grpc_message),
+        }))
+    }
+
+    async fn vote_poll(
+        &self,
+        request: Request<VotePollRequest>,
+    ) -> Result<Response<VotePollResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let poll = self
+            .poll_service
+            .vote(&user, &req.poll_id, req.option_ids)
+            .await
+            .map_err(poll_status)?;
+
+        Ok(Response::new(VotePollResponse {
+            poll: Some(poll.into_grpc()),
+        }))
+    }
+
+    async fn close_poll(
+        &self,
+        request: Request<ClosePollRequest>,
+    ) -> Result<Response<ClosePollResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let poll = self
+            .poll_service
+            .close(&user, &req.poll_id)
+            .await
+            .map_err(poll_status)?;
+
+        Ok(Response::new(ClosePollResponse {
+            poll: Some(poll.into_grpc()),
+        }))
+    }
+
+    async fn schedule_message(
+        &self,
+        request: Request<ScheduleMessageRequest>,
+    ) -> Result<Response<ScheduleMessageResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let scheduled = self
+            .scheduled_service
+            .schedule(
+                &user,
+                &req.room_id,
+                ScheduleMessage {
+                    content: req.content,
+                    send_at: req.send_at,
+                },
+            )
+            .await
+            .map_err(schedule_status)?;
+
+        Ok(Response::new(ScheduleMessageResponse {
+            scheduled_message: Some(scheduled.to_grpc()),
+        }))
+    }
+
+    async fn list_scheduled_messages(
+        &self,
+        request: Request<ListScheduledMessagesRequest>,
+    ) -> Result<Response<ListScheduledMessagesResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let scheduled = self
+            .scheduled_service
+            .list(&user, Some(&req.status), req.limit, req.offset)
+            .await
+            .map_err(schedule_status)?;
+
+        Ok(Response::new(ListScheduledMessagesResponse {
+            scheduled_messages: scheduled.iter().map(|s| s.to_grpc()).collect(),
+        }))
+    }
+
+    async fn update_scheduled_message(
+        &self,
+        request: Request<UpdateScheduledMessageRequest>,
+    ) -> Result<Response<UpdateScheduledMessageResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let scheduled = self
+            .scheduled_service
+            .update(
+                &user,
+                &req.id,
+                UpdateScheduledMessage {
+                    content: (!req.content.is_empty()).then_some(req.content),
+                    send_at: (req.send_at > 0).then_some(req.send_at),
+                },
+            )
+            .await
+            .map_err(schedule_status)?;
+
+        Ok(Response::new(UpdateScheduledMessageResponse {
+            scheduled_message: Some(scheduled.to_grpc()),
+        }))
+    }
+
+    async fn cancel_scheduled_message(
+        &self,
+        request: Request<CancelScheduledMessageRequest>,
+    ) -> Result<Response<CancelScheduledMessageResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let scheduled = self
+            .scheduled_service
+            .cancel(&user, &req.id)
+            .await
+            .map_err(schedule_status)?;
+
+        Ok(Response::new(CancelScheduledMessageResponse {
+            scheduled_message: Some(scheduled.to_grpc()),
+        }))
+    }
+
+    async fn pin_message(
+        &self,
+        request: Request<PinMessageRequest>,
+    ) -> Result<Response<PinMessageResponse>, Status> {
+        let ip = remote_ip(&request);
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let pin = self
+            .pin_service
+            .pin(&user, &req.message_id, Some(&ip))
+            .await
+            .map_err(pin_status)?;
+
+        Ok(Response::new(PinMessageResponse {
+            pin: Some(pin.into_grpc()),
+        }))
+    }
+
+    async fn unpin_message(
+        &self,
+        request: Request<UnpinMessageRequest>,
+    ) -> Result<Response<UnpinMessageResponse>, Status> {
+        let ip = remote_ip(&request);
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        self.pin_service
+            .unpin(&user, &req.message_id, Some(&ip))
+            .await
+            .map_err(pin_status)?;
+
+        Ok(Response::new(UnpinMessageResponse { success: true }))
+    }
+
+    async fn list_pinned_messages(
+        &self,
+        request: Request<ListPinnedMessagesRequest>,
+    ) -> Result<Response<ListPinnedMessagesResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let pins = self
+            .pin_service
+            .list(&user, &req.room_id)
+            .await
+            .map_err(pin_status)?;
+
+        Ok(Response::new(ListPinnedMessagesResponse {
+            pins: pins.into_iter().map(|pin| pin.into_grpc()).collect(),
+        }))
+    }
+
+    async fn save_bookmark(
+        &self,
+        request: Request<SaveBookmarkRequest>,
+    ) -> Result<Response<SaveBookmarkResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let bookmark = self
+            .bookmark_service
+            .save(
+                &user,
+                &req.message_id,
+                SaveBookmark {
+                    note: Some(req.note),
+                },
+            )
+            .await
+            .map_err(bookmark_status)?;
+
+        Ok(Response::new(SaveBookmarkResponse {
+            bookmark: Some(bookmark.into_grpc()),
+        }))
+    }
+
+    async fn remove_bookmark(
+        &self,
+        request: Request<RemoveBookmarkRequest>,
+    ) -> Result<Response<RemoveBookmarkResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        self.bookmark_service
+            .remove(&user, &req.message_id)
+            .await
+            .map_err(bookmark_status)?;
+
+        Ok(Response::new(RemoveBookmarkResponse { success: true }))
+    }
+
+    async fn list_bookmarks(
+        &self,
+        request: Request<ListBookmarksRequest>,
+    ) -> Result<Response<ListBookmarksResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let page = self
+            .bookmark_service
+            .list(&user, Some(&req.room_id), req.limit, req.offset)
+            .await
+            .map_err(bookmark_status)?;
+
+        Ok(Response::new(ListBookmarksResponse {
+            bookmarks: page
+                .bookmarks
+                .into_iter()
+                .map(|bookmark| bookmark.into_grpc())
+                .collect(),
+            total: page.total,
+        }))
+    }
+
+    async fn forward_message(
+        &self,
+        request: Request<ForwardMessageRequest>,
+    ) -> Result<Response<ForwardMessageResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let message = self
+            .forward_service
+            .forward(
+                &user,
+                &req.message_id,
+                ForwardMessage {
+                    room_id: req.room_id,
+                },
+            )
+            .await
+            .map_err(forward_status)?;
+
+        Ok(Response::new(ForwardMessageResponse {
+            message: Some(message.to_grpc()),
+        }))
+    }
+
+    async fn quote_message(
+        &self,
+        request: Request<QuoteMessageRequest>,
+    ) -> Result<Response<QuoteMessageResponse>, Status> {
+        let user = self.authenticated_user(&request).await?;
+        let req = request.into_inner();
+
+        let message = self
+            .forward_service
+            .quote(
+                &user,
+                &req.message_id,
+                QuoteMessage {
+                    room_id: req.room_id,
+                    content: req.content,
+                },
+            )
+            .await
+            .map_err(forward_status)?;
+
+        Ok(Response::new(QuoteMessageResponse {
+            message: Some(message.to_grpc()),
+        }))
+    }
+}
+
+fn report_status(error: ReportError) -> Status {
+    match error {
+        ReportError::Invalid(message) => Status::invalid_argument(message),
+        ReportError::NotFound => Status::not_found(error.to_string()),
+        ReportError::Forbidden => Status::permission_denied(error.to_string()),
+        ReportError::Conflict => Status::failed_precondition(error.to_string()),
+        ReportError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+fn poll_status(error: PollError) -> Status {
+    match error {
+        PollError::Invalid(message) => Status::invalid_argument(message),
+        PollError::NotFound => Status::not_found(error.to_string()),
+        PollError::Forbidden => Status::permission_denied(error.to_string()),
+        PollError::Closed | PollError::AlreadyVoted => {
+            Status::failed_precondition(error.to_string())
+        }
+        PollError::Rejected(SendRejection::RateLimited {
+            reason,
+            retry_after_secs,
+        }) => resource_exhausted(&reason, retry_after_secs),
+        PollError::Rejected(rejection) => Status::permission_denied(rejection.message()),
+        PollError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+fn schedule_status(error: ScheduleError) -> Status {
+    match error {
+        ScheduleError::Invalid(message) => Status::invalid_argument(message),
+        ScheduleError::NotFound => Status::not_found(error.to_string()),
+        ScheduleError::Conflict => Status::failed_precondition(error.to_string()),
+        ScheduleError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+/// 获取房间事件的发起用户
+fn message_user_id(message: &WebSocketMessage) -> Option<&str> {
+    match message {
+        WebSocketMessage::TypingStart { user_id, .. }
+        | WebSocketMessage::TypingStop { user_id, .. } => Some(user_id),
+        _ => None,
+    }
+}
+
+fn pin_status(error: PinError) -> Status {
+    match error {
+        PinError::NotFound | PinError::RoomNotFound => Status::not_found(error.to_string()),
+        PinError::Forbidden => Status::permission_denied(error.to_string()),
+        PinError::AlreadyPinned | PinError::NotPinned | PinError::LimitReached => {
+            Status::failed_precondition(error.to_string())
+        }
+        PinError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+fn bookmark_status(error: BookmarkError) -> Status {
+    match error {
+        BookmarkError::Invalid(message) => Status::invalid_argument(message),
+        BookmarkError::NotFound | BookmarkError::NotBookmarked => {
+            Status::not_found(error.to_string())
+        }
+        BookmarkError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+fn forward_status(error: ForwardError) -> Status {
+    match error {
+        ForwardError::Invalid(message) => Status::invalid_argument(message),
+        ForwardError::NotFound | ForwardError::RoomNotFound => Status::not_found(error.to_string()),
+        ForwardError::NotMember => Status::permission_denied(error.to_string()),
+        ForwardError::Rejected(SendRejection::RateLimited {
+            reason,
+            retry_after_secs,
+        }) => resource_exhausted(&reason, retry_after_secs),
+        ForwardError::Rejected(rejection) => Status::permission_denied(rejection.message()),
+        ForwardError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+fn command_status(error: CommandError) -> Status {
+    match error {
+        CommandError::Unknown(_) | CommandError::Usage(_) | CommandError::Invalid(_) => {
+            Status::invalid_argument(error.to_string())
+        }
+        CommandError::Forbidden => Status::permission_denied(error.to_string()),
+        CommandError::Rejected(SendRejection::RateLimited {
+            reason,
+            retry_after_secs,
+        }) => resource_exhausted(&reason, retry_after_secs),
+        CommandError::Rejected(rejection) => Status::permission_denied(rejection.message()),
+        CommandError::Database(e) => Status::internal(format!("Database error: {}", e)),
+    }
+}
+
+/// 把WebSocket房间事件转换为gRPC房间事件，不支持的事件返回None
+fn to_room_event(room_id: &str, message: WebSocketMessage) -> Option<RoomEvent> {
+    let event = match message {
+        WebSocketMessage::TypingStart {
+            user_id, username, ..
+        } => room_event::Event::Typing(TypingEvent {
+            user_id,
+            username,
+            is_typing: true,
+        }),
+        WebSocketMessage::TypingStop { user_id, .. } => room_event::Event::Typing(TypingEvent {
+            user_id,
+            username: String::new(),
+            is_typing: false,
+        }),
+        WebSocketMessage::PresenceUpdate {
+            user_id,
+            status,
+            last_seen_at,
+        } => room_event::Event::Presence(PresenceEvent {
+            user_id,
+            status,
+            last_seen_at: last_seen_at.unwrap_or_default(),
+        }),
+        WebSocketMessage::MessageUpdated {
+            message_id,
+            link_previews,
+            ..
+        } => room_event::Event::MessageUpdated(MessageUpdatedEvent {
+            message_id,
+            link_previews: link_previews.into_iter().map(|p| p.into_grpc()).collect(),
+        }),
+        WebSocketMessage::MessageDeleted { message_id, .. } => {
+            room_event::Event::MessageDeleted(MessageDeletedEvent { message_id })
+        }
+        WebSocketMessage::PollUpdated { poll, .. } => {
+            room_event::Event::PollUpdated(PollUpdatedEvent {
+                poll: Some(poll.into_grpc()),
+            })
+        }
+        WebSocketMessage::MessagePinned { pin, .. } => {
+            room_event::Event::MessagePinned(MessagePinnedEvent {
+                pin: Some(pin.into_grpc()),
+            })
+        }
+        WebSocketMessage::MessageUnpinned {
+            message_id,
+            unpinned_by,
+            ..
+        } => room_event::Event::MessageUnpinned(MessageUnpinnedEvent {
+            message_id,
+            unpinned_by,
+        }),
+        WebSocketMessage::UserKicked {
+            user_id,
+            kicked_by,
+            reason,
+            ..
+        } => room_event::Event::MemberKicked(MemberKickedEvent {
+            user_id,
+            kicked_by,
+            reason,
+        }),
+        _ => return None,
+    };
+
+    Some(RoomEvent {
+        room_id: room_id.to_string(),
+        event: Some(event),
+    })
 }
@@ -1,7 +1,9 @@
 pub mod auth;
 pub mod chat_service;
+pub mod rate_limit;
 pub mod user_service;
 
 pub use auth::*;
 pub use chat_service::*;
+pub use rate_limit::*;
 pub use user_service::*;
@@ -0,0 +1,45 @@
+use crate::redis::{RateLimitDecision, RateLimiter};
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Status};
+
+/// 被限流时返回RESOURCE_EXHAUSTED，并在retry-after元数据中带上需要等待的秒数
+pub fn resource_exhausted(message: &str, retry_after_secs: u64) -> Status {
+    let mut metadata = MetadataMap::new();
+    if let Ok(value) = retry_after_secs.to_string().parse() {
+        metadata.insert("retry-after", value);
+    }
+    Status::with_metadata(
+        Code::ResourceExhausted,
+        format!("{}, retry after {}s", message, retry_after_secs),
+        metadata,
+    )
+}
+
+/// 客户端IP，拿不到地址时返回unknown
+pub fn remote_ip<T>(request: &tonic::Request<T>) -> String {
+    request
+        .remote_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 按客户端IP限流，拿不到地址时按unknown统一计数；Redis不可用时放行
+pub async fn check_ip_rate_limit<T>(
+    rate_limiter: &RateLimiter,
+    operation: &str,
+    request: &tonic::Request<T>,
+) -> Result<(), Status> {
+    let ip = remote_ip(request);
+
+    match rate_limiter.check_ip(operation, &ip).await {
+        Ok(decision @ RateLimitDecision::Limited { .. }) => Err(resource_exhausted(
+            "Too many requests",
+            decision.retry_after_secs(),
+        )),
+        Ok(RateLimitDecision::Allowed) => Ok(()),
+        Err(e) => {
+            eprintln!("限流检查失败: {}", e);
+            Ok(())
+        }
+    }
+}
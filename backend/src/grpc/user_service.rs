@@ -1,8 +1,11 @@
+use crate::audit::AuditLogger;
 use crate::chat::{user_service_server::UserService, *};
 use crate::database::{DbPool, UserRepository};
 use crate::grpc::auth::AuthService;
-use crate::models::{CreateUser, UpdateUser};
-use crate::redis::SessionManager;
+use crate::grpc::{check_ip_rate_limit, remote_ip};
+use crate::models::{AuditAction, AuditEntry, CreateUser, PresenceStatus, UpdateUser};
+use crate::redis::{RateLimiter, SessionManager};
+use crate::websocket::PresenceTracker;
 use redis::Client as RedisClient;
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
@@ -11,10 +14,19 @@ pub struct UserServiceImpl {
     user_repo: UserRepository,
     session_manager: SessionManager,
     auth_service: AuthService,
+    presence_tracker: PresenceTracker,
+    rate_limiter: RateLimiter,
+    audit_logger: AuditLogger,
 }
 
 impl UserServiceImpl {
-    pub fn new(pool: DbPool, redis_client: RedisClient) -> Self {
+    pub fn new(
+        pool: DbPool,
+        redis_client: RedisClient,
+        presence_tracker: PresenceTracker,
+        rate_limiter: RateLimiter,
+        audit_logger: AuditLogger,
+    ) -> Self {
         let user_repo = UserRepository::new(pool);
         let session_manager = SessionManager::new(redis_client);
         let auth_service = AuthService::new(
@@ -25,8 +37,21 @@ impl UserServiceImpl {
             user_repo,
             session_manager,
             auth_service,
+            presence_tracker,
+            rate_limiter,
+            audit_logger,
         }
     }
+
+    fn record_login_failed(&self, email: &str, user_id: Option<&str>, ip: &str, reason: &str) {
+        let mut entry = AuditEntry::new(AuditAction::LoginFailed)
+            .ip(Some(ip))
+            .details(serde_json::json!({ "email": email, "reason": reason, "via": "grpc" }));
+        if let Some(user_id) = user_id {
+            entry = entry.target("user", user_id);
+        }
+        self.audit_logger.record(entry);
+    }
 }
 
 #[tonic::async_trait]
@@ -35,6 +60,7 @@ impl UserService for UserServiceImpl {
         &self,
         request: Request<RegisterRequest>,
     ) -> Result<Response<RegisterResponse>, Status> {
+        check_ip_rate_limit(&self.rate_limiter, "register", &request).await?;
         let req = request.into_inner();
 
         // 检查用户是否已存在
@@ -74,14 +100,19 @@ impl UserService for UserServiceImpl {
         &self,
         request: Request<LoginRequest>,
     ) -> Result<Response<LoginResponse>, Status> {
+        check_ip_rate_limit(&self.rate_limiter, "login", &request).await?;
+        let ip = remote_ip(&request);
         let req = request.into_inner();
 
-        let user = self
+        let Some(user) = self
             .user_repo
             .find_by_email(&req.email)
             .await
             .map_err(|e| Status::internal(format!("Database error: {}", e)))?
-            .ok_or_else(|| Status::not_found("User not found"))?;
+        else {
+            self.record_login_failed(&req.email, None, &ip, "unknown_email");
+            return Err(Status::not_found("User not found"));
+        };
 
         let is_valid = self
             .user_repo
@@ -90,6 +121,7 @@ impl UserService for UserServiceImpl {
             .map_err(|e| Status::internal(format!("Password verification failed: {}", e)))?;
 
         if !is_valid {
+            self.record_login_failed(&req.email, Some(&user.id), &ip, "invalid_password");
             return Ok(Response::new(LoginResponse {
                 success: false,
                 message: "Invalid password".to_string(),
@@ -98,6 +130,11 @@ impl UserService for UserServiceImpl {
             }));
         }
 
+        if user.is_banned() {
+            self.record_login_failed(&req.email, Some(&user.id), &ip, "banned");
+            return Err(Status::permission_denied("Account is banned"));
+        }
+
         // 生成JWT token
         let token = self
             .auth_service
@@ -111,16 +148,15 @@ impl UserService for UserServiceImpl {
             .await
             .map_err(|e| Status::internal(format!("Session creation failed: {}", e)))?;
 
-        // 设置用户在线状态
-        self.user_repo
-            .set_online_status(&user.id, true)
-            .await
-            .map_err(|e| Status::internal(format!("Failed to set online status: {}", e)))?;
+        // 在线状态由实时连接维护，登录本身不再标记在线
 
-        self.session_manager
-            .set_user_online(&user.id)
-            .await
-            .map_err(|e| Status::internal(format!("Failed to set user online: {}", e)))?;
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::Login)
+                .actor(&user.id)
+                .target("user", &user.id)
+                .ip(Some(&ip))
+                .details(serde_json::json!({ "via": "grpc" })),
+        );
 
         Ok(Response::new(LoginResponse {
             success: true,
@@ -153,6 +189,7 @@ impl UserService for UserServiceImpl {
         &self,
         request: Request<UpdateUserRequest>,
     ) -> Result<Response<UpdateUserResponse>, Status> {
+        let ip = remote_ip(&request);
         let req = request.into_inner();
 
         let update_user = UpdateUser {
@@ -161,11 +198,6 @@ impl UserService for UserServiceImpl {
             } else {
                 Some(req.username)
             },
-            avatar: if req.avatar.is_empty() {
-                None
-            } else {
-                Some(req.avatar)
-            },
         };
 
         let user = self
@@ -174,12 +206,43 @@ impl UserService for UserServiceImpl {
             .await
             .map_err(|e| Status::internal(format!("Failed to update user: {}", e)))?;
 
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::ProfileUpdated)
+                .actor(&req.user_id)
+                .target("user", &req.user_id)
+                .ip(Some(&ip))
+                .details(serde_json::json!({
+                    "fields": ["username"],
+                    "username": user.username,
+                })),
+        );
+
         Ok(Response::new(UpdateUserResponse {
             success: true,
             message: "User updated successfully".to_string(),
             user: Some(user.to_public().into()),
         }))
     }
+
+    async fn set_presence(
+        &self,
+        request: Request<SetPresenceRequest>,
+    ) -> Result<Response<SetPresenceResponse>, Status> {
+        let req = request.into_inner();
+
+        let status = PresenceStatus::parse(&req.status)
+            .ok_or_else(|| Status::invalid_argument("Invalid presence status"))?;
+
+        self.presence_tracker
+            .set_status(&req.user_id, status)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to set presence: {}", e)))?;
+
+        Ok(Response::new(SetPresenceResponse {
+            success: true,
+            message: "Presence updated successfully".to_string(),
+        }))
+    }
 }
 
 impl From<crate::models::PublicUser> for User {
@@ -192,6 +255,8 @@ impl From<crate::models::PublicUser> for User {
             is_online: user.is_online.map(|v| v != 0).unwrap_or(false),
             created_at: user.created_at,
             updated_at: user.updated_at,
+            status: user.status,
+            last_seen_at: user.last_seen_at.unwrap_or_default(),
         }
     }
 }
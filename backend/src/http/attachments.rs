@@ -0,0 +1,405 @@
+use super::handlers::{ApiResponse, with_attachment_repo, with_room_repo};
+use super::{with_auth, with_ip_rate_limit};
+use crate::database::{AttachmentRepository, DbPool, RoomRepository, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::media::{detect_image_format, process_image};
+use crate::models::{Attachment, AttachmentThumbnail, MAX_ATTACHMENT_BYTES, sanitize_file_name};
+use crate::redis::RateLimiter;
+use crate::storage::{BlobStore, StorageError};
+use bytes::{BufMut, Bytes};
+use futures_util::TryStreamExt;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use warp::http::{StatusCode, header};
+use warp::multipart::{FormData, Part};
+use warp::{Filter, Rejection, Reply};
+
+/// 附件上传和下载路由
+pub fn attachment_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    blob_store: Arc<dyn BlobStore>,
+    rate_limiter: RateLimiter,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let attachment_repo = Arc::new(AttachmentRepository::new(pool.clone()));
+    let room_repo = Arc::new(RoomRepository::new(pool.clone()));
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    // 表单中除文件外只有room_id字段，预留少量空间
+    let upload = warp::path("api")
+        .and(warp::path("attachments"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_ip_rate_limit(rate_limiter, "upload"))
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::multipart::form().max_length(MAX_ATTACHMENT_BYTES + 64 * 1024))
+        .and(with_attachment_repo(attachment_repo.clone()))
+        .and(with_room_repo(room_repo.clone()))
+        .and(with_blob_store(blob_store.clone()))
+        .and_then(handle_upload_attachment);
+
+    let download = warp::path("api")
+        .and(warp::path("attachments"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_attachment_repo(attachment_repo.clone()))
+        .and(with_room_repo(room_repo.clone()))
+        .and(with_blob_store(blob_store.clone()))
+        .and_then(handle_download_attachment);
+
+    let thumbnail = warp::path("api")
+        .and(warp::path("attachments"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("thumbnails"))
+        .and(warp::path::param::<u32>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service, user_repo))
+        .and(with_attachment_repo(attachment_repo))
+        .and(with_room_repo(room_repo))
+        .and(with_blob_store(blob_store))
+        .and_then(handle_download_thumbnail);
+
+    upload.or(download).or(thumbnail)
+}
+
+pub(crate) fn with_blob_store(
+    blob_store: Arc<dyn BlobStore>,
+) -> impl Filter<Extract = (Arc<dyn BlobStore>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || blob_store.clone())
+}
+
+/// 上传中的文件
+struct UploadedFile {
+    file_name: String,
+    content_type: Option<String>,
+    data: Bytes,
+}
+
+async fn read_part(part: Part) -> Result<Bytes, warp::Error> {
+    let data = part
+        .stream()
+        .try_fold(Vec::new(), |mut data, buf| async move {
+            data.put(buf);
+            Ok(data)
+        })
+        .await?;
+    Ok(Bytes::from(data))
+}
+
+async fn handle_upload_attachment(
+    user_id: String,
+    mut form: FormData,
+    attachment_repo: Arc<AttachmentRepository>,
+    room_repo: Arc<RoomRepository>,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<impl Reply, Rejection> {
+    let mut room_id = None;
+    let mut file = None;
+
+    loop {
+        let part = match form.try_next().await {
+            Ok(Some(part)) => part,
+            Ok(None) => break,
+            Err(e) => {
+                return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                    "读取上传内容失败: {}",
+                    e
+                ))));
+            }
+        };
+
+        match part.name() {
+            "room_id" => {
+                let value = read_part(part).await.map_err(|_| warp::reject::reject())?;
+                room_id = Some(String::from_utf8_lossy(&value).trim().to_string());
+            }
+            "file" => {
+                let file_name = sanitize_file_name(part.filename().unwrap_or_default());
+                let content_type = part.content_type().map(|s| s.to_string());
+                let data = read_part(part).await.map_err(|_| warp::reject::reject())?;
+                file = Some(UploadedFile {
+                    file_name,
+                    content_type,
+                    data,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let (Some(room_id), Some(file)) = (room_id, file) else {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(
+            "缺少room_id或file字段",
+        )));
+    };
+    if file.data.is_empty() {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error("文件为空")));
+    }
+
+    match room_repo.is_member(&user_id, &room_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(warp::reply::json(&ApiResponse::<()>::error(
+                "不是该房间成员",
+            )));
+        }
+        Err(_) => return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误"))),
+    }
+
+    // 客户端未提供类型时根据文件名推断
+    let mut mime_type = file
+        .content_type
+        .filter(|t| t.parse::<mime_guess::mime::Mime>().is_ok())
+        .unwrap_or_else(|| {
+            mime_guess::from_path(&file.file_name)
+                .first_or_octet_stream()
+                .to_string()
+        });
+    let mut data = file.data;
+
+    // 按内容识别图片：声明为图片但无法识别时拒绝，识别出的图片去除元数据并生成缩略图
+    let processed = if detect_image_format(&data).is_some() {
+        let input = data.clone();
+        match tokio::task::spawn_blocking(move || process_image(&input)).await {
+            Ok(Ok(processed)) => Some(processed),
+            Ok(Err(e)) => {
+                return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                    "图片处理失败: {}",
+                    e
+                ))));
+            }
+            Err(_) => return Ok(warp::reply::json(&ApiResponse::<()>::error("图片处理失败"))),
+        }
+    } else if mime_type.starts_with("image/") {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(
+            "不是有效的图片",
+        )));
+    } else {
+        None
+    };
+    if let Some(processed) = &processed {
+        data = processed.original.data.clone();
+        mime_type = processed.original.mime_type.to_string();
+    }
+
+    let checksum = hex::encode(Sha256::digest(&data));
+    let mut attachment = Attachment::new(
+        room_id,
+        user_id,
+        file.file_name,
+        mime_type,
+        data.len() as i64,
+        checksum,
+    );
+
+    let mut stored_keys = Vec::new();
+    let mut store_result = blob_store
+        .put(&attachment.storage_key, data, &attachment.mime_type)
+        .await;
+    if store_result.is_ok() {
+        stored_keys.push(attachment.storage_key.clone());
+    }
+
+    if let (Ok(()), Some(processed)) = (&store_result, processed) {
+        let mut thumbnails = Vec::new();
+        for thumbnail in processed.thumbnails {
+            let key = attachment.thumbnail_key(thumbnail.size);
+            store_result = blob_store
+                .put(&key, thumbnail.image.data, thumbnail.image.mime_type)
+                .await;
+            if store_result.is_err() {
+                break;
+            }
+            stored_keys.push(key.clone());
+            thumbnails.push(AttachmentThumbnail {
+                size: thumbnail.size,
+                width: thumbnail.image.width as i32,
+                height: thumbnail.image.height as i32,
+                mime_type: thumbnail.image.mime_type.to_string(),
+                storage_key: key,
+            });
+        }
+        attachment.set_image_metadata(
+            processed.original.width as i32,
+            processed.original.height as i32,
+            &thumbnails,
+        );
+    }
+
+    let result = match store_result {
+        Ok(()) => attachment_repo
+            .create(&attachment)
+            .await
+            .map_err(|e| format!("保存附件信息失败: {}", e)),
+        Err(e) => Err(format!("保存文件失败: {}", e)),
+    };
+    if let Err(message) = result {
+        // 删除已保存的文件
+        for key in stored_keys {
+            let _ = blob_store.delete(&key).await;
+        }
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&message)));
+    }
+
+    Ok(warp::reply::json(&ApiResponse::success(
+        attachment.to_info(),
+        "上传成功",
+    )))
+}
+
+async fn handle_download_attachment(
+    attachment_id: String,
+    user_id: String,
+    attachment_repo: Arc<AttachmentRepository>,
+    room_repo: Arc<RoomRepository>,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<warp::reply::Response, Rejection> {
+    let attachment =
+        match authorize_download(&attachment_id, &user_id, &attachment_repo, &room_repo).await {
+            Ok(attachment) => attachment,
+            Err(response) => return Ok(response),
+        };
+
+    // 图片直接展示，其他类型作为下载
+    let disposition = if attachment.is_image() {
+        "inline"
+    } else {
+        "attachment"
+    };
+
+    serve_blob(
+        &blob_store,
+        &attachment.storage_key,
+        &attachment.mime_type,
+        &format!(
+            "{}; filename*=UTF-8''{}",
+            disposition,
+            percent_encode(&attachment.file_name)
+        ),
+        &attachment.checksum,
+    )
+    .await
+}
+
+async fn handle_download_thumbnail(
+    attachment_id: String,
+    size: u32,
+    user_id: String,
+    attachment_repo: Arc<AttachmentRepository>,
+    room_repo: Arc<RoomRepository>,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<warp::reply::Response, Rejection> {
+    let attachment =
+        match authorize_download(&attachment_id, &user_id, &attachment_repo, &room_repo).await {
+            Ok(attachment) => attachment,
+            Err(response) => return Ok(response),
+        };
+
+    let Some(thumbnail) = attachment
+        .thumbnail_list()
+        .into_iter()
+        .find(|thumbnail| thumbnail.size == size)
+    else {
+        return Ok(error_response(StatusCode::NOT_FOUND, "缩略图不存在"));
+    };
+
+    serve_blob(
+        &blob_store,
+        &thumbnail.storage_key,
+        &thumbnail.mime_type,
+        "inline",
+        &format!("{}-{}", attachment.checksum, size),
+    )
+    .await
+}
+
+/// 查找附件并校验下载权限
+///
+/// 只有房间成员可以下载，未发送的附件只有上传者可以访问。
+async fn authorize_download(
+    attachment_id: &str,
+    user_id: &str,
+    attachment_repo: &AttachmentRepository,
+    room_repo: &RoomRepository,
+) -> Result<Attachment, warp::reply::Response> {
+    let attachment = match attachment_repo.find_by_id(attachment_id).await {
+        Ok(Some(attachment)) => attachment,
+        Ok(None) => return Err(error_response(StatusCode::NOT_FOUND, "附件不存在")),
+        Err(_) => {
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "数据库错误",
+            ));
+        }
+    };
+
+    let allowed = if attachment.message_id.is_none() {
+        attachment.uploader_id == user_id
+    } else {
+        room_repo
+            .is_member(user_id, &attachment.room_id)
+            .await
+            .map_err(|_| error_response(StatusCode::INTERNAL_SERVER_ERROR, "数据库错误"))?
+    };
+
+    if allowed {
+        Ok(attachment)
+    } else {
+        Err(error_response(StatusCode::FORBIDDEN, "无权访问该附件"))
+    }
+}
+
+async fn serve_blob(
+    blob_store: &Arc<dyn BlobStore>,
+    key: &str,
+    mime_type: &str,
+    disposition: &str,
+    etag: &str,
+) -> Result<warp::reply::Response, Rejection> {
+    let data = match blob_store.get(key).await {
+        Ok(data) => data,
+        Err(StorageError::NotFound(_)) => {
+            return Ok(error_response(StatusCode::NOT_FOUND, "附件文件不存在"));
+        }
+        Err(e) => {
+            return Ok(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("读取附件失败: {}", e),
+            ));
+        }
+    };
+
+    warp::http::Response::builder()
+        .header(header::CONTENT_TYPE, mime_type)
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .header(header::ETAG, format!("\"{}\"", etag))
+        .header("X-Content-Type-Options", "nosniff")
+        .body(data.into())
+        .map_err(|_| warp::reject::reject())
+}
+
+fn error_response(status: StatusCode, message: &str) -> warp::reply::Response {
+    warp::reply::with_status(
+        warp::reply::json(&ApiResponse::<()>::error(message)),
+        status,
+    )
+    .into_response()
+}
+
+/// RFC 5987 文件名编码
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,172 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::with_auth;
+use crate::database::{AuditRepository, DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{AuditAction, AuditQuery, AuditRecord, UserRole};
+use serde::Serialize;
+use std::sync::Arc;
+use warp::http::header;
+use warp::{Filter, Rejection, Reply};
+
+/// 单页默认返回条数
+const DEFAULT_PAGE_SIZE: i64 = 50;
+/// 单页最大返回条数
+const MAX_PAGE_SIZE: i64 = 200;
+/// 导出时每次从数据库读取的条数
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditRecord>,
+    /// 下一页的before_id，没有更多记录时为空
+    pub next_before_id: Option<String>,
+}
+
+/// 审计日志查询和导出路由，只允许管理员调用
+pub fn audit_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let audit_repo = Arc::new(AuditRepository::new(pool.clone()));
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let list = warp::path("api")
+        .and(warp::path("admin"))
+        .and(warp::path("audit"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::query::<AuditQuery>())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_audit_repo(audit_repo.clone()))
+        .and_then(handle_list_audit);
+
+    let export = warp::path("api")
+        .and(warp::path("admin"))
+        .and(warp::path("audit"))
+        .and(warp::path("export"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(warp::query::<AuditQuery>())
+        .and(with_user_repo(user_repo))
+        .and(with_audit_repo(audit_repo))
+        .and_then(handle_export_audit);
+
+    list.or(export)
+}
+
+fn with_audit_repo(
+    audit_repo: Arc<AuditRepository>,
+) -> impl Filter<Extract = (Arc<AuditRepository>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || audit_repo.clone())
+}
+
+/// 检查管理员权限和查询条件
+async fn check_request(
+    user_repo: &UserRepository,
+    user_id: &str,
+    query: &AuditQuery,
+) -> Result<(), String> {
+    match user_repo.find_by_id(user_id).await {
+        Ok(Some(user)) if user.role() == UserRole::Admin => {}
+        Ok(Some(_)) => return Err("只有管理员可以查看审计日志".to_string()),
+        Ok(None) => return Err("用户不存在".to_string()),
+        Err(_) => return Err("数据库错误".to_string()),
+    }
+
+    if let Some(action) = query.action.as_deref() {
+        if AuditAction::parse(action).is_none() {
+            return Err(format!("未知的操作类型: {}", action));
+        }
+    }
+    Ok(())
+}
+
+async fn handle_list_audit(
+    user_id: String,
+    query: AuditQuery,
+    user_repo: Arc<UserRepository>,
+    audit_repo: Arc<AuditRepository>,
+) -> Result<warp::reply::Response, Rejection> {
+    if let Err(message) = check_request(&user_repo, &user_id, &query).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&message)).into_response());
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    match audit_repo.query(&query, limit).await {
+        Ok(entries) => {
+            let next_before_id = if entries.len() as i64 == limit {
+                entries.last().map(|entry| entry.id.clone())
+            } else {
+                None
+            };
+            let page = AuditPage {
+                entries: entries.iter().map(|entry| entry.to_record()).collect(),
+                next_before_id,
+            };
+            Ok(warp::reply::json(&ApiResponse::success(page, "查询成功")).into_response())
+        }
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "查询审计日志失败: {}",
+            e
+        )))
+        .into_response()),
+    }
+}
+
+/// 按查询条件导出全部记录（JSON Lines），分批读取并以流的方式返回
+async fn handle_export_audit(
+    user_id: String,
+    query: AuditQuery,
+    user_repo: Arc<UserRepository>,
+    audit_repo: Arc<AuditRepository>,
+) -> Result<warp::reply::Response, Rejection> {
+    if let Err(message) = check_request(&user_repo, &user_id, &query).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&message)).into_response());
+    }
+
+    // 状态为None表示已经读完
+    let stream = futures::stream::unfold(Some(query), move |state| {
+        let audit_repo = audit_repo.clone();
+        async move {
+            let mut query = state?;
+            match audit_repo.query(&query, EXPORT_BATCH_SIZE).await {
+                Ok(entries) if entries.is_empty() => None,
+                Ok(entries) => {
+                    let mut chunk = String::new();
+                    for entry in &entries {
+                        if let Ok(line) = serde_json::to_string(&entry.to_record()) {
+                            chunk.push_str(&line);
+                            chunk.push('\n');
+                        }
+                    }
+                    let next = if entries.len() as i64 == EXPORT_BATCH_SIZE {
+                        query.before_id = entries.last().map(|entry| entry.id.clone());
+                        Some(query)
+                    } else {
+                        None
+                    };
+                    Some((Ok::<_, sqlx::Error>(chunk), next))
+                }
+                Err(e) => {
+                    eprintln!("导出审计日志失败: {}", e);
+                    Some((Err(e), None))
+                }
+            }
+        }
+    });
+
+    let filename = format!("audit-{}.jsonl", chrono::Utc::now().format("%Y%m%d%H%M%S"));
+    Ok(warp::http::Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(warp::hyper::Body::wrap_stream(stream))
+        .unwrap())
+}
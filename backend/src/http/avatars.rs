@@ -0,0 +1,181 @@
+use super::handlers::{ApiResponse, with_audit_logger, with_user_repo};
+use super::{with_auth, with_blob_store, with_client_ip, with_ip_rate_limit};
+use crate::audit::AuditLogger;
+use crate::cleanup::delete_avatar_files;
+use crate::database::{DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::media::{image_mime_type, square_variants};
+use crate::models::{AVATAR_SIZES, AuditAction, AuditEntry, MAX_AVATAR_BYTES, avatar_storage_key};
+use crate::redis::RateLimiter;
+use crate::storage::{BlobStore, StorageError};
+use bytes::BufMut;
+use futures_util::TryStreamExt;
+use std::sync::Arc;
+use uuid::Uuid;
+use warp::http::{StatusCode, header};
+use warp::multipart::FormData;
+use warp::{Filter, Rejection, Reply};
+
+/// 头像上传和访问路由
+pub fn avatar_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    blob_store: Arc<dyn BlobStore>,
+    rate_limiter: RateLimiter,
+    audit_logger: AuditLogger,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let upload = warp::path("api")
+        .and(warp::path("users"))
+        .and(warp::path("avatar"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_ip_rate_limit(rate_limiter, "upload"))
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(warp::multipart::form().max_length(MAX_AVATAR_BYTES + 64 * 1024))
+        .and(with_user_repo(user_repo))
+        .and(with_blob_store(blob_store.clone()))
+        .and(with_client_ip())
+        .and(with_audit_logger(audit_logger))
+        .and_then(handle_upload_avatar);
+
+    // 头像地址包含版本号，公开访问且可以长期缓存
+    let serve = warp::path("api")
+        .and(warp::path("avatars"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<u32>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_blob_store(blob_store))
+        .and_then(handle_get_avatar);
+
+    upload.or(serve)
+}
+
+async fn handle_upload_avatar(
+    user_id: String,
+    mut form: FormData,
+    user_repo: Arc<UserRepository>,
+    blob_store: Arc<dyn BlobStore>,
+    ip: String,
+    audit_logger: AuditLogger,
+) -> Result<impl Reply, Rejection> {
+    let mut data = None;
+    while let Ok(Some(part)) = form.try_next().await {
+        if part.name() == "file" {
+            let bytes = part
+                .stream()
+                .try_fold(Vec::new(), |mut data, buf| async move {
+                    data.put(buf);
+                    Ok(data)
+                })
+                .await
+                .map_err(|_| warp::reject::reject())?;
+            data = Some(bytes);
+        }
+    }
+
+    let Some(data) = data else {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error("缺少file字段")));
+    };
+
+    let variants =
+        match tokio::task::spawn_blocking(move || square_variants(&data, &AVATAR_SIZES)).await {
+            Ok(Ok(variants)) => variants,
+            Ok(Err(e)) => {
+                return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                    "头像处理失败: {}",
+                    e
+                ))));
+            }
+            Err(_) => return Ok(warp::reply::json(&ApiResponse::<()>::error("头像处理失败"))),
+        };
+
+    let version = Uuid::new_v4().simple().to_string();
+    for variant in variants {
+        let key = avatar_storage_key(&user_id, &version, variant.size);
+        if let Err(e) = blob_store
+            .put(&key, variant.image.data, variant.image.mime_type)
+            .await
+        {
+            delete_avatar_files(&blob_store, &user_id, &version).await;
+            return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                "保存头像失败: {}",
+                e
+            ))));
+        }
+    }
+
+    // 旧版本的地址可能仍被客户端缓存，文件由AvatarCleanup在宽限期过后删除
+    let previous = match user_repo.set_avatar(&user_id, &version).await {
+        Ok(previous) => previous,
+        Err(e) => {
+            delete_avatar_files(&blob_store, &user_id, &version).await;
+            return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                "更新头像失败: {}",
+                e
+            ))));
+        }
+    };
+
+    audit_logger.record(
+        AuditEntry::new(AuditAction::ProfileUpdated)
+            .actor(&user_id)
+            .target("user", &user_id)
+            .ip(Some(&ip))
+            .details(serde_json::json!({
+                "fields": ["avatar"],
+                "previous_avatar": previous,
+                "avatar": version,
+            })),
+    );
+
+    match user_repo.find_by_id(&user_id).await {
+        Ok(Some(user)) => Ok(warp::reply::json(&ApiResponse::success(
+            user.to_public(),
+            "头像已更新",
+        ))),
+        _ => Ok(warp::reply::json(&ApiResponse::<()>::error("用户不存在"))),
+    }
+}
+
+async fn handle_get_avatar(
+    user_id: String,
+    version: String,
+    size: u32,
+    blob_store: Arc<dyn BlobStore>,
+) -> Result<warp::reply::Response, Rejection> {
+    if !AVATAR_SIZES.contains(&size) {
+        return Err(warp::reject::not_found());
+    }
+
+    let data = match blob_store
+        .get(&avatar_storage_key(&user_id, &version, size))
+        .await
+    {
+        Ok(data) => data,
+        Err(StorageError::NotFound(_) | StorageError::InvalidKey(_)) => {
+            return Err(warp::reject::not_found());
+        }
+        Err(e) => {
+            eprintln!("读取头像失败: {}", e);
+            return Ok(
+                warp::reply::with_status("读取头像失败", StatusCode::INTERNAL_SERVER_ERROR)
+                    .into_response(),
+            );
+        }
+    };
+
+    warp::http::Response::builder()
+        .header(
+            header::CONTENT_TYPE,
+            image_mime_type(&data).unwrap_or("application/octet-stream"),
+        )
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+        .header("X-Content-Type-Options", "nosniff")
+        .body(data.into())
+        .map_err(|_| warp::reject::reject())
+}
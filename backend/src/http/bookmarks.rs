@@ -0,0 +1,131 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::reports::load_user;
+use super::with_auth;
+use crate::bookmarks::{BookmarkResult, BookmarkService};
+use crate::database::{DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::SaveBookmark;
+use serde::Deserialize;
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+#[derive(Debug, Deserialize)]
+pub struct BookmarkListQuery {
+    /// 只看某个房间的收藏
+    pub room_id: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// 收藏路由：收藏、取消收藏和查看自己的收藏
+pub fn bookmark_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    bookmark_service: BookmarkService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let save = warp::path("api")
+        .and(warp::path("messages"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("bookmark"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_bookmark_service(bookmark_service.clone()))
+        .and_then(handle_save_bookmark);
+
+    let remove = warp::path("api")
+        .and(warp::path("messages"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("bookmark"))
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_bookmark_service(bookmark_service.clone()))
+        .and_then(handle_remove_bookmark);
+
+    let list = warp::path("api")
+        .and(warp::path("bookmarks"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(warp::query::<BookmarkListQuery>())
+        .and(with_user_repo(user_repo))
+        .and(with_bookmark_service(bookmark_service))
+        .and_then(handle_list_bookmarks);
+
+    save.or(remove).or(list)
+}
+
+fn with_bookmark_service(
+    bookmark_service: BookmarkService,
+) -> impl Filter<Extract = (BookmarkService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || bookmark_service.clone())
+}
+
+fn bookmark_reply<T: serde::Serialize>(
+    result: BookmarkResult<T>,
+    message: &str,
+) -> warp::reply::Json {
+    match result {
+        Ok(data) => warp::reply::json(&ApiResponse::success(data, message)),
+        Err(e) => warp::reply::json(&ApiResponse::<()>::error(&e.to_string())),
+    }
+}
+
+async fn handle_save_bookmark(
+    message_id: String,
+    user_id: String,
+    req: SaveBookmark,
+    user_repo: Arc<UserRepository>,
+    bookmark_service: BookmarkService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = bookmark_service.save(&user, &message_id, req).await;
+    Ok(bookmark_reply(result, "已收藏"))
+}
+
+async fn handle_remove_bookmark(
+    message_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    bookmark_service: BookmarkService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = bookmark_service.remove(&user, &message_id).await;
+    Ok(bookmark_reply(result, "已取消收藏"))
+}
+
+async fn handle_list_bookmarks(
+    user_id: String,
+    query: BookmarkListQuery,
+    user_repo: Arc<UserRepository>,
+    bookmark_service: BookmarkService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = bookmark_service
+        .list(
+            &user,
+            query.room_id.as_deref(),
+            query.limit.unwrap_or(50),
+            query.offset.unwrap_or(0),
+        )
+        .await;
+    Ok(bookmark_reply(result, "获取收藏成功"))
+}
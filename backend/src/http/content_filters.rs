@@ -0,0 +1,142 @@
+use super::handlers::{ApiResponse, ensure_room_owner, with_audit_logger, with_room_repo};
+use super::{with_auth, with_client_ip};
+use crate::audit::AuditLogger;
+use crate::database::{ContentFilterRepository, DbPool, RoomRepository, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{AuditAction, AuditEntry, FilterRule, RoomFilterRule};
+use crate::moderation::ContentFilter;
+use serde::Serialize;
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+/// 房间过滤规则，global_rules始终生效，room_rules在其后追加
+#[derive(Serialize)]
+struct RoomFilterRulesResponse {
+    global_rules: Vec<FilterRule>,
+    room_rules: Vec<FilterRule>,
+}
+
+/// 房间内容过滤规则管理路由，只有房间创建者可以查看和修改
+pub fn content_filter_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    content_filter: ContentFilter,
+    audit_logger: AuditLogger,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let filter_repo = Arc::new(ContentFilterRepository::new(pool.clone()));
+    let room_repo = Arc::new(RoomRepository::new(pool.clone()));
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let get_rules = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("filters"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_filter_repo(filter_repo.clone()))
+        .and(with_room_repo(room_repo.clone()))
+        .and(with_content_filter(content_filter.clone()))
+        .and_then(handle_get_room_filters);
+
+    let put_rules = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("filters"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(with_auth(auth_service, user_repo))
+        .and(warp::body::json())
+        .and(with_filter_repo(filter_repo))
+        .and(with_room_repo(room_repo))
+        .and(with_content_filter(content_filter))
+        .and(with_client_ip())
+        .and(with_audit_logger(audit_logger))
+        .and_then(handle_put_room_filters);
+
+    get_rules.or(put_rules)
+}
+
+fn with_filter_repo(
+    filter_repo: Arc<ContentFilterRepository>,
+) -> impl Filter<Extract = (Arc<ContentFilterRepository>,), Error = std::convert::Infallible> + Clone
+{
+    warp::any().map(move || filter_repo.clone())
+}
+
+pub(crate) fn with_content_filter(
+    content_filter: ContentFilter,
+) -> impl Filter<Extract = (ContentFilter,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || content_filter.clone())
+}
+
+async fn handle_get_room_filters(
+    room_id: String,
+    user_id: String,
+    filter_repo: Arc<ContentFilterRepository>,
+    room_repo: Arc<RoomRepository>,
+    content_filter: ContentFilter,
+) -> Result<impl Reply, Rejection> {
+    if let Err(message) = ensure_room_owner(&room_repo, &room_id, &user_id).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(message)));
+    }
+
+    match filter_repo.find_room_rules(&room_id).await {
+        Ok(rules) => Ok(warp::reply::json(&ApiResponse::success(
+            RoomFilterRulesResponse {
+                global_rules: content_filter.global_rules().to_vec(),
+                room_rules: rules.iter().filter_map(|rule| rule.to_rule()).collect(),
+            },
+            "获取过滤规则成功",
+        ))),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "获取过滤规则失败: {}",
+            e
+        )))),
+    }
+}
+
+async fn handle_put_room_filters(
+    room_id: String,
+    user_id: String,
+    rules: Vec<FilterRule>,
+    filter_repo: Arc<ContentFilterRepository>,
+    room_repo: Arc<RoomRepository>,
+    content_filter: ContentFilter,
+    ip: String,
+    audit_logger: AuditLogger,
+) -> Result<impl Reply, Rejection> {
+    if let Err(message) = ensure_room_owner(&room_repo, &room_id, &user_id).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(message)));
+    }
+
+    if let Err(e) = content_filter.validate_room_rules(&rules) {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&e)));
+    }
+
+    let rows: Vec<RoomFilterRule> = rules
+        .iter()
+        .map(|rule| RoomFilterRule::new(room_id.clone(), rule))
+        .collect();
+    if let Err(e) = filter_repo.replace_room_rules(&room_id, &rows).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "保存过滤规则失败: {}",
+            e
+        ))));
+    }
+    content_filter.invalidate(&room_id);
+    audit_logger.record(
+        AuditEntry::new(AuditAction::FilterRulesChanged)
+            .actor(&user_id)
+            .target("room", &room_id)
+            .ip(Some(&ip))
+            .details(serde_json::json!({ "rules": rules })),
+    );
+
+    Ok(warp::reply::json(&ApiResponse::success(
+        rules,
+        "过滤规则已更新",
+    )))
+}
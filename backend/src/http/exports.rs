@@ -0,0 +1,136 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::reports::load_user;
+use super::{with_auth, with_client_ip};
+use crate::database::{DbPool, UserRepository};
+use crate::export::{ExportResult, ExportService};
+use crate::grpc::auth::AuthService;
+use crate::models::CreateExport;
+use std::sync::Arc;
+use warp::http::header;
+use warp::{Filter, Rejection, Reply};
+
+/// 房间历史导出路由：创建导出任务、查询进度和下载文件
+pub fn export_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    export_service: ExportService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let create = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("exports"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_client_ip())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_export_service(export_service.clone()))
+        .and_then(handle_create_export);
+
+    let status = warp::path("api")
+        .and(warp::path("exports"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_export_service(export_service.clone()))
+        .and_then(handle_export_status);
+
+    let download = warp::path("api")
+        .and(warp::path("exports"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("download"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(with_user_repo(user_repo))
+        .and(with_export_service(export_service))
+        .and_then(handle_download_export);
+
+    create.or(status).or(download)
+}
+
+fn with_export_service(
+    export_service: ExportService,
+) -> impl Filter<Extract = (ExportService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || export_service.clone())
+}
+
+fn export_reply(
+    result: ExportResult<crate::models::ExportJob>,
+    message: &str,
+) -> warp::reply::Json {
+    match result {
+        Ok(job) => warp::reply::json(&ApiResponse::success(job.to_info(), message)),
+        Err(e) => warp::reply::json(&ApiResponse::<()>::error(&e.to_string())),
+    }
+}
+
+async fn handle_create_export(
+    room_id: String,
+    user_id: String,
+    req: CreateExport,
+    ip: String,
+    user_repo: Arc<UserRepository>,
+    export_service: ExportService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = export_service.create(&user, &room_id, req, Some(&ip)).await;
+    Ok(export_reply(result, "导出任务已创建"))
+}
+
+async fn handle_export_status(
+    job_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    export_service: ExportService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = export_service.find(&user, &job_id).await;
+    Ok(export_reply(result, "获取导出任务成功"))
+}
+
+/// 按分片顺序以流的方式返回导出文件
+async fn handle_download_export(
+    job_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    export_service: ExportService,
+) -> Result<warp::reply::Response, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => {
+            return Ok(warp::reply::json(&ApiResponse::<()>::error(message)).into_response());
+        }
+    };
+
+    let (job, stream) = match export_service.download(&user, &job_id).await {
+        Ok(download) => download,
+        Err(e) => {
+            return Ok(warp::reply::json(&ApiResponse::<()>::error(&e.to_string())).into_response());
+        }
+    };
+
+    Ok(warp::http::Response::builder()
+        .header(header::CONTENT_TYPE, job.export_format().content_type())
+        .header(header::CONTENT_LENGTH, job.size_bytes)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", job.file_name()),
+        )
+        .body(warp::hyper::Body::wrap_stream(stream))
+        .unwrap())
+}
@@ -0,0 +1,104 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::reports::load_user;
+use super::{RateLimited, with_auth};
+use crate::database::{DbPool, UserRepository};
+use crate::forwarding::{ForwardError, ForwardResult, ForwardService};
+use crate::grpc::auth::AuthService;
+use crate::models::{ForwardMessage, Message, QuoteMessage};
+use crate::moderation::SendRejection;
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+/// 转发和引用回复路由
+pub fn forward_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    forward_service: ForwardService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let forward = warp::path("api")
+        .and(warp::path("messages"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("forward"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_forward_service(forward_service.clone()))
+        .and_then(handle_forward_message);
+
+    let quote = warp::path("api")
+        .and(warp::path("messages"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("quote"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo))
+        .and(with_forward_service(forward_service))
+        .and_then(handle_quote_message);
+
+    forward.or(quote)
+}
+
+fn with_forward_service(
+    forward_service: ForwardService,
+) -> impl Filter<Extract = (ForwardService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || forward_service.clone())
+}
+
+/// 发送频率超限时和发送消息一样返回429
+fn forward_reply(
+    result: ForwardResult<Message>,
+    message: &str,
+) -> Result<warp::reply::Json, Rejection> {
+    match result {
+        Ok(data) => Ok(warp::reply::json(&ApiResponse::success(
+            data.to_grpc(),
+            message,
+        ))),
+        Err(ForwardError::Rejected(SendRejection::RateLimited {
+            reason,
+            retry_after_secs,
+        })) => Err(warp::reject::custom(RateLimited {
+            message: reason,
+            retry_after_secs,
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&e.to_string()))),
+    }
+}
+
+async fn handle_forward_message(
+    message_id: String,
+    user_id: String,
+    req: ForwardMessage,
+    user_repo: Arc<UserRepository>,
+    forward_service: ForwardService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = forward_service.forward(&user, &message_id, req).await;
+    forward_reply(result, "消息已转发")
+}
+
+async fn handle_quote_message(
+    message_id: String,
+    user_id: String,
+    req: QuoteMessage,
+    user_repo: Arc<UserRepository>,
+    forward_service: ForwardService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = forward_service.quote(&user, &message_id, req).await;
+    forward_reply(result, "回复已发送")
+}
@@ -1,7 +1,32 @@
-use crate::database::{DbPool, MessageRepository, UserRepository};
+use crate::audit::AuditLogger;
+use crate::bookmarks::BookmarkService;
+use crate::cleanup::RetentionJob;
+use crate::commands::{CommandDispatch, CommandError, CommandRegistry};
+use crate::database::{
+    AttachmentRepository, DbPool, LinkPreviewRepository, MessageRepository, PollRepository,
+    RoomRepository, UserRepository,
+};
+use crate::export::ExportService;
+use crate::forwarding::ForwardService;
 use crate::grpc::auth::AuthService;
-use crate::models::{CreateUser, UpdateUser};
-use crate::redis::SessionManager;
+use crate::http::{
+    RateLimited, attachment_routes, audit_routes, avatar_routes, bookmark_routes,
+    content_filter_routes, export_routes, forward_routes, handle_rate_limit_rejection,
+    message_ttl_routes, pin_routes, poll_routes, report_routes, retention_routes,
+    scheduled_message_routes, slow_mode_routes, with_auth, with_client_ip, with_content_filter,
+    with_ip_rate_limit,
+};
+use crate::models::{
+    AuditAction, AuditEntry, CreateUser, MessageHistoryQuery, MessageSearchQuery, MessageType,
+    UpdateUser,
+};
+use crate::moderation::{ContentFilter, FilterDecision, ReportService, SendGuard, SendRejection};
+use crate::pins::PinService;
+use crate::polls::PollService;
+use crate::redis::{RateLimiter, SessionManager};
+use crate::scheduling::ScheduledMessageService;
+use crate::storage::BlobStore;
+use crate::unfurl::LinkUnfurler;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use warp::{Filter, Rejection, Reply};
@@ -24,6 +49,26 @@ pub struct SendMessageRequest {
     pub content: String,
     pub room_id: String,
     pub message_type: Option<String>,
+    /// 通过 /api/attachments 上传后得到的附件ID
+    #[serde(default)]
+    pub attachment_ids: Vec<String>,
+    /// 消息有效期（秒），不填时使用房间默认值
+    #[serde(default)]
+    pub ttl_seconds: u32,
+}
+
+#[derive(Deserialize)]
+pub struct SearchMessagesParams {
+    pub q: String,
+    pub room_id: Option<String>,
+    /// 按消息作者过滤
+    pub user_id: Option<String>,
+    pub message_type: Option<String>,
+    /// 起止时间，Unix时间戳（秒）
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -55,32 +100,144 @@ pub fn create_routes(
     pool: DbPool,
     session_manager: SessionManager,
     auth_service: Arc<AuthService>,
+    blob_store: Arc<dyn BlobStore>,
+    link_unfurler: LinkUnfurler,
+    content_filter: ContentFilter,
+    send_guard: SendGuard,
+    rate_limiter: RateLimiter,
+    report_service: ReportService,
+    audit_logger: AuditLogger,
+    poll_service: PollService,
+    scheduled_service: ScheduledMessageService,
+    retention_job: RetentionJob,
+    export_service: ExportService,
+    pin_service: PinService,
+    bookmark_service: BookmarkService,
+    forward_service: ForwardService,
+    command_registry: CommandRegistry,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let user_repo = Arc::new(UserRepository::new(pool.clone()));
-    let message_repo = Arc::new(MessageRepository::new(pool));
+    let message_repo = Arc::new(MessageRepository::new(pool.clone()));
 
     // 用户路由
     let user_routes = user_routes(
         user_repo.clone(),
         session_manager.clone(),
         auth_service.clone(),
+        rate_limiter.clone(),
+        audit_logger.clone(),
     );
 
     // 聊天路由
-    let chat_routes = chat_routes(user_repo, message_repo, session_manager, auth_service);
+    let chat_routes = chat_routes(
+        user_repo,
+        message_repo,
+        session_manager,
+        auth_service.clone(),
+        link_unfurler,
+        content_filter.clone(),
+        send_guard,
+        rate_limiter.clone(),
+        command_registry,
+    );
+
+    // 附件路由
+    let attachment_routes = attachment_routes(
+        pool.clone(),
+        auth_service.clone(),
+        blob_store.clone(),
+        rate_limiter.clone(),
+    );
+
+    // 头像路由
+    let avatar_routes = avatar_routes(
+        pool.clone(),
+        auth_service.clone(),
+        blob_store,
+        rate_limiter,
+        audit_logger.clone(),
+    );
+
+    // 房间内容过滤规则路由
+    let content_filter_routes = content_filter_routes(
+        pool.clone(),
+        auth_service.clone(),
+        content_filter,
+        audit_logger.clone(),
+    );
 
-    user_routes.or(chat_routes)
+    // 房间慢速模式路由
+    let slow_mode_routes =
+        slow_mode_routes(pool.clone(), auth_service.clone(), audit_logger.clone());
+
+    // 房间消息有效期路由
+    let message_ttl_routes =
+        message_ttl_routes(pool.clone(), auth_service.clone(), audit_logger.clone());
+
+    // 举报和审核路由
+    let report_routes = report_routes(pool.clone(), auth_service.clone(), report_service);
+
+    // 投票路由
+    let poll_routes = poll_routes(pool.clone(), auth_service.clone(), poll_service);
+
+    // 置顶消息路由
+    let pin_routes = pin_routes(pool.clone(), auth_service.clone(), pin_service);
+
+    // 收藏路由
+    let bookmark_routes = bookmark_routes(pool.clone(), auth_service.clone(), bookmark_service);
+
+    // 转发和引用回复路由
+    let forward_routes = forward_routes(pool.clone(), auth_service.clone(), forward_service);
+
+    // 定时消息路由
+    let scheduled_message_routes =
+        scheduled_message_routes(pool.clone(), auth_service.clone(), scheduled_service);
+
+    // 消息保留策略路由
+    let retention_routes = retention_routes(
+        pool.clone(),
+        auth_service.clone(),
+        retention_job,
+        audit_logger.clone(),
+    );
+
+    // 房间历史导出路由
+    let export_routes = export_routes(pool.clone(), auth_service.clone(), export_service);
+
+    // 审计日志查询路由
+    let audit_routes = audit_routes(pool, auth_service);
+
+    user_routes
+        .or(chat_routes)
+        .or(attachment_routes)
+        .or(avatar_routes)
+        .or(content_filter_routes)
+        .or(slow_mode_routes)
+        .or(message_ttl_routes)
+        .or(report_routes)
+        .or(poll_routes)
+        .or(pin_routes)
+        .or(bookmark_routes)
+        .or(forward_routes)
+        .or(scheduled_message_routes)
+        .or(retention_routes)
+        .or(export_routes)
+        .or(audit_routes)
+        .recover(handle_rate_limit_rejection)
 }
 
 fn user_routes(
     user_repo: Arc<UserRepository>,
     session_manager: SessionManager,
     auth_service: Arc<AuthService>,
+    rate_limiter: RateLimiter,
+    audit_logger: AuditLogger,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let register = warp::path("api")
         .and(warp::path("users"))
         .and(warp::path("register"))
         .and(warp::post())
+        .and(with_ip_rate_limit(rate_limiter.clone(), "register"))
         .and(warp::body::json())
         .and(with_user_repo(user_repo.clone()))
         .and_then(handle_register);
@@ -89,10 +246,13 @@ fn user_routes(
         .and(warp::path("users"))
         .and(warp::path("login"))
         .and(warp::post())
+        .and(with_ip_rate_limit(rate_limiter, "login"))
         .and(warp::body::json())
+        .and(with_client_ip())
         .and(with_user_repo(user_repo.clone()))
         .and(with_session_manager(session_manager))
         .and(with_auth_service(auth_service))
+        .and(with_audit_logger(audit_logger))
         .and_then(handle_login);
 
     register.or(login)
@@ -103,16 +263,30 @@ fn chat_routes(
     message_repo: Arc<MessageRepository>,
     session_manager: SessionManager,
     auth_service: Arc<AuthService>,
+    link_unfurler: LinkUnfurler,
+    content_filter: ContentFilter,
+    send_guard: SendGuard,
+    rate_limiter: RateLimiter,
+    command_registry: CommandRegistry,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let attachment_repo = Arc::new(AttachmentRepository::new(user_repo.pool().clone()));
+    let link_preview_repo = Arc::new(LinkPreviewRepository::new(user_repo.pool().clone()));
+    let poll_repo = Arc::new(PollRepository::new(user_repo.pool().clone()));
+
     let send_message = warp::path("api")
         .and(warp::path("chat"))
         .and(warp::path("messages"))
         .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
         .and(warp::body::json())
         .and(with_user_repo(user_repo.clone()))
         .and(with_message_repo(message_repo))
-        .and(with_session_manager(session_manager.clone()))
-        .and(with_auth_service(auth_service.clone()))
+        .and(with_attachment_repo(attachment_repo.clone()))
+        .and(with_link_unfurler(link_unfurler))
+        .and(with_content_filter(content_filter))
+        .and(with_send_guard(send_guard))
+        .and(with_command_registry(command_registry))
+        .and(with_client_ip())
         .and_then(handle_send_message);
 
     let get_messages = warp::path("api")
@@ -125,6 +299,9 @@ fn chat_routes(
         .and(with_message_repo(Arc::new(MessageRepository::new(
             user_repo.pool().clone(),
         ))))
+        .and(with_attachment_repo(attachment_repo))
+        .and(with_link_preview_repo(link_preview_repo))
+        .and(with_poll_repo(poll_repo))
         .and_then(handle_get_messages);
 
     let get_online_users = warp::path("api")
@@ -158,30 +335,110 @@ fn chat_routes(
         .and(warp::path("chat"))
         .and(warp::path("rooms"))
         .and(warp::get())
-        .and(with_user_repo(user_repo))
+        .and(with_user_repo(user_repo.clone()))
         .and_then(handle_get_rooms);
 
+    let search_messages = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_ip_rate_limit(rate_limiter, "search"))
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::query::<SearchMessagesParams>())
+        .and(with_message_repo(Arc::new(MessageRepository::new(
+            user_repo.pool().clone(),
+        ))))
+        .and(with_room_repo(Arc::new(RoomRepository::new(
+            user_repo.pool().clone(),
+        ))))
+        .and_then(handle_search_messages);
+
     send_message
         .or(get_messages)
         .or(get_online_users)
         .or(join_room)
         .or(leave_room)
         .or(get_rooms)
+        .or(search_messages)
 }
 
 // 辅助函数来传递依赖
-fn with_user_repo(
+pub(crate) fn with_user_repo(
     user_repo: Arc<UserRepository>,
 ) -> impl Filter<Extract = (Arc<UserRepository>,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || user_repo.clone())
 }
 
+pub(crate) fn with_audit_logger(
+    audit_logger: AuditLogger,
+) -> impl Filter<Extract = (AuditLogger,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || audit_logger.clone())
+}
+
 fn with_message_repo(
     message_repo: Arc<MessageRepository>,
 ) -> impl Filter<Extract = (Arc<MessageRepository>,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || message_repo.clone())
 }
 
+pub(crate) fn with_attachment_repo(
+    attachment_repo: Arc<AttachmentRepository>,
+) -> impl Filter<Extract = (Arc<AttachmentRepository>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || attachment_repo.clone())
+}
+
+fn with_link_preview_repo(
+    link_preview_repo: Arc<LinkPreviewRepository>,
+) -> impl Filter<Extract = (Arc<LinkPreviewRepository>,), Error = std::convert::Infallible> + Clone
+{
+    warp::any().map(move || link_preview_repo.clone())
+}
+
+fn with_poll_repo(
+    poll_repo: Arc<PollRepository>,
+) -> impl Filter<Extract = (Arc<PollRepository>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || poll_repo.clone())
+}
+
+fn with_send_guard(
+    send_guard: SendGuard,
+) -> impl Filter<Extract = (SendGuard,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || send_guard.clone())
+}
+
+fn with_command_registry(
+    command_registry: CommandRegistry,
+) -> impl Filter<Extract = (CommandRegistry,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || command_registry.clone())
+}
+
+fn with_link_unfurler(
+    link_unfurler: LinkUnfurler,
+) -> impl Filter<Extract = (LinkUnfurler,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || link_unfurler.clone())
+}
+
+pub(crate) fn with_room_repo(
+    room_repo: Arc<RoomRepository>,
+) -> impl Filter<Extract = (Arc<RoomRepository>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || room_repo.clone())
+}
+
+/// 检查用户是否为房间创建者，不是时返回错误信息
+pub(crate) async fn ensure_room_owner(
+    room_repo: &RoomRepository,
+    room_id: &str,
+    user_id: &str,
+) -> Result<(), &'static str> {
+    match room_repo.find_by_id(room_id).await {
+        Ok(Some(room)) if room.created_by == user_id => Ok(()),
+        Ok(Some(_)) => Err("只有房间创建者可以修改房间设置"),
+        Ok(None) => Err("房间不存在"),
+        Err(_) => Err("数据库错误"),
+    }
+}
+
 fn with_session_manager(
     session_manager: SessionManager,
 ) -> impl Filter<Extract = (SessionManager,), Error = std::convert::Infallible> + Clone {
@@ -219,13 +476,29 @@ async fn handle_register(
 
 async fn handle_login(
     req: LoginRequest,
+    ip: String,
     user_repo: Arc<UserRepository>,
     session_manager: SessionManager,
     auth_service: Arc<AuthService>,
+    audit_logger: AuditLogger,
 ) -> Result<impl Reply, Rejection> {
+    let login_failed = |user_id: Option<&str>, reason: &str| {
+        let mut entry = AuditEntry::new(AuditAction::LoginFailed)
+            .ip(Some(&ip))
+            .details(serde_json::json!({ "email": req.email, "reason": reason, "via": "http" }));
+        if let Some(user_id) = user_id {
+            entry = entry.target("user", user_id);
+        }
+        audit_logger.record(entry);
+    };
+
     match user_repo.find_by_email(&req.email).await {
         Ok(Some(user)) => {
             match user_repo.verify_password(&user, &req.password).await {
+                Ok(true) if user.is_banned() => {
+                    login_failed(Some(&user.id), "banned");
+                    Ok(warp::reply::json(&ApiResponse::<()>::error("账号已被封禁")))
+                }
                 Ok(true) => {
                     // 生成JWT token
                     match auth_service.generate_token(user.id.clone(), user.username.clone()) {
@@ -235,9 +508,15 @@ async fn handle_login(
                                 .create_session(user.id.clone(), user.username.clone())
                                 .await;
 
-                            // 设置用户在线状态
-                            let _ = user_repo.set_online_status(&user.id, true).await;
-                            let _ = session_manager.set_user_online(&user.id).await;
+                            // 在线状态由WebSocket连接维护，登录时不再标记在线
+
+                            audit_logger.record(
+                                AuditEntry::new(AuditAction::Login)
+                                    .actor(&user.id)
+                                    .target("user", &user.id)
+                                    .ip(Some(&ip))
+                                    .details(serde_json::json!({ "via": "http" })),
+                            );
 
                             #[derive(Serialize)]
                             struct LoginResponse {
@@ -258,52 +537,171 @@ async fn handle_login(
                         ))),
                     }
                 }
-                Ok(false) => Ok(warp::reply::json(&ApiResponse::<()>::error("密码错误"))),
+                Ok(false) => {
+                    login_failed(Some(&user.id), "invalid_password");
+                    Ok(warp::reply::json(&ApiResponse::<()>::error("密码错误")))
+                }
                 Err(_) => Ok(warp::reply::json(&ApiResponse::<()>::error("密码验证失败"))),
             }
         }
-        Ok(None) => Ok(warp::reply::json(&ApiResponse::<()>::error("用户不存在"))),
+        Ok(None) => {
+            login_failed(None, "unknown_email");
+            Ok(warp::reply::json(&ApiResponse::<()>::error("用户不存在")))
+        }
         Err(_) => Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误"))),
     }
 }
 
 async fn handle_send_message(
+    user_id: String,
     req: SendMessageRequest,
     user_repo: Arc<UserRepository>,
     message_repo: Arc<MessageRepository>,
-    session_manager: SessionManager,
-    auth_service: Arc<AuthService>,
+    attachment_repo: Arc<AttachmentRepository>,
+    link_unfurler: LinkUnfurler,
+    content_filter: ContentFilter,
+    send_guard: SendGuard,
+    command_registry: CommandRegistry,
+    client_ip: String,
 ) -> Result<impl Reply, Rejection> {
-    // 这里需要从请求中获取用户ID，实际实现中应该从JWT token中解析
-    // 为了简化，这里假设用户ID在请求中
-    let user_id = "temp_user_id".to_string(); // 实际应该从认证中间件获取
-
     match user_repo.find_by_id(&user_id).await {
         Ok(Some(user)) => {
+            // 以/开头的消息先交给命令注册表，命令结果直接返回给调用方
+            let content = match command_registry
+                .dispatch(&user, &req.room_id, &req.content, Some(&client_ip))
+                .await
+            {
+                Ok(CommandDispatch::Message(content)) => content,
+                Ok(CommandDispatch::Reply(reply)) => {
+                    return Ok(warp::reply::json(&ApiResponse::success(
+                        reply,
+                        "命令已执行",
+                    )));
+                }
+                Err(CommandError::Rejected(SendRejection::RateLimited {
+                    reason,
+                    retry_after_secs,
+                })) => {
+                    return Err(warp::reject::custom(RateLimited {
+                        message: reason,
+                        retry_after_secs,
+                    }));
+                }
+                Err(CommandError::Database(_)) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误")));
+                }
+                Err(e) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&e.to_string())));
+                }
+            };
+
+            // 禁言、发送频率、房间慢速模式和垃圾消息检测，超限时返回429
+            match send_guard.check(&user, &req.room_id, &content).await {
+                Ok(Some(SendRejection::RateLimited {
+                    reason,
+                    retry_after_secs,
+                })) => {
+                    return Err(warp::reject::custom(RateLimited {
+                        message: reason,
+                        retry_after_secs,
+                    }));
+                }
+                Ok(Some(rejection)) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(
+                        &rejection.message(),
+                    )));
+                }
+                Ok(None) => {}
+                Err(_) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误")));
+                }
+            }
+
+            if let Err(message) = crate::models::validate_message_ttl(req.ttl_seconds) {
+                return Ok(warp::reply::json(&ApiResponse::<()>::error(&message)));
+            }
+
             let message_type = match req.message_type.as_deref() {
                 Some("image") => crate::models::MessageType::Image,
                 Some("file") => crate::models::MessageType::File,
                 _ => crate::models::MessageType::Text,
             };
 
+            // 附件必须是该用户在该房间上传且尚未发送的
+            let attachments = if req.attachment_ids.is_empty() {
+                Vec::new()
+            } else {
+                match attachment_repo
+                    .find_pending(&req.attachment_ids, &user_id, &req.room_id)
+                    .await
+                {
+                    Ok(Some(attachments)) => attachments,
+                    Ok(None) => {
+                        return Ok(warp::reply::json(&ApiResponse::<()>::error(
+                            "附件无效或已被使用",
+                        )));
+                    }
+                    Err(_) => {
+                        return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误")));
+                    }
+                }
+            };
+
+            // 保存前执行内容过滤
+            let (content, flags) = match content_filter.check(&req.room_id, &content).await {
+                Ok(FilterDecision::Allow { content, flags }) => (content, flags),
+                Ok(FilterDecision::Reject { reason, .. }) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                        "消息被拒绝: {}",
+                        reason
+                    ))));
+                }
+                Err(_) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误")));
+                }
+            };
+
             let message = crate::models::Message::new(
                 user_id,
                 user.username,
-                req.content,
+                content,
                 req.room_id,
                 message_type,
-            );
-
-            match message_repo.create(message).await {
-                Ok(saved_message) => Ok(warp::reply::json(&ApiResponse::success(
-                    saved_message.to_grpc(),
-                    "消息发送成功",
-                ))),
-                Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
-                    "消息发送失败: {}",
-                    e
-                )))),
+            )
+            .with_ttl(req.ttl_seconds);
+
+            let saved_message = match message_repo.create(message).await {
+                Ok(saved_message) => saved_message,
+                Err(e) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                        "消息发送失败: {}",
+                        e
+                    ))));
+                }
+            };
+
+            if !attachments.is_empty() {
+                if let Err(e) = attachment_repo
+                    .attach_to_message(&saved_message.id, &req.attachment_ids)
+                    .await
+                {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                        "关联附件失败: {}",
+                        e
+                    ))));
+                }
+            }
+            if let Err(e) = content_filter.record_flags(&saved_message, &flags).await {
+                eprintln!("保存过滤标记失败: {}", e);
             }
+            link_unfurler.enqueue(&saved_message);
+
+            let mut grpc_message = saved_message.to_grpc();
+            grpc_message.attachments = attachments.iter().map(|a| a.to_grpc()).collect();
+            Ok(warp::reply::json(&ApiResponse::success(
+                grpc_message,
+                "消息发送成功",
+            )))
         }
         Ok(None) => Ok(warp::reply::json(&ApiResponse::<()>::error("用户不存在"))),
         Err(_) => Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误"))),
@@ -314,29 +712,73 @@ async fn handle_get_messages(
     room_id: String,
     query: std::collections::HashMap<String, String>,
     message_repo: Arc<MessageRepository>,
+    attachment_repo: Arc<AttachmentRepository>,
+    link_preview_repo: Arc<LinkPreviewRepository>,
+    poll_repo: Arc<PollRepository>,
 ) -> Result<impl Reply, Rejection> {
-    let limit = query
-        .get("limit")
-        .and_then(|s| s.parse::<i32>().ok())
-        .unwrap_or(1000); // 增加默认限制到1000条消息
-
-    let before_timestamp = query
-        .get("before_timestamp")
-        .and_then(|s| s.parse::<i64>().ok());
+    let limit = query.get("limit").and_then(|s| s.parse::<u32>().ok());
+
+    let history_query = match MessageHistoryQuery::from_params(
+        query.get("before").map(String::as_str),
+        query.get("after").map(String::as_str),
+        query.get("around").map(String::as_str),
+        limit,
+    ) {
+        Ok(history_query) => history_query,
+        Err(e) => return Ok(warp::reply::json(&ApiResponse::<()>::error(&e))),
+    };
 
     println!(
-        "获取消息请求 - 房间ID: {}, 限制: {}, before_timestamp: {:?}",
-        room_id, limit, before_timestamp
+        "获取消息请求 - 房间ID: {}, 查询: {:?}",
+        room_id, history_query
     );
 
     match message_repo
-        .get_messages_by_room(&room_id, limit, before_timestamp)
+        .get_messages_by_room(&room_id, &history_query)
         .await
     {
-        Ok(messages) => {
-            println!("从数据库获取到 {} 条消息", messages.len());
-            let grpc_messages: Vec<_> = messages.into_iter().map(|m| m.to_grpc()).collect();
-            Ok(warp::reply::json(&grpc_messages))
+        Ok(mut page) => {
+            println!("从数据库获取到 {} 条消息", page.messages.len());
+            match attachment_repo
+                .find_by_message_ids(&page.message_ids())
+                .await
+            {
+                Ok(attachments) => page.attachments = attachments,
+                Err(e) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                        "获取附件失败: {}",
+                        e
+                    ))));
+                }
+            }
+            match link_preview_repo
+                .find_by_message_ids(&page.message_ids())
+                .await
+            {
+                Ok(link_previews) => page.link_previews = link_previews,
+                Err(e) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                        "获取链接预览失败: {}",
+                        e
+                    ))));
+                }
+            }
+            match poll_repo
+                .find_infos_by_message_ids(&page.message_ids())
+                .await
+            {
+                Ok(polls) => page.polls = polls,
+                Err(e) => {
+                    return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+                        "获取投票失败: {}",
+                        e
+                    ))));
+                }
+            }
+            Ok(warp::reply::json(&ApiResponse::success(
+                page.to_response(),
+                "获取消息成功",
+            )))
         }
         Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
             "获取消息失败: {}",
@@ -345,6 +787,63 @@ async fn handle_get_messages(
     }
 }
 
+async fn handle_search_messages(
+    user_id: String,
+    params: SearchMessagesParams,
+    message_repo: Arc<MessageRepository>,
+    room_repo: Arc<RoomRepository>,
+) -> Result<impl Reply, Rejection> {
+    // 只在用户可读的房间内搜索
+    let room_ids = match &params.room_id {
+        Some(room_id) => match room_repo.can_read(&user_id, room_id).await {
+            Ok(true) => vec![room_id.clone()],
+            Ok(false) => {
+                return Ok(warp::reply::json(&ApiResponse::<()>::error(
+                    "无权访问该房间",
+                )));
+            }
+            Err(_) => return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误"))),
+        },
+        None => match room_repo.get_readable_room_ids(&user_id).await {
+            Ok(room_ids) => room_ids,
+            Err(_) => return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误"))),
+        },
+    };
+
+    let message_type = match params.message_type.as_deref() {
+        Some(value) => match MessageType::parse(value) {
+            Some(message_type) => Some(message_type),
+            None => {
+                return Ok(warp::reply::json(&ApiResponse::<()>::error(
+                    "无效的消息类型",
+                )));
+            }
+        },
+        None => None,
+    };
+
+    let mut query = MessageSearchQuery::new(params.q, room_ids).with_page(
+        params.page.unwrap_or(1),
+        params.page_size.unwrap_or_default(),
+    );
+    query.user_id = params.user_id;
+    query.message_type = message_type;
+    query.from = params
+        .from
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+    query.to = params
+        .to
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+
+    match message_repo.search_messages(&query).await {
+        Ok(page) => Ok(warp::reply::json(&ApiResponse::success(page, "搜索成功"))),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "搜索消息失败: {}",
+            e
+        )))),
+    }
+}
+
 async fn handle_get_online_users(
     room_id: String,
     session_manager: SessionManager,
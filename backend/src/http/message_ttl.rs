@@ -0,0 +1,76 @@
+use super::handlers::{ApiResponse, ensure_room_owner, with_audit_logger, with_room_repo};
+use super::{with_auth, with_client_ip};
+use crate::audit::AuditLogger;
+use crate::database::{DbPool, RoomRepository, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{AuditAction, AuditEntry, validate_message_ttl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageTtlRequest {
+    /// 新消息默认有效期（秒），0表示不过期
+    pub seconds: u32,
+}
+
+/// 房间消息默认有效期设置路由，只有房间创建者可以修改，只影响之后发送的消息
+pub fn message_ttl_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    audit_logger: AuditLogger,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let room_repo = Arc::new(RoomRepository::new(pool.clone()));
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("message_ttl"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(with_auth(auth_service, user_repo))
+        .and(warp::body::json())
+        .and(with_room_repo(room_repo))
+        .and(with_client_ip())
+        .and(with_audit_logger(audit_logger))
+        .and_then(handle_set_message_ttl)
+}
+
+async fn handle_set_message_ttl(
+    room_id: String,
+    user_id: String,
+    req: MessageTtlRequest,
+    room_repo: Arc<RoomRepository>,
+    ip: String,
+    audit_logger: AuditLogger,
+) -> Result<impl Reply, Rejection> {
+    if let Err(message) = ensure_room_owner(&room_repo, &room_id, &user_id).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(message)));
+    }
+
+    if let Err(message) = validate_message_ttl(req.seconds) {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&message)));
+    }
+
+    match room_repo.set_message_ttl(&room_id, req.seconds).await {
+        Ok(()) => {
+            audit_logger.record(
+                AuditEntry::new(AuditAction::MessageTtlChanged)
+                    .actor(&user_id)
+                    .target("room", &room_id)
+                    .ip(Some(&ip))
+                    .details(serde_json::json!({ "seconds": req.seconds })),
+            );
+            Ok(warp::reply::json(&ApiResponse::success(
+                req,
+                "消息有效期已更新",
+            )))
+        }
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "更新消息有效期失败: {}",
+            e
+        )))),
+    }
+}
@@ -1,21 +1,33 @@
+use crate::database::UserRepository;
 use crate::grpc::auth::AuthService;
 use std::sync::Arc;
 use warp::Filter;
 
+/// 校验Bearer令牌，返回令牌中的用户ID
+///
+/// 令牌在封禁前签发时仍然有效，因此每次请求都检查账号是否已被封禁。
 pub fn with_auth(
     auth_service: Arc<AuthService>,
+    user_repo: Arc<UserRepository>,
 ) -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
     warp::header::<String>("authorization").and_then(move |auth_header: String| {
         let auth_service = auth_service.clone();
+        let user_repo = user_repo.clone();
         async move {
-            if auth_header.starts_with("Bearer ") {
-                let token = &auth_header[7..];
-                match auth_service.verify_token(token) {
-                    Ok(claims) => Ok(claims.user_id),
-                    Err(_) => Err(warp::reject::custom(AuthError)),
+            let Some(token) = auth_header.strip_prefix("Bearer ") else {
+                return Err(warp::reject::custom(AuthError));
+            };
+            let claims = auth_service
+                .verify_token(token)
+                .map_err(|_| warp::reject::custom(AuthError))?;
+
+            match user_repo.find_by_id(&claims.user_id).await {
+                Ok(Some(user)) if !user.is_banned() => Ok(claims.user_id),
+                Ok(_) => Err(warp::reject::custom(AuthError)),
+                Err(e) => {
+                    eprintln!("检查用户状态失败: {}", e);
+                    Err(warp::reject::custom(AuthError))
                 }
-            } else {
-                Err(warp::reject::custom(AuthError))
             }
         }
     })
@@ -1,5 +1,35 @@
+pub mod attachments;
+pub mod audit;
+pub mod avatars;
+pub mod bookmarks;
+pub mod content_filters;
+pub mod exports;
+pub mod forwarding;
 pub mod handlers;
+pub mod message_ttl;
 pub mod middleware;
+pub mod pins;
+pub mod polls;
+pub mod rate_limit;
+pub mod reports;
+pub mod retention;
+pub mod scheduled;
+pub mod slow_mode;
 
+pub use attachments::*;
+pub use audit::*;
+pub use avatars::*;
+pub use bookmarks::*;
+pub use content_filters::*;
+pub use exports::*;
+pub use forwarding::*;
 pub use handlers::*;
+pub use message_ttl::*;
 pub use middleware::*;
+pub use pins::*;
+pub use polls::*;
+pub use rate_limit::*;
+pub use reports::*;
+pub use retention::*;
+pub use scheduled::*;
+pub use slow_mode::*;
@@ -0,0 +1,115 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::reports::load_user;
+use super::{with_auth, with_client_ip};
+use crate::database::{DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::pins::{PinResult, PinService};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+/// 置顶消息路由：查看房间置顶消息，置顶和取消置顶
+pub fn pin_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    pin_service: PinService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let list = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("pins"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_pin_service(pin_service.clone()))
+        .and_then(handle_list_pins);
+
+    let pin = warp::path("api")
+        .and(warp::path("messages"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("pin"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_client_ip())
+        .and(with_pin_service(pin_service.clone()))
+        .and_then(handle_pin_message);
+
+    let unpin = warp::path("api")
+        .and(warp::path("messages"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("pin"))
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(with_user_repo(user_repo))
+        .and(with_client_ip())
+        .and(with_pin_service(pin_service))
+        .and_then(handle_unpin_message);
+
+    list.or(pin).or(unpin)
+}
+
+fn with_pin_service(
+    pin_service: PinService,
+) -> impl Filter<Extract = (PinService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || pin_service.clone())
+}
+
+fn pin_reply<T: serde::Serialize>(result: PinResult<T>, message: &str) -> warp::reply::Json {
+    match result {
+        Ok(data) => warp::reply::json(&ApiResponse::success(data, message)),
+        Err(e) => warp::reply::json(&ApiResponse::<()>::error(&e.to_string())),
+    }
+}
+
+async fn handle_list_pins(
+    room_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    pin_service: PinService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = pin_service.list(&user, &room_id).await;
+    Ok(pin_reply(result, "获取置顶消息成功"))
+}
+
+async fn handle_pin_message(
+    message_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    ip: String,
+    pin_service: PinService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = pin_service.pin(&user, &message_id, Some(&ip)).await;
+    Ok(pin_reply(result, "消息已置顶"))
+}
+
+async fn handle_unpin_message(
+    message_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    ip: String,
+    pin_service: PinService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = pin_service.unpin(&user, &message_id, Some(&ip)).await;
+    Ok(pin_reply(result, "已取消置顶"))
+}
@@ -0,0 +1,135 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::reports::load_user;
+use super::{RateLimited, with_auth};
+use crate::database::{DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{CreatePoll, VotePoll};
+use crate::moderation::SendRejection;
+use crate::polls::{PollError, PollResult, PollService};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+/// 投票路由：发起投票、投票和提前结束
+pub fn poll_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    poll_service: PollService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let create = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("polls"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_poll_service(poll_service.clone()))
+        .and_then(handle_create_poll);
+
+    let vote = warp::path("api")
+        .and(warp::path("polls"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_poll_service(poll_service.clone()))
+        .and_then(handle_vote_poll);
+
+    let close = warp::path("api")
+        .and(warp::path("polls"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("close"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(with_user_repo(user_repo))
+        .and(with_poll_service(poll_service))
+        .and_then(handle_close_poll);
+
+    create.or(vote).or(close)
+}
+
+fn with_poll_service(
+    poll_service: PollService,
+) -> impl Filter<Extract = (PollService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || poll_service.clone())
+}
+
+/// 发送频率超限时和发送消息一样返回429
+fn poll_reply<T: serde::Serialize>(
+    result: PollResult<T>,
+    message: &str,
+) -> Result<warp::reply::Json, Rejection> {
+    match result {
+        Ok(data) => Ok(warp::reply::json(&ApiResponse::success(data, message))),
+        Err(PollError::Rejected(SendRejection::RateLimited {
+            reason,
+            retry_after_secs,
+        })) => Err(warp::reject::custom(RateLimited {
+            message: reason,
+            retry_after_secs,
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&e.to_string()))),
+    }
+}
+
+async fn handle_create_poll(
+    room_id: String,
+    user_id: String,
+    req: CreatePoll,
+    user_repo: Arc<UserRepository>,
+    poll_service: PollService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = poll_service
+        .create(&user, &room_id, req)
+        .await
+        .map(|(message, poll)| {
+            let mut grpc_message = message.to_grpc();
+            grpc_message.poll = Some(poll.into_grpc());
+            grpc_message
+        });
+    poll_reply(result, "投票已发起")
+}
+
+async fn handle_vote_poll(
+    poll_id: String,
+    user_id: String,
+    req: VotePoll,
+    user_repo: Arc<UserRepository>,
+    poll_service: PollService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = poll_service.vote(&user, &poll_id, req.option_ids).await;
+    poll_reply(result, "投票成功")
+}
+
+async fn handle_close_poll(
+    poll_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    poll_service: PollService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = poll_service.close(&user, &poll_id).await;
+    poll_reply(result, "投票已结束")
+}
@@ -0,0 +1,106 @@
+use super::handlers::ApiResponse;
+use crate::redis::{RateLimitDecision, RateLimiter};
+use std::net::SocketAddr;
+use warp::http::{StatusCode, header};
+use warp::{Filter, Rejection, Reply};
+
+/// 请求被限流
+#[derive(Debug)]
+pub struct RateLimited {
+    pub message: String,
+    pub retry_after_secs: u64,
+}
+
+impl warp::reject::Reject for RateLimited {}
+
+/// 按客户端IP限流的过滤器，需要放在路径和方法匹配之后，避免其他路由消耗额度
+///
+/// 设置TRUST_FORWARDED_FOR=true时从X-Forwarded-For取地址（部署在反向代理之后），
+/// 规则见`client_ip`。
+/// Redis不可用时放行。
+pub fn with_ip_rate_limit(
+    rate_limiter: RateLimiter,
+    operation: &'static str,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .and_then(
+            move |remote: Option<SocketAddr>, forwarded_for: Option<String>| {
+                let rate_limiter = rate_limiter.clone();
+                async move {
+                    let ip = client_ip(remote, forwarded_for);
+                    match rate_limiter.check_ip(operation, &ip).await {
+                        Ok(decision @ RateLimitDecision::Limited { .. }) => {
+                            Err(warp::reject::custom(RateLimited {
+                                message: "请求过于频繁".to_string(),
+                                retry_after_secs: decision.retry_after_secs(),
+                            }))
+                        }
+                        Ok(RateLimitDecision::Allowed) => Ok(()),
+                        Err(e) => {
+                            eprintln!("限流检查失败: {}", e);
+                            Ok(())
+                        }
+                    }
+                }
+            },
+        )
+        .untuple_one()
+}
+
+/// 提取客户端IP，规则与按IP限流相同
+pub fn with_client_ip() -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .map(client_ip)
+}
+
+/// X-Forwarded-For最左边的地址由客户端控制，不能信任。可信代理会在末尾追加地址，
+/// 因此从右往左数第TRUSTED_PROXY_HOPS个（默认1，即最右边）才是代理看到的客户端地址；
+/// 地址数量不足时使用连接的对端地址。
+fn client_ip(remote: Option<SocketAddr>, forwarded_for: Option<String>) -> String {
+    let trust_forwarded = std::env::var("TRUST_FORWARDED_FOR")
+        .map(|value| value == "true")
+        .unwrap_or(false);
+
+    if trust_forwarded {
+        let hops = std::env::var("TRUSTED_PROXY_HOPS")
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|hops| *hops > 0)
+            .unwrap_or(1);
+        if let Some(ip) = forwarded_for
+            .as_deref()
+            .and_then(|value| value.rsplit(',').nth(hops - 1))
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+        {
+            return ip.to_string();
+        }
+    }
+
+    remote
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 把限流拒绝转换为429响应并带上Retry-After，其他拒绝原样返回
+pub async fn handle_rate_limit_rejection(
+    rejection: Rejection,
+) -> Result<warp::reply::Response, Rejection> {
+    if let Some(limited) = rejection.find::<RateLimited>() {
+        let body = warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "{}，请在{}秒后重试",
+            limited.message, limited.retry_after_secs
+        )));
+        let reply = warp::reply::with_status(body, StatusCode::TOO_MANY_REQUESTS);
+        let reply = warp::reply::with_header(
+            reply,
+            header::RETRY_AFTER,
+            limited.retry_after_secs.to_string(),
+        );
+        return Ok(reply.into_response());
+    }
+
+    Err(rejection)
+}
@@ -0,0 +1,176 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::{with_auth, with_client_ip};
+use crate::database::{DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{Report, ResolveReport, SubmitReport, User};
+use crate::moderation::{ReportResult, ReportService};
+use serde::Deserialize;
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+#[derive(Debug, Deserialize)]
+pub struct ReportListQuery {
+    /// open/claimed/resolved/all，默认open
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// 举报和审核队列路由
+///
+/// 所有登录用户都可以提交举报，审核接口只允许版主和管理员调用。
+pub fn report_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    report_service: ReportService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let submit = warp::path("api")
+        .and(warp::path("reports"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_report_service(report_service.clone()))
+        .and_then(handle_submit_report);
+
+    let list = warp::path("api")
+        .and(warp::path("moderation"))
+        .and(warp::path("reports"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::query::<ReportListQuery>())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_report_service(report_service.clone()))
+        .and_then(handle_list_reports);
+
+    let claim = warp::path("api")
+        .and(warp::path("moderation"))
+        .and(warp::path("reports"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("claim"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(with_client_ip())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_report_service(report_service.clone()))
+        .and_then(handle_claim_report);
+
+    let resolve = warp::path("api")
+        .and(warp::path("moderation"))
+        .and(warp::path("reports"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("resolve"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_client_ip())
+        .and(with_user_repo(user_repo))
+        .and(with_report_service(report_service))
+        .and_then(handle_resolve_report);
+
+    submit.or(list).or(claim).or(resolve)
+}
+
+fn with_report_service(
+    report_service: ReportService,
+) -> impl Filter<Extract = (ReportService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || report_service.clone())
+}
+
+pub(crate) async fn load_user(
+    user_repo: &UserRepository,
+    user_id: &str,
+) -> Result<User, &'static str> {
+    match user_repo.find_by_id(user_id).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err("用户不存在"),
+        Err(_) => Err("数据库错误"),
+    }
+}
+
+fn report_reply<T: serde::Serialize>(result: ReportResult<T>, message: &str) -> warp::reply::Json {
+    match result {
+        Ok(data) => warp::reply::json(&ApiResponse::success(data, message)),
+        Err(e) => warp::reply::json(&ApiResponse::<()>::error(&e.to_string())),
+    }
+}
+
+async fn handle_submit_report(
+    user_id: String,
+    req: SubmitReport,
+    user_repo: Arc<UserRepository>,
+    report_service: ReportService,
+) -> Result<impl Reply, Rejection> {
+    let reporter = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = report_service.submit(&reporter, req).await;
+    Ok(report_reply(result, "举报已提交"))
+}
+
+async fn handle_list_reports(
+    user_id: String,
+    query: ReportListQuery,
+    user_repo: Arc<UserRepository>,
+    report_service: ReportService,
+) -> Result<impl Reply, Rejection> {
+    let moderator = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result: ReportResult<Vec<Report>> = report_service
+        .list(
+            &moderator,
+            query.status.as_deref(),
+            query.limit.unwrap_or(50),
+            query.offset.unwrap_or(0),
+        )
+        .await;
+    Ok(report_reply(result, "获取举报列表成功"))
+}
+
+async fn handle_claim_report(
+    report_id: String,
+    user_id: String,
+    ip: String,
+    user_repo: Arc<UserRepository>,
+    report_service: ReportService,
+) -> Result<impl Reply, Rejection> {
+    let moderator = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = report_service
+        .claim(&moderator, &report_id, Some(&ip))
+        .await;
+    Ok(report_reply(result, "已认领举报"))
+}
+
+async fn handle_resolve_report(
+    report_id: String,
+    user_id: String,
+    req: ResolveReport,
+    ip: String,
+    user_repo: Arc<UserRepository>,
+    report_service: ReportService,
+) -> Result<impl Reply, Rejection> {
+    let moderator = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = report_service
+        .resolve(&moderator, &report_id, req, Some(&ip))
+        .await;
+    Ok(report_reply(result, "举报已处理"))
+}
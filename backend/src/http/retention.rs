@@ -0,0 +1,124 @@
+use super::handlers::{ApiResponse, with_audit_logger, with_room_repo, with_user_repo};
+use super::reports::load_user;
+use super::{with_auth, with_client_ip};
+use crate::audit::AuditLogger;
+use crate::cleanup::RetentionJob;
+use crate::database::{DbPool, RoomRepository, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{AuditAction, AuditEntry, MAX_RETENTION_DAYS, SetRetention, UserRole};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+/// 消息保留策略路由，只允许管理员调用
+pub fn retention_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    retention_job: RetentionJob,
+    audit_logger: AuditLogger,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool.clone()));
+    let room_repo = Arc::new(RoomRepository::new(pool));
+
+    let set = warp::path("api")
+        .and(warp::path("admin"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("retention"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_room_repo(room_repo))
+        .and(with_client_ip())
+        .and(with_audit_logger(audit_logger))
+        .and_then(handle_set_retention);
+
+    let report = warp::path("api")
+        .and(warp::path("admin"))
+        .and(warp::path("retention"))
+        .and(warp::path("report"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(with_user_repo(user_repo))
+        .and(warp::any().map(move || retention_job.clone()))
+        .and_then(handle_retention_report);
+
+    set.or(report)
+}
+
+async fn ensure_admin(user_repo: &UserRepository, user_id: &str) -> Result<(), &'static str> {
+    match load_user(user_repo, user_id).await? {
+        user if user.role() == UserRole::Admin => Ok(()),
+        _ => Err("只有管理员可以管理保留策略"),
+    }
+}
+
+async fn handle_set_retention(
+    room_id: String,
+    user_id: String,
+    req: SetRetention,
+    user_repo: Arc<UserRepository>,
+    room_repo: Arc<RoomRepository>,
+    ip: String,
+    audit_logger: AuditLogger,
+) -> Result<impl Reply, Rejection> {
+    if let Err(message) = ensure_admin(&user_repo, &user_id).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(message)));
+    }
+
+    if req.days.is_some_and(|days| days > MAX_RETENTION_DAYS) {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "保留天数不能超过{}天",
+            MAX_RETENTION_DAYS
+        ))));
+    }
+
+    match room_repo.find_by_id(&room_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Ok(warp::reply::json(&ApiResponse::<()>::error("房间不存在"))),
+        Err(_) => return Ok(warp::reply::json(&ApiResponse::<()>::error("数据库错误"))),
+    }
+
+    match room_repo.set_retention_days(&room_id, req.days).await {
+        Ok(()) => {
+            audit_logger.record(
+                AuditEntry::new(AuditAction::RetentionChanged)
+                    .actor(&user_id)
+                    .target("room", &room_id)
+                    .ip(Some(&ip))
+                    .details(serde_json::json!({ "days": req.days })),
+            );
+            Ok(warp::reply::json(&ApiResponse::success(
+                req,
+                "保留策略已更新",
+            )))
+        }
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "更新保留策略失败: {}",
+            e
+        )))),
+    }
+}
+
+async fn handle_retention_report(
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    retention_job: RetentionJob,
+) -> Result<impl Reply, Rejection> {
+    if let Err(message) = ensure_admin(&user_repo, &user_id).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(message)));
+    }
+
+    match retention_job.report().await {
+        Ok(reports) => Ok(warp::reply::json(&ApiResponse::success(
+            reports,
+            "获取保留策略预演结果成功",
+        ))),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "统计失败: {}",
+            e
+        )))),
+    }
+}
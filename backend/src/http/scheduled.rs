@@ -0,0 +1,158 @@
+use super::handlers::{ApiResponse, with_user_repo};
+use super::reports::load_user;
+use super::with_auth;
+use crate::database::{DbPool, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{ScheduleMessage, UpdateScheduledMessage};
+use crate::scheduling::{ScheduleResult, ScheduledMessageService};
+use serde::Deserialize;
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduledListQuery {
+    /// pending/sending/sent/failed/cancelled/all，默认pending
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// 定时消息路由：安排、查看、修改和取消自己的定时消息
+pub fn scheduled_message_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    scheduled_service: ScheduledMessageService,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    let schedule = warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("scheduled"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_scheduled_service(scheduled_service.clone()))
+        .and_then(handle_schedule_message);
+
+    let list = warp::path("api")
+        .and(warp::path("scheduled"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::query::<ScheduledListQuery>())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_scheduled_service(scheduled_service.clone()))
+        .and_then(handle_list_scheduled);
+
+    let update = warp::path("api")
+        .and(warp::path("scheduled"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(with_auth(auth_service.clone(), user_repo.clone()))
+        .and(warp::body::json())
+        .and(with_user_repo(user_repo.clone()))
+        .and(with_scheduled_service(scheduled_service.clone()))
+        .and_then(handle_update_scheduled);
+
+    let cancel = warp::path("api")
+        .and(warp::path("scheduled"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(with_auth(auth_service, user_repo.clone()))
+        .and(with_user_repo(user_repo))
+        .and(with_scheduled_service(scheduled_service))
+        .and_then(handle_cancel_scheduled);
+
+    schedule.or(list).or(update).or(cancel)
+}
+
+fn with_scheduled_service(
+    scheduled_service: ScheduledMessageService,
+) -> impl Filter<Extract = (ScheduledMessageService,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || scheduled_service.clone())
+}
+
+fn scheduled_reply<T: serde::Serialize>(
+    result: ScheduleResult<T>,
+    message: &str,
+) -> warp::reply::Json {
+    match result {
+        Ok(data) => warp::reply::json(&ApiResponse::success(data, message)),
+        Err(e) => warp::reply::json(&ApiResponse::<()>::error(&e.to_string())),
+    }
+}
+
+async fn handle_schedule_message(
+    room_id: String,
+    user_id: String,
+    req: ScheduleMessage,
+    user_repo: Arc<UserRepository>,
+    scheduled_service: ScheduledMessageService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = scheduled_service.schedule(&user, &room_id, req).await;
+    Ok(scheduled_reply(result, "定时消息已安排"))
+}
+
+async fn handle_list_scheduled(
+    user_id: String,
+    query: ScheduledListQuery,
+    user_repo: Arc<UserRepository>,
+    scheduled_service: ScheduledMessageService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = scheduled_service
+        .list(
+            &user,
+            query.status.as_deref(),
+            query.limit.unwrap_or(50),
+            query.offset.unwrap_or(0),
+        )
+        .await;
+    Ok(scheduled_reply(result, "获取定时消息成功"))
+}
+
+async fn handle_update_scheduled(
+    scheduled_id: String,
+    user_id: String,
+    req: UpdateScheduledMessage,
+    user_repo: Arc<UserRepository>,
+    scheduled_service: ScheduledMessageService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = scheduled_service.update(&user, &scheduled_id, req).await;
+    Ok(scheduled_reply(result, "定时消息已修改"))
+}
+
+async fn handle_cancel_scheduled(
+    scheduled_id: String,
+    user_id: String,
+    user_repo: Arc<UserRepository>,
+    scheduled_service: ScheduledMessageService,
+) -> Result<impl Reply, Rejection> {
+    let user = match load_user(&user_repo, &user_id).await {
+        Ok(user) => user,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::<()>::error(message))),
+    };
+
+    let result = scheduled_service.cancel(&user, &scheduled_id).await;
+    Ok(scheduled_reply(result, "定时消息已取消"))
+}
@@ -0,0 +1,79 @@
+use super::handlers::{ApiResponse, ensure_room_owner, with_audit_logger, with_room_repo};
+use super::{with_auth, with_client_ip};
+use crate::audit::AuditLogger;
+use crate::database::{DbPool, RoomRepository, UserRepository};
+use crate::grpc::auth::AuthService;
+use crate::models::{AuditAction, AuditEntry, MAX_SLOW_MODE_SECONDS};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlowModeRequest {
+    /// 两次发言的最小间隔（秒），0表示关闭
+    pub seconds: u32,
+}
+
+/// 房间慢速模式设置路由，只有房间创建者可以修改
+pub fn slow_mode_routes(
+    pool: DbPool,
+    auth_service: Arc<AuthService>,
+    audit_logger: AuditLogger,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let room_repo = Arc::new(RoomRepository::new(pool.clone()));
+    let user_repo = Arc::new(UserRepository::new(pool));
+
+    warp::path("api")
+        .and(warp::path("chat"))
+        .and(warp::path("rooms"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("slow_mode"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(with_auth(auth_service, user_repo))
+        .and(warp::body::json())
+        .and(with_room_repo(room_repo))
+        .and(with_client_ip())
+        .and(with_audit_logger(audit_logger))
+        .and_then(handle_set_slow_mode)
+}
+
+async fn handle_set_slow_mode(
+    room_id: String,
+    user_id: String,
+    req: SlowModeRequest,
+    room_repo: Arc<RoomRepository>,
+    ip: String,
+    audit_logger: AuditLogger,
+) -> Result<impl Reply, Rejection> {
+    if let Err(message) = ensure_room_owner(&room_repo, &room_id, &user_id).await {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(message)));
+    }
+
+    if req.seconds > MAX_SLOW_MODE_SECONDS {
+        return Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "慢速模式间隔不能超过{}秒",
+            MAX_SLOW_MODE_SECONDS
+        ))));
+    }
+
+    match room_repo.set_slow_mode(&room_id, req.seconds).await {
+        Ok(()) => {
+            audit_logger.record(
+                AuditEntry::new(AuditAction::SlowModeChanged)
+                    .actor(&user_id)
+                    .target("room", &room_id)
+                    .ip(Some(&ip))
+                    .details(serde_json::json!({ "seconds": req.seconds })),
+            );
+            Ok(warp::reply::json(&ApiResponse::success(
+                req,
+                "慢速模式已更新",
+            )))
+        }
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()>::error(&format!(
+            "更新慢速模式失败: {}",
+            e
+        )))),
+    }
+}
@@ -0,0 +1,153 @@
+use super::{ImportError, ImportResult};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+#[derive(Debug, Deserialize)]
+pub struct SlackUser {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub profile: SlackProfile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SlackProfile {
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackChannel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub created: i64,
+    pub creator: Option<String>,
+    #[serde(default)]
+    pub members: Vec<String>,
+    pub purpose: Option<SlackText>,
+    /// 来自groups.json的私有频道
+    #[serde(skip)]
+    pub is_private: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackText {
+    #[serde(default)]
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackMessage {
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    pub subtype: Option<String>,
+    pub user: Option<String>,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub ts: String,
+    #[serde(default)]
+    pub files: Vec<SlackFile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackFile {
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub permalink: Option<String>,
+}
+
+impl SlackMessage {
+    /// 普通消息、转发到频道的讨论串回复、文件分享和/me消息，加入/离开频道等系统事件不导入
+    pub fn is_importable(&self) -> bool {
+        self.kind == "message"
+            && matches!(
+                self.subtype.as_deref(),
+                None | Some("thread_broadcast") | Some("file_share") | Some("me_message")
+            )
+    }
+
+    /// Slack的ts是带微秒的Unix时间戳，如"1512085950.000216"
+    pub fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let (secs, fraction) = self.ts.split_once('.').unwrap_or((&self.ts, "0"));
+        let secs = secs.parse::<i64>().ok()?;
+        let micros = format!("{:0<6}", fraction).get(..6)?.parse::<u32>().ok()?;
+        chrono::DateTime::from_timestamp(secs, micros * 1000)
+    }
+}
+
+/// Slack工作区导出的ZIP文件
+///
+/// 根目录是users.json、channels.json和可选的groups.json，
+/// 每个频道一个目录，按天保存消息，如`general/2024-01-31.json`。
+pub struct SlackArchive {
+    zip: ZipArchive<File>,
+}
+
+impl SlackArchive {
+    pub fn open(path: &Path) -> ImportResult<Self> {
+        let zip = ZipArchive::new(File::open(path)?)?;
+        Ok(Self { zip })
+    }
+
+    pub fn users(&mut self) -> ImportResult<Vec<SlackUser>> {
+        self.read_json("users.json")?
+            .ok_or_else(|| ImportError::Invalid("导出文件中没有users.json".to_string()))
+    }
+
+    /// 公开频道和私有频道，私有频道只有管理员导出时才有
+    pub fn channels(&mut self) -> ImportResult<Vec<SlackChannel>> {
+        let mut channels: Vec<SlackChannel> = self
+            .read_json("channels.json")?
+            .ok_or_else(|| ImportError::Invalid("导出文件中没有channels.json".to_string()))?;
+        let groups: Vec<SlackChannel> = self.read_json("groups.json")?.unwrap_or_default();
+        channels.extend(groups.into_iter().map(|mut group| {
+            group.is_private = true;
+            group
+        }));
+        Ok(channels)
+    }
+
+    /// 频道目录下按日期排序的消息文件
+    pub fn day_files(&self, channel_name: &str) -> Vec<String> {
+        let prefix = format!("{}/", channel_name);
+        let mut files: Vec<String> = self
+            .zip
+            .file_names()
+            .filter(|name| {
+                name.strip_prefix(&prefix)
+                    .is_some_and(|rest| !rest.contains('/') && rest.ends_with(".json"))
+            })
+            .map(str::to_string)
+            .collect();
+        files.sort();
+        files
+    }
+
+    pub fn messages(&mut self, file_name: &str) -> ImportResult<Vec<SlackMessage>> {
+        Ok(self.read_json(file_name)?.unwrap_or_default())
+    }
+
+    /// 文件不存在时返回None
+    fn read_json<T: DeserializeOwned>(&mut self, file_name: &str) -> ImportResult<Option<T>> {
+        let mut file = match self.zip.by_name(file_name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|source| ImportError::Json {
+                file: file_name.to_string(),
+                source,
+            })
+    }
+}
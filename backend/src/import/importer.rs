@@ -0,0 +1,361 @@
+use super::{SlackArchive, SlackChannel, SlackMarkup, SlackMessage, SlackUser};
+use crate::database::{ImportMappingRepository, RoomRepository, UserRepository};
+use crate::models::{CreateUser, Message, MessageType, Room, User};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 导入映射表中的来源名
+const SOURCE: &str = "slack";
+/// users.username列的长度
+const MAX_USERNAME_LENGTH: usize = 50;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("读取导出文件失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("解析ZIP文件失败: {0}")]
+    Archive(#[from] zip::result::ZipError),
+    #[error("解析{file}失败: {source}")]
+    Json {
+        file: String,
+        source: serde_json::Error,
+    },
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type ImportResult<T> = Result<T, ImportError>;
+
+/// 导入结果汇总
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub users_created: usize,
+    pub users_mapped: usize,
+    pub rooms_created: usize,
+    pub rooms_mapped: usize,
+    pub channels_skipped: usize,
+    pub messages_imported: usize,
+    pub messages_duplicated: usize,
+    pub messages_skipped: usize,
+    pub failed_files: Vec<String>,
+}
+
+impl std::fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Slack导入完成")?;
+        writeln!(
+            f,
+            "  用户: 新建 {}，映射到已有用户 {}",
+            self.users_created, self.users_mapped
+        )?;
+        writeln!(
+            f,
+            "  房间: 新建 {}，映射到已有房间 {}，跳过 {}",
+            self.rooms_created, self.rooms_mapped, self.channels_skipped
+        )?;
+        write!(
+            f,
+            "  消息: 导入 {}，已导入过 {}，跳过 {}",
+            self.messages_imported, self.messages_duplicated, self.messages_skipped
+        )?;
+        if !self.failed_files.is_empty() {
+            write!(
+                f,
+                "\n  无法解析的文件 {} 个: {}",
+                self.failed_files.len(),
+                self.failed_files.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Slack导出文件导入
+///
+/// 用户、房间和消息的Slack ID到本地ID的映射保存在数据库中，重复导入同一个或更新的导出文件时
+/// 只会补充新的内容。用户只按已有映射或邮箱匹配已有用户，没有邮箱时新建用户；
+/// 公开频道按名称匹配已有的公开房间，私有频道总是导入到新建的私有房间。
+pub struct SlackImporter {
+    mapping_repo: Arc<ImportMappingRepository>,
+    user_repo: Arc<UserRepository>,
+    room_repo: Arc<RoomRepository>,
+}
+
+impl SlackImporter {
+    pub fn new(
+        mapping_repo: Arc<ImportMappingRepository>,
+        user_repo: Arc<UserRepository>,
+        room_repo: Arc<RoomRepository>,
+    ) -> Self {
+        Self {
+            mapping_repo,
+            user_repo,
+            room_repo,
+        }
+    }
+
+    /// 导入命令单独运行，直接同步读取ZIP文件
+    pub async fn import(&self, path: &Path) -> ImportResult<ImportReport> {
+        let mut archive = SlackArchive::open(path)?;
+        let mut report = ImportReport::default();
+
+        let slack_users = archive.users()?;
+        let channels = archive.channels()?;
+
+        let mut users = HashMap::new();
+        for slack_user in &slack_users {
+            let user = self.map_user(slack_user, &mut report).await?;
+            users.insert(slack_user.id.clone(), user);
+        }
+        println!("已处理 {} 个Slack用户", users.len());
+
+        let markup = SlackMarkup::new(
+            users
+                .iter()
+                .map(|(id, user)| (id.clone(), user.username.clone()))
+                .collect(),
+            channels
+                .iter()
+                .map(|channel| (channel.id.clone(), channel.name.clone()))
+                .collect(),
+        );
+
+        for channel in &channels {
+            let Some(room) = self.map_room(channel, &users, &mut report).await? else {
+                eprintln!("频道 #{} 找不到可以作为创建者的用户，已跳过", channel.name);
+                report.channels_skipped += 1;
+                continue;
+            };
+            for member in channel.members.iter().filter_map(|id| users.get(id)) {
+                self.room_repo.add_member(&room.id, &member.id).await?;
+            }
+
+            let imported_before = report.messages_imported;
+            for file_name in archive.day_files(&channel.name) {
+                let messages = match archive.messages(&file_name) {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        eprintln!("跳过 {}: {}", file_name, e);
+                        report.failed_files.push(file_name);
+                        continue;
+                    }
+                };
+                for message in &messages {
+                    self.import_message(channel, &room, message, &users, &markup, &mut report)
+                        .await?;
+                }
+            }
+            println!(
+                "频道 #{} 导入 {} 条消息",
+                channel.name,
+                report.messages_imported - imported_before
+            );
+        }
+
+        Ok(report)
+    }
+
+    async fn map_user(
+        &self,
+        slack_user: &SlackUser,
+        report: &mut ImportReport,
+    ) -> ImportResult<User> {
+        if let Some(user_id) = self
+            .mapping_repo
+            .find(SOURCE, "user", &slack_user.id)
+            .await?
+        {
+            if let Some(user) = self.user_repo.find_by_id(&user_id).await? {
+                report.users_mapped += 1;
+                return Ok(user);
+            }
+        }
+
+        let email = slack_user
+            .profile
+            .email
+            .as_deref()
+            .filter(|email| !email.is_empty());
+        // 用户名相同不代表是同一个人，不能按用户名合并
+        let existing = match email {
+            Some(email) => self.user_repo.find_by_email(email).await?,
+            None => None,
+        };
+        let user = match existing {
+            Some(user) => {
+                report.users_mapped += 1;
+                user
+            }
+            None => {
+                report.users_created += 1;
+                self.create_user(slack_user, email).await?
+            }
+        };
+        self.mapping_repo
+            .save(SOURCE, "user", &slack_user.id, &user.id)
+            .await?;
+
+        Ok(user)
+    }
+
+    /// 新建的用户使用随机密码，不能直接登录
+    async fn create_user(&self, slack_user: &SlackUser, email: Option<&str>) -> ImportResult<User> {
+        let slack_id = slack_user.id.to_lowercase();
+        let mut username: String = slack_user.name.chars().take(MAX_USERNAME_LENGTH).collect();
+        if self.user_repo.find_by_username(&username).await?.is_some() {
+            // 用户名已被其他人占用，加上Slack ID区分
+            let prefix: String = slack_user
+                .name
+                .chars()
+                .take(MAX_USERNAME_LENGTH - slack_id.len() - 1)
+                .collect();
+            username = format!("{}-{}", prefix, slack_id);
+        }
+        let email = email
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}@slack-import.invalid", slack_id));
+
+        let user = self
+            .user_repo
+            .create(CreateUser {
+                username,
+                email,
+                password: uuid::Uuid::new_v4().to_string(),
+            })
+            .await?;
+        Ok(user)
+    }
+
+    /// 没有可用的创建者时返回None
+    async fn map_room(
+        &self,
+        channel: &SlackChannel,
+        users: &HashMap<String, User>,
+        report: &mut ImportReport,
+    ) -> ImportResult<Option<Room>> {
+        if let Some(room_id) = self.mapping_repo.find(SOURCE, "room", &channel.id).await? {
+            if let Some(room) = self.room_repo.find_by_id(&room_id).await? {
+                report.rooms_mapped += 1;
+                return Ok(Some(room));
+            }
+        }
+
+        // 私有频道的历史不能发布到同名的公开房间，公开频道也不合并到私有房间
+        let existing = if channel.is_private {
+            None
+        } else {
+            self.room_repo
+                .find_by_name(&channel.name)
+                .await?
+                .filter(|room| room.is_public_room())
+        };
+        let room = match existing {
+            Some(room) => {
+                report.rooms_mapped += 1;
+                room
+            }
+            None => {
+                let Some(owner) = channel
+                    .creator
+                    .iter()
+                    .chain(&channel.members)
+                    .find_map(|id| users.get(id))
+                else {
+                    return Ok(None);
+                };
+                let description = channel
+                    .purpose
+                    .as_ref()
+                    .map(|purpose| purpose.value.clone())
+                    .filter(|value| !value.is_empty());
+                let mut room = Room::new(
+                    channel.name.clone(),
+                    description,
+                    !channel.is_private,
+                    owner.id.clone(),
+                );
+                if let Some(created_at) = chrono::DateTime::from_timestamp(channel.created, 0) {
+                    room.created_at = created_at;
+                }
+
+                report.rooms_created += 1;
+                let room = self.room_repo.create(room).await?;
+                self.room_repo.add_member(&room.id, &owner.id).await?;
+                room
+            }
+        };
+        self.mapping_repo
+            .save(SOURCE, "room", &channel.id, &room.id)
+            .await?;
+
+        Ok(Some(room))
+    }
+
+    /// 按频道ID和ts去重，保留原来的作者和发送时间
+    async fn import_message(
+        &self,
+        channel: &SlackChannel,
+        room: &Room,
+        slack_message: &SlackMessage,
+        users: &HashMap<String, User>,
+        markup: &SlackMarkup,
+        report: &mut ImportReport,
+    ) -> ImportResult<()> {
+        if !slack_message.is_importable() {
+            report.messages_skipped += 1;
+            return Ok(());
+        }
+        // 机器人消息没有对应的用户
+        let author = slack_message.user.as_ref().and_then(|id| users.get(id));
+        let (Some(author), Some(created_at)) = (author, slack_message.timestamp()) else {
+            report.messages_skipped += 1;
+            return Ok(());
+        };
+
+        // 导出文件不包含附件本身，只保留原文件的链接
+        let mut content = markup.convert(&slack_message.text);
+        for file in &slack_message.files {
+            let name = file
+                .title
+                .as_deref()
+                .or(file.name.as_deref())
+                .unwrap_or("附件");
+            let line = match &file.permalink {
+                Some(url) => format!("[{}]({})", name, url),
+                None => name.to_string(),
+            };
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(&line);
+        }
+        if content.is_empty() {
+            report.messages_skipped += 1;
+            return Ok(());
+        }
+
+        let mut message = Message::new(
+            author.id.clone(),
+            author.username.clone(),
+            content,
+            room.id.clone(),
+            MessageType::Text,
+        );
+        message.created_at = created_at;
+
+        let external_id = format!("{}:{}", channel.id, slack_message.ts);
+        if self
+            .mapping_repo
+            .insert_message(SOURCE, &external_id, &message)
+            .await?
+        {
+            report.messages_imported += 1;
+        } else {
+            report.messages_duplicated += 1;
+        }
+        Ok(())
+    }
+}
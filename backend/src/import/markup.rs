@@ -0,0 +1,142 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Slack消息格式转换为Markdown
+///
+/// `<@U123>`、`<#C123|general>`换成用户名和频道名，`<url|文字>`换成Markdown链接，
+/// `*粗体*`换成`**粗体**`。斜体`_文字_`与Markdown相同，删除线没有对应语法，保持原样。
+/// 代码块和行内代码中的内容只做HTML实体还原。
+pub struct SlackMarkup {
+    /// Slack用户ID到本地用户名
+    users: HashMap<String, String>,
+    /// Slack频道ID到频道名
+    channels: HashMap<String, String>,
+}
+
+impl SlackMarkup {
+    pub fn new(users: HashMap<String, String>, channels: HashMap<String, String>) -> Self {
+        Self { users, channels }
+    }
+
+    pub fn convert(&self, text: &str) -> String {
+        let mut converted = String::with_capacity(text.len());
+        let mut last = 0;
+        for code in code_pattern().find_iter(text) {
+            converted.push_str(&self.convert_text(&text[last..code.start()]));
+            converted.push_str(&convert_code(code.as_str()));
+            last = code.end();
+        }
+        converted.push_str(&self.convert_text(&text[last..]));
+        converted.trim().to_string()
+    }
+
+    fn convert_text(&self, text: &str) -> String {
+        let text = reference_pattern().replace_all(text, |caps: &regex::Captures| {
+            self.convert_reference(&caps[1])
+        });
+        unescape(&convert_bold(&text))
+    }
+
+    /// 尖括号中的提及、频道、特殊提及和链接
+    fn convert_reference(&self, reference: &str) -> String {
+        let (target, label) = match reference.split_once('|') {
+            Some((target, label)) => (target, Some(label)),
+            None => (reference, None),
+        };
+
+        if let Some(id) = target.strip_prefix('@') {
+            let name = self.users.get(id).map(String::as_str).or(label);
+            return format!("@{}", name.unwrap_or(id));
+        }
+        if let Some(id) = target.strip_prefix('#') {
+            let name = self.channels.get(id).map(String::as_str).or(label);
+            return format!("#{}", name.unwrap_or(id));
+        }
+        if let Some(command) = target.strip_prefix('!') {
+            // <!here>、<!channel>，以及带显示文字的<!subteam^ID|@team>、<!date^...|文字>
+            return match label {
+                Some(label) => label.to_string(),
+                None => format!("@{}", command),
+            };
+        }
+
+        let plain = target.strip_prefix("mailto:").unwrap_or(target);
+        match label {
+            Some(label) if label != target && label != plain => format!("[{}]({})", label, target),
+            _ => plain.to_string(),
+        }
+    }
+}
+
+fn code_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?s)```.*?```|`[^`\n]+`").unwrap())
+}
+
+fn reference_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"<([^<>\n]+)>").unwrap())
+}
+
+/// Slack允许代码块与文字写在同一行，Markdown需要围栏单独成行
+fn convert_code(code: &str) -> String {
+    match code
+        .strip_prefix("```")
+        .and_then(|code| code.strip_suffix("```"))
+    {
+        Some(block) => format!("\n```\n{}\n```\n", unescape(block.trim_matches('\n'))),
+        None => unescape(code),
+    }
+}
+
+/// 把成对的单个星号换成双星号，星号两侧必须是单词边界
+fn convert_bold(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut converted = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '*' && is_bold_start(&chars, i) {
+            if let Some(end) = find_bold_end(&chars, i) {
+                converted.push_str("**");
+                converted.extend(&chars[i + 1..end]);
+                converted.push_str("**");
+                i = end + 1;
+                continue;
+            }
+        }
+        converted.push(chars[i]);
+        i += 1;
+    }
+    converted
+}
+
+fn is_word_boundary(c: Option<&char>) -> bool {
+    !matches!(c, Some(c) if c.is_alphanumeric() || *c == '*')
+}
+
+fn is_bold_start(chars: &[char], i: usize) -> bool {
+    let prev = i.checked_sub(1).and_then(|p| chars.get(p));
+    is_word_boundary(prev) && matches!(chars.get(i + 1), Some(c) if !c.is_whitespace() && *c != '*')
+}
+
+/// 结束星号必须在同一行
+fn find_bold_end(chars: &[char], start: usize) -> Option<usize> {
+    for (j, c) in chars.iter().enumerate().skip(start + 2) {
+        match c {
+            '\n' => return None,
+            '*' if !chars[j - 1].is_whitespace() && is_word_boundary(chars.get(j + 1)) => {
+                return Some(j);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Slack只转义这三个字符
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,7 @@
+pub mod archive;
+pub mod importer;
+pub mod markup;
+
+pub use archive::*;
+pub use importer::*;
+pub use markup::*;
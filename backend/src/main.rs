@@ -1,8 +1,22 @@
+mod audit;
+mod bookmarks;
+mod cleanup;
+mod commands;
 mod database;
+mod export;
+mod forwarding;
 mod grpc;
 mod http;
+mod import;
+mod media;
 mod models;
+mod moderation;
+mod pins;
+mod polls;
 mod redis;
+mod scheduling;
+mod storage;
+mod unfurl;
 mod websocket;
 
 // 包含生成的gRPC代码
@@ -11,17 +25,37 @@ pub mod chat {
 }
 
 use crate::chat::{chat_service_server::ChatServiceServer, user_service_server::UserServiceServer};
+use audit::AuditLogger;
+use bookmarks::BookmarkService;
+use cleanup::{AvatarCleanup, ExpirySweeper, MessagePurger, RetentionJob};
+use commands::CommandRegistry;
+use database::{
+    AttachmentRepository, AuditRepository, BookmarkRepository, ContentFilterRepository,
+    ExportJobRepository, ImportMappingRepository, LinkPreviewRepository, MessageRepository,
+    ModerationRepository, PinRepository, PollRepository, ReportRepository, RoomRepository,
+    ScheduledMessageRepository, UserRepository,
+};
 use database::{create_pool, init_database};
+use export::ExportService;
+use forwarding::ForwardService;
 use grpc::{AuthService, ChatServiceImpl, UserServiceImpl};
 use http::create_routes;
-use redis::{SessionManager, create_redis_client};
+use import::SlackImporter;
+use models::RetentionConfig;
+use moderation::{ContentFilter, ReportService, SendGuard, SpamConfig, SpamDetector};
+use pins::PinService;
+use polls::PollService;
+use redis::{PresenceManager, RateLimiter, SessionManager, TypingManager, create_redis_client};
+use scheduling::{ScheduledDispatcher, ScheduledMessageService};
+use std::path::Path;
 use std::sync::Arc;
+use storage::create_blob_store;
 use tokio::net::TcpListener;
-use tokio_tungstenite::accept_async;
 use tonic::transport::Server;
 use tracing::{error, info};
+use unfurl::{HttpPageFetcher, LinkUnfurler};
 use warp::Filter;
-use websocket::WebSocketHandler;
+use websocket::{BroadcastHandler, PresenceTracker, RoomEventBus, TypingTracker, WebSocketHandler};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -31,6 +65,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 加载环境变量
     dotenv::dotenv().ok();
 
+    // 导入Slack导出文件：chat-backend import-slack <export.zip>
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("import-slack") {
+        let Some(path) = args.get(2) else {
+            eprintln!("用法: chat-backend import-slack <export.zip>");
+            std::process::exit(2);
+        };
+        init_database().await?;
+        let db_pool = create_pool().await?;
+        let importer = SlackImporter::new(
+            Arc::new(ImportMappingRepository::new(db_pool.clone())),
+            Arc::new(UserRepository::new(db_pool.clone())),
+            Arc::new(RoomRepository::new(db_pool)),
+        );
+        let report = importer.import(Path::new(path)).await?;
+        println!("{}", report);
+        return Ok(());
+    }
+
     info!("Starting Chat Server...");
 
     // 初始化数据库
@@ -50,16 +103,251 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::env::var("JWT_SECRET").unwrap_or_else(|_| "your-secret-key".to_string()),
     ));
 
+    // 房间广播在WebSocket和gRPC之间共享，并通过Redis事件总线跨实例同步
+    let broadcast_handler = Arc::new(tokio::sync::Mutex::new(BroadcastHandler::new()));
+    let event_bus = RoomEventBus::new(redis_client.clone());
+    tokio::spawn(event_bus.clone().run_relay(broadcast_handler.clone()));
+
+    let typing_tracker =
+        TypingTracker::new(TypingManager::new(redis_client.clone()), event_bus.clone());
+    tokio::spawn(typing_tracker.clone().run_expiry_sweeper());
+
+    // 在线状态由连接和心跳驱动
+    let presence_tracker = PresenceTracker::new(
+        PresenceManager::new(redis_client.clone()),
+        session_manager.clone(),
+        Arc::new(UserRepository::new(db_pool.clone())),
+        event_bus.clone(),
+    );
+    tokio::spawn(presence_tracker.clone().run_expiry_sweeper());
+
+    // 链接预览在后台抓取，完成后通过事件总线通知房间
+    let (link_unfurler, unfurl_worker) = LinkUnfurler::new(
+        Arc::new(HttpPageFetcher::new()),
+        Arc::new(LinkPreviewRepository::new(db_pool.clone())),
+        event_bus.clone(),
+    );
+    tokio::spawn(unfurl_worker.run());
+
+    // 消息内容过滤：全局规则来自配置文件，房间规则保存在数据库
+    let content_filter = ContentFilter::new(
+        ContentFilter::load_global_rules()?,
+        Arc::new(ContentFilterRepository::new(db_pool.clone())),
+    )?;
+
+    // 审计日志异步批量写入，不占用聊天请求的处理时间
+    let (audit_logger, audit_worker) =
+        AuditLogger::new(Arc::new(AuditRepository::new(db_pool.clone())));
+    tokio::spawn(audit_worker.run());
+
+    // 限流计数保存在Redis中，多实例共享
+    let rate_limiter = RateLimiter::from_env(redis_client.clone());
+
+    // 垃圾消息检测，命中后自动禁言并通知版主
+    let moderation_repo = Arc::new(ModerationRepository::new(db_pool.clone()));
+    let spam_detector = SpamDetector::new(
+        redis_client.clone(),
+        SpamConfig::from_env(),
+        moderation_repo.clone(),
+        Arc::new(UserRepository::new(db_pool.clone())),
+        event_bus.clone(),
+        audit_logger.clone(),
+    );
+    let send_guard = SendGuard::new(
+        rate_limiter.clone(),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        moderation_repo.clone(),
+        spam_detector,
+    );
+
+    // 投票消息，票数变化通过事件总线广播
+    let poll_service = PollService::new(
+        Arc::new(PollRepository::new(db_pool.clone())),
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        content_filter.clone(),
+        send_guard.clone(),
+        event_bus.clone(),
+    );
+
+    // 置顶消息，变化通过事件总线广播
+    let pin_service = PinService::new(
+        Arc::new(PinRepository::new(db_pool.clone())),
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        Arc::new(AttachmentRepository::new(db_pool.clone())),
+        Arc::new(LinkPreviewRepository::new(db_pool.clone())),
+        Arc::new(PollRepository::new(db_pool.clone())),
+        event_bus.clone(),
+        audit_logger.clone(),
+    );
+
+    // 个人收藏
+    let bookmark_service = BookmarkService::new(
+        Arc::new(BookmarkRepository::new(db_pool.clone())),
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        Arc::new(AttachmentRepository::new(db_pool.clone())),
+        Arc::new(LinkPreviewRepository::new(db_pool.clone())),
+        Arc::new(PollRepository::new(db_pool.clone())),
+    );
+
+    // 转发和引用回复，按普通消息检查和广播
+    let forward_service = ForwardService::new(
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        send_guard.clone(),
+        content_filter.clone(),
+        link_unfurler.clone(),
+        event_bus.clone(),
+    );
+
+    // 斜杠命令注册表，内置命令由WebSocket事件处理器工厂注册
+    let command_registry = CommandRegistry::new(
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        send_guard.clone(),
+    );
+
+    // 定时消息，到期后由后台任务按普通消息发送
+    let scheduled_repo = Arc::new(ScheduledMessageRepository::new(db_pool.clone()));
+    let scheduled_service = ScheduledMessageService::new(
+        scheduled_repo.clone(),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+    );
+    let scheduled_dispatcher = ScheduledDispatcher::new(
+        scheduled_repo,
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(UserRepository::new(db_pool.clone())),
+        send_guard.clone(),
+        content_filter.clone(),
+        link_unfurler.clone(),
+        event_bus.clone(),
+    );
+    tokio::spawn(scheduled_dispatcher.run());
+
+    // 附件存储后端
+    let blob_store = create_blob_store()?;
+
+    // 删除消息时附件文件一并删除，供过期清理、保留策略和举报处理共用
+    let message_purger = MessagePurger::new(
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(AttachmentRepository::new(db_pool.clone())),
+        blob_store.clone(),
+    );
+
+    // 举报审核，处理结果通过事件总线通知举报人
+    let report_service = ReportService::new(
+        Arc::new(ReportRepository::new(db_pool.clone())),
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        Arc::new(UserRepository::new(db_pool.clone())),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        moderation_repo,
+        session_manager.clone(),
+        event_bus.clone(),
+        audit_logger.clone(),
+        message_purger.clone(),
+    );
+
     // 创建服务实例
-    let user_service = UserServiceImpl::new(db_pool.clone(), redis_client.clone());
-    let chat_service = ChatServiceImpl::new(db_pool.clone(), redis_client.clone());
+    let user_service = UserServiceImpl::new(
+        db_pool.clone(),
+        redis_client.clone(),
+        presence_tracker.clone(),
+        rate_limiter.clone(),
+        audit_logger.clone(),
+    );
+    let chat_service = ChatServiceImpl::new(
+        db_pool.clone(),
+        redis_client.clone(),
+        broadcast_handler.clone(),
+        typing_tracker.clone(),
+        link_unfurler.clone(),
+        content_filter.clone(),
+        send_guard.clone(),
+        rate_limiter.clone(),
+        report_service.clone(),
+        audit_logger.clone(),
+        poll_service.clone(),
+        scheduled_service.clone(),
+        pin_service.clone(),
+        bookmark_service.clone(),
+        forward_service.clone(),
+        command_registry.clone(),
+    );
     let ws_handler = Arc::new(WebSocketHandler::new(
         db_pool.clone(),
         session_manager.clone(),
+        broadcast_handler,
+        typing_tracker,
+        presence_tracker,
+        link_unfurler.clone(),
+        content_filter.clone(),
+        send_guard.clone(),
+        rate_limiter.clone(),
+        report_service.clone(),
+        audit_logger.clone(),
+        poll_service.clone(),
+        pin_service.clone(),
+        forward_service.clone(),
+        event_bus.clone(),
+        command_registry.clone(),
     ));
 
+    // 过期消息清理
+    let expiry_sweeper = ExpirySweeper::new(
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        message_purger.clone(),
+        event_bus,
+    );
+    tokio::spawn(expiry_sweeper.run());
+
+    // 按房间保留策略分批删除历史消息
+    let retention_job = RetentionJob::new(
+        RetentionConfig::from_env(),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        message_purger.clone(),
+    );
+    tokio::spawn(retention_job.clone().run());
+
+    // 房间历史导出，文件按分片保存在存储后端中
+    let (export_service, export_worker) = ExportService::new(
+        Arc::new(ExportJobRepository::new(db_pool.clone())),
+        Arc::new(RoomRepository::new(db_pool.clone())),
+        Arc::new(MessageRepository::new(db_pool.clone())),
+        blob_store.clone(),
+        audit_logger.clone(),
+    );
+    tokio::spawn(export_worker.run());
+
+    // 被替换的头像在宽限期过后删除文件
+    let avatar_cleanup = AvatarCleanup::new(
+        Arc::new(UserRepository::new(db_pool.clone())),
+        blob_store.clone(),
+    );
+    tokio::spawn(avatar_cleanup.run());
+
     // 创建HTTP API路由
-    let api_routes = create_routes(db_pool, session_manager, auth_service);
+    let api_routes = create_routes(
+        db_pool,
+        session_manager,
+        auth_service,
+        blob_store,
+        link_unfurler,
+        content_filter,
+        send_guard,
+        rate_limiter,
+        report_service,
+        audit_logger,
+        poll_service,
+        scheduled_service,
+        retention_job,
+        export_service,
+        pin_service,
+        bookmark_service,
+        forward_service,
+        command_registry,
+    );
 
     // 启动gRPC服务器
     let grpc_addr = "0.0.0.0:50051".parse()?;
@@ -78,9 +366,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         while let Ok((stream, _)) = ws_listener.accept().await {
             let ws_handler = ws_handler_clone.clone();
             tokio::spawn(async move {
-                ws_handler
-                    .handle_connection(accept_async(stream).await.unwrap())
-                    .await;
+                if let Err(e) = ws_handler.handle_connection(stream).await {
+                    println!("WebSocket连接出错: {}", e);
+                }
             });
         }
     });
@@ -0,0 +1,206 @@
+use bytes::Bytes;
+use image::imageops::FilterType;
+use image::io::{Limits, Reader};
+use image::{DynamicImage, ImageFormat, ImageOutputFormat};
+use std::borrow::Cow;
+use std::io::Cursor;
+
+/// 缩略图最长边的像素数
+pub const THUMBNAIL_SIZES: [u32; 3] = [160, 480, 960];
+
+/// 允许处理的最大图片尺寸，防止解压炸弹
+const MAX_IMAGE_DIMENSION: u32 = 12_000;
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MediaError {
+    #[error("不支持的图片格式")]
+    UnsupportedFormat,
+    #[error("无效的图片: {0}")]
+    InvalidImage(String),
+}
+
+pub type MediaResult<T> = Result<T, MediaError>;
+
+/// 编码后的图片
+#[derive(Debug, Clone)]
+pub struct EncodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: &'static str,
+    pub data: Bytes,
+}
+
+/// 缩略图，size为生成时使用的最长边
+#[derive(Debug, Clone)]
+pub struct EncodedThumbnail {
+    pub size: u32,
+    pub image: EncodedImage,
+}
+
+/// 处理后的图片：已去除EXIF等元数据，并附带缩略图
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub original: EncodedImage,
+    pub thumbnails: Vec<EncodedThumbnail>,
+}
+
+/// 根据文件内容判断是否为支持的图片格式
+pub fn detect_image_format(data: &[u8]) -> Option<ImageFormat> {
+    match image::guess_format(data) {
+        Ok(
+            format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP),
+        ) => Some(format),
+        _ => None,
+    }
+}
+
+/// 根据内容判断图片的MIME类型
+pub fn image_mime_type(data: &[u8]) -> Option<&'static str> {
+    match detect_image_format(data)? {
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// 校验并处理上传的图片
+///
+/// 图片会按EXIF方向旋转后重新编码，从而去掉GPS等元数据。
+/// GIF保留原始内容以保留动画（GIF不包含EXIF）。CPU密集，应在阻塞线程中调用。
+pub fn process_image(data: &[u8]) -> MediaResult<ProcessedImage> {
+    let format = detect_image_format(data).ok_or(MediaError::UnsupportedFormat)?;
+    let image = decode(data, format)?;
+
+    let original = match format {
+        ImageFormat::Gif => EncodedImage {
+            width: image.width(),
+            height: image.height(),
+            mime_type: "image/gif",
+            data: Bytes::copy_from_slice(data),
+        },
+        ImageFormat::Jpeg => encode(&image, ImageOutputFormat::Jpeg(JPEG_QUALITY))?,
+        // PNG和WebP统一重新编码为PNG，保留透明通道
+        _ => encode(&image, ImageOutputFormat::Png)?,
+    };
+
+    let mut thumbnails = Vec::new();
+    for size in THUMBNAIL_SIZES {
+        // 不放大小图
+        if image.width() <= size && image.height() <= size {
+            break;
+        }
+        let resized = image.resize(size, size, FilterType::Triangle);
+        thumbnails.push(EncodedThumbnail {
+            size,
+            image: encode_compact(&resized)?,
+        });
+    }
+
+    Ok(ProcessedImage {
+        original,
+        thumbnails,
+    })
+}
+
+/// 居中裁剪为正方形并生成指定边长的图片
+pub fn square_variants(data: &[u8], sizes: &[u32]) -> MediaResult<Vec<EncodedThumbnail>> {
+    let format = detect_image_format(data).ok_or(MediaError::UnsupportedFormat)?;
+    let image = decode(data, format)?;
+
+    let side = image.width().min(image.height());
+    let square = image.crop_imm(
+        (image.width() - side) / 2,
+        (image.height() - side) / 2,
+        side,
+        side,
+    );
+
+    sizes
+        .iter()
+        .map(|&size| {
+            let resized = square.resize_exact(size, size, FilterType::Lanczos3);
+            Ok(EncodedThumbnail {
+                size,
+                image: encode_compact(&resized)?,
+            })
+        })
+        .collect()
+}
+
+fn decode(data: &[u8], format: ImageFormat) -> MediaResult<DynamicImage> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = Reader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|e| MediaError::InvalidImage(e.to_string()))?;
+
+    Ok(apply_orientation(image, read_orientation(data)))
+}
+
+/// 读取EXIF方向，没有EXIF时返回1（正常方向）
+fn read_orientation(data: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .unwrap_or(1)
+}
+
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// 有透明通道时使用PNG，否则使用JPEG
+fn encode_compact(image: &DynamicImage) -> MediaResult<EncodedImage> {
+    if image.color().has_alpha() {
+        encode(image, ImageOutputFormat::Png)
+    } else {
+        encode(image, ImageOutputFormat::Jpeg(JPEG_QUALITY))
+    }
+}
+
+fn encode(image: &DynamicImage, format: ImageOutputFormat) -> MediaResult<EncodedImage> {
+    let mime_type = match format {
+        ImageOutputFormat::Jpeg(_) => "image/jpeg",
+        _ => "image/png",
+    };
+    // JPEG只支持8位灰度和RGB
+    let image = match (&format, image) {
+        (ImageOutputFormat::Jpeg(_), DynamicImage::ImageRgb8(_) | DynamicImage::ImageLuma8(_)) => {
+            Cow::Borrowed(image)
+        }
+        (ImageOutputFormat::Jpeg(_), _) => Cow::Owned(DynamicImage::ImageRgb8(image.to_rgb8())),
+        _ => Cow::Borrowed(image),
+    };
+
+    let mut buffer = Cursor::new(Vec::new());
+    image
+        .write_to(&mut buffer, format)
+        .map_err(|e| MediaError::InvalidImage(e.to_string()))?;
+
+    Ok(EncodedImage {
+        width: image.width(),
+        height: image.height(),
+        mime_type,
+        data: Bytes::from(buffer.into_inner()),
+    })
+}
@@ -0,0 +1,3 @@
+pub mod image_processor;
+
+pub use image_processor::*;
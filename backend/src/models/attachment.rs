@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 单个附件的最大字节数
+pub const MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Attachment {
+    pub id: String,
+    pub room_id: String,
+    pub uploader_id: String,
+    /// 上传后、发送消息前为空
+    pub message_id: Option<String>,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    /// 文件内容的SHA-256（十六进制）
+    pub checksum: String,
+    pub storage_key: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// 图片尺寸，非图片为空
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// 缩略图列表的JSON，见`AttachmentThumbnail`
+    pub thumbnails: Option<String>,
+}
+
+/// 图片附件的缩略图，size为最长边
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentThumbnail {
+    pub size: u32,
+    pub width: i32,
+    pub height: i32,
+    pub mime_type: String,
+    pub storage_key: String,
+}
+
+/// 返回给客户端的缩略图信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailInfo {
+    pub size: u32,
+    pub width: i32,
+    pub height: i32,
+    pub url: String,
+}
+
+/// 返回给客户端的附件信息，不包含存储位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentInfo {
+    pub id: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub checksum: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thumbnails: Vec<ThumbnailInfo>,
+}
+
+impl Attachment {
+    pub fn new(
+        room_id: String,
+        uploader_id: String,
+        file_name: String,
+        mime_type: String,
+        size_bytes: i64,
+        checksum: String,
+    ) -> Self {
+        let id = Uuid::new_v4().to_string();
+        Self {
+            storage_key: format!("attachments/{}/{}", room_id, id),
+            id,
+            room_id,
+            uploader_id,
+            message_id: None,
+            file_name,
+            mime_type,
+            size_bytes,
+            checksum,
+            created_at: chrono::Utc::now(),
+            width: None,
+            height: None,
+            thumbnails: None,
+        }
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.width.is_some() && self.height.is_some()
+    }
+
+    pub fn thumbnail_key(&self, size: u32) -> String {
+        format!("thumbnails/{}/{}_{}", self.room_id, self.id, size)
+    }
+
+    pub fn thumbnail_list(&self) -> Vec<AttachmentThumbnail> {
+        self.thumbnails
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 记录图片尺寸和缩略图
+    pub fn set_image_metadata(
+        &mut self,
+        width: i32,
+        height: i32,
+        thumbnails: &[AttachmentThumbnail],
+    ) {
+        self.width = Some(width);
+        self.height = Some(height);
+        self.thumbnails = serde_json::to_string(thumbnails).ok();
+    }
+
+    fn thumbnail_infos(&self) -> Vec<ThumbnailInfo> {
+        self.thumbnail_list()
+            .into_iter()
+            .map(|thumbnail| ThumbnailInfo {
+                size: thumbnail.size,
+                width: thumbnail.width,
+                height: thumbnail.height,
+                url: format!("{}/thumbnails/{}", self.download_url(), thumbnail.size),
+            })
+            .collect()
+    }
+
+    /// 下载地址，下载时校验房间成员身份
+    pub fn download_url(&self) -> String {
+        format!("/api/attachments/{}", self.id)
+    }
+
+    pub fn to_info(&self) -> AttachmentInfo {
+        AttachmentInfo {
+            id: self.id.clone(),
+            file_name: self.file_name.clone(),
+            mime_type: self.mime_type.clone(),
+            size: self.size_bytes,
+            checksum: self.checksum.clone(),
+            url: self.download_url(),
+            width: self.width,
+            height: self.height,
+            thumbnails: self.thumbnail_infos(),
+        }
+    }
+
+    pub fn to_grpc(&self) -> crate::chat::Attachment {
+        crate::chat::Attachment {
+            id: self.id.clone(),
+            file_name: self.file_name.clone(),
+            mime_type: self.mime_type.clone(),
+            size: self.size_bytes,
+            checksum: self.checksum.clone(),
+            url: self.download_url(),
+            width: self.width.unwrap_or_default(),
+            height: self.height.unwrap_or_default(),
+            thumbnails: self
+                .thumbnail_infos()
+                .into_iter()
+                .map(|thumbnail| crate::chat::Thumbnail {
+                    size: thumbnail.size,
+                    width: thumbnail.width,
+                    height: thumbnail.height,
+                    url: thumbnail.url,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 上传时的文件名只保留最后一段，去掉控制字符并限制长度
+pub fn sanitize_file_name(file_name: &str) -> String {
+    let name: String = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(255)
+        .collect();
+
+    if name.trim().is_empty() {
+        "file".to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 审计事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Login,
+    LoginFailed,
+    TokenRevoked,
+    ProfileUpdated,
+    RoomCreated,
+    RoomDeleted,
+    MemberJoined,
+    MemberLeft,
+    MemberKicked,
+    UserMuted,
+    UserBanned,
+    MessageDeleted,
+    MessagePinned,
+    MessageUnpinned,
+    HistoryExported,
+    ReportClaimed,
+    ReportResolved,
+    TopicChanged,
+    SlowModeChanged,
+    MessageTtlChanged,
+    RetentionChanged,
+    FilterRulesChanged,
+}
+
+impl AuditAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "login",
+            AuditAction::LoginFailed => "login_failed",
+            AuditAction::TokenRevoked => "token_revoked",
+            AuditAction::ProfileUpdated => "profile_updated",
+            AuditAction::RoomCreated => "room_created",
+            AuditAction::RoomDeleted => "room_deleted",
+            AuditAction::MemberJoined => "member_joined",
+            AuditAction::MemberLeft => "member_left",
+            AuditAction::MemberKicked => "member_kicked",
+            AuditAction::UserMuted => "user_muted",
+            AuditAction::UserBanned => "user_banned",
+            AuditAction::MessageDeleted => "message_deleted",
+            AuditAction::MessagePinned => "message_pinned",
+            AuditAction::MessageUnpinned => "message_unpinned",
+            AuditAction::HistoryExported => "history_exported",
+            AuditAction::ReportClaimed => "report_claimed",
+            AuditAction::ReportResolved => "report_resolved",
+            AuditAction::TopicChanged => "topic_changed",
+            AuditAction::SlowModeChanged => "slow_mode_changed",
+            AuditAction::MessageTtlChanged => "message_ttl_changed",
+            AuditAction::RetentionChanged => "retention_changed",
+            AuditAction::FilterRulesChanged => "filter_rules_changed",
+        }
+    }
+}
+
+/// 审计日志条目，details保存JSON格式的附加信息
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditEntry {
+    pub id: String,
+    pub action: String,
+    pub actor_id: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub details: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            action: action.as_str().to_string(),
+            actor_id: None,
+            target_type: None,
+            target_id: None,
+            ip: None,
+            details: "{}".to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// 操作者，系统自动操作时使用SYSTEM_ACTOR
+    pub fn actor(mut self, actor_id: &str) -> Self {
+        self.actor_id = Some(actor_id.to_string());
+        self
+    }
+
+    /// 操作对象，如user/room/message/report
+    pub fn target(mut self, target_type: &str, target_id: &str) -> Self {
+        self.target_type = Some(target_type.to_string());
+        self.target_id = Some(target_id.to_string());
+        self
+    }
+
+    pub fn ip(mut self, ip: Option<&str>) -> Self {
+        self.ip = ip.map(str::to_string);
+        self
+    }
+
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = details.to_string();
+        self
+    }
+
+    /// 对外输出时把details还原为JSON对象
+    pub fn to_record(&self) -> AuditRecord {
+        AuditRecord {
+            id: self.id.clone(),
+            action: self.action.clone(),
+            actor_id: self.actor_id.clone(),
+            target_type: self.target_type.clone(),
+            target_id: self.target_id.clone(),
+            ip: self.ip.clone(),
+            details: serde_json::from_str(&self.details).unwrap_or(serde_json::Value::Null),
+            created_at: self.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    pub id: String,
+    pub action: String,
+    pub actor_id: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 审计日志查询条件，结果按时间倒序，before_id用于翻页
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub action: Option<String>,
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    /// Unix时间戳（秒）
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// 上一页最后一条的ID
+    pub before_id: Option<String>,
+    pub limit: Option<i64>,
+}
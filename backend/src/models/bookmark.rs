@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 收藏备注的最大长度
+pub const MAX_BOOKMARK_NOTE_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Bookmark {
+    pub id: String,
+    pub user_id: String,
+    pub message_id: String,
+    pub room_id: String,
+    /// 只有收藏者自己可见的备注
+    pub note: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Bookmark {
+    pub fn new(user_id: String, message_id: String, room_id: String, note: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            message_id,
+            room_id,
+            note,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// 收藏消息请求，重复收藏时更新备注
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SaveBookmark {
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// 收藏的消息当前是否可以查看
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookmarkStatus {
+    Available,
+    /// 已经不能访问消息所在的房间，只显示占位
+    Inaccessible,
+    /// 消息已被删除或已过期
+    Deleted,
+}
+
+impl BookmarkStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookmarkStatus::Available => "available",
+            BookmarkStatus::Inaccessible => "inaccessible",
+            BookmarkStatus::Deleted => "deleted",
+        }
+    }
+}
+
+/// 收藏及消息内容，只有status为available时才有message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkInfo {
+    pub id: String,
+    pub message_id: String,
+    pub room_id: String,
+    pub note: Option<String>,
+    pub status: BookmarkStatus,
+    pub message: Option<crate::chat::ChatMessage>,
+    pub created_at: i64,
+}
+
+impl BookmarkInfo {
+    pub fn new(
+        bookmark: Bookmark,
+        status: BookmarkStatus,
+        message: Option<crate::chat::ChatMessage>,
+    ) -> Self {
+        Self {
+            id: bookmark.id,
+            message_id: bookmark.message_id,
+            room_id: bookmark.room_id,
+            note: bookmark.note,
+            status,
+            message,
+            created_at: bookmark.created_at.timestamp(),
+        }
+    }
+
+    pub fn into_grpc(self) -> crate::chat::Bookmark {
+        crate::chat::Bookmark {
+            id: self.id,
+            message_id: self.message_id,
+            room_id: self.room_id,
+            note: self.note.unwrap_or_default(),
+            status: self.status.as_str().to_string(),
+            message: self.message,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// 一页收藏，total为符合条件的收藏总数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkPage {
+    pub bookmarks: Vec<BookmarkInfo>,
+    pub total: i64,
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterKind {
+    /// 敏感词列表，忽略大小写
+    WordList,
+    Regex,
+    /// 最大字符数
+    MaxLength,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// 用`*`替换命中的内容，超长时截断
+    Mask,
+    /// 拒绝发送并返回原因
+    Reject,
+    /// 正常发送，同时记录待审核
+    Flag,
+}
+
+impl FilterKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterKind::WordList => "word_list",
+            FilterKind::Regex => "regex",
+            FilterKind::MaxLength => "max_length",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "word_list" => Some(FilterKind::WordList),
+            "regex" => Some(FilterKind::Regex),
+            "max_length" => Some(FilterKind::MaxLength),
+            _ => None,
+        }
+    }
+}
+
+impl FilterAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterAction::Mask => "mask",
+            FilterAction::Reject => "reject",
+            FilterAction::Flag => "flag",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "mask" => Some(FilterAction::Mask),
+            "reject" => Some(FilterAction::Reject),
+            "flag" => Some(FilterAction::Flag),
+            _ => None,
+        }
+    }
+}
+
+/// 内容过滤规则
+///
+/// 全局规则来自配置文件，房间规则保存在数据库中，只能在全局规则之外追加，不能覆盖同名的全局规则。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub name: String,
+    pub kind: FilterKind,
+    /// word_list为逗号或换行分隔的词，regex为正则表达式，max_length为字符数
+    pub pattern: String,
+    pub action: FilterAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// 设置为false时规则不生效，房间规则不能借此关闭或削弱同名的全局规则
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 数据库中保存的房间过滤规则
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RoomFilterRule {
+    pub id: String,
+    pub room_id: String,
+    pub name: String,
+    pub kind: String,
+    pub pattern: String,
+    pub action: String,
+    pub reason: Option<String>,
+    pub enabled: i8,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl RoomFilterRule {
+    pub fn new(room_id: String, rule: &FilterRule) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            room_id,
+            name: rule.name.clone(),
+            kind: rule.kind.as_str().to_string(),
+            pattern: rule.pattern.clone(),
+            action: rule.action.as_str().to_string(),
+            reason: rule.reason.clone(),
+            enabled: rule.enabled as i8,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// 转换为过滤规则，类型或动作无法识别时返回None
+    pub fn to_rule(&self) -> Option<FilterRule> {
+        Some(FilterRule {
+            name: self.name.clone(),
+            kind: FilterKind::parse(&self.kind)?,
+            pattern: self.pattern.clone(),
+            action: FilterAction::parse(&self.action)?,
+            reason: self.reason.clone(),
+            enabled: self.enabled != 0,
+        })
+    }
+}
+
+/// 被规则标记、等待审核的消息
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MessageFlag {
+    pub id: String,
+    pub message_id: String,
+    pub room_id: String,
+    pub user_id: String,
+    pub rule_name: String,
+    pub reason: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MessageFlag {
+    pub fn new(
+        message_id: String,
+        room_id: String,
+        user_id: String,
+        rule_name: String,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            message_id,
+            room_id,
+            user_id,
+            rule_name,
+            reason,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
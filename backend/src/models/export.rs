@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 导出文件保留天数，过期后删除文件
+pub const EXPORT_FILE_RETENTION_DAYS: i64 = 7;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "jsonl" => Some(ExportFormat::Jsonl),
+            "csv" => Some(ExportFormat::Csv),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "application/x-ndjson; charset=utf-8",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// 导出任务状态：pending -> running -> completed/failed，文件过期后为expired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Expired,
+}
+
+impl ExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStatus::Pending => "pending",
+            ExportStatus::Running => "running",
+            ExportStatus::Completed => "completed",
+            ExportStatus::Failed => "failed",
+            ExportStatus::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExportJob {
+    pub id: String,
+    pub room_id: String,
+    pub requested_by: String,
+    pub format: String,
+    pub from_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub to_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub claimed_by: Option<String>,
+    pub total_messages: i64,
+    pub processed_messages: i64,
+    #[serde(skip_serializing)]
+    pub part_count: i32,
+    pub size_bytes: i64,
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 创建导出任务请求，from/to为Unix时间戳（秒），不填表示不限
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateExport {
+    pub format: String,
+    #[serde(default)]
+    pub from: Option<i64>,
+    #[serde(default)]
+    pub to: Option<i64>,
+}
+
+/// 返回给客户端的导出任务信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportJobInfo {
+    #[serde(flatten)]
+    pub job: ExportJob,
+    /// 已处理的百分比
+    pub progress: u8,
+    /// 导出完成后的下载地址
+    pub download_url: Option<String>,
+}
+
+impl ExportJob {
+    pub fn new(
+        room_id: String,
+        requested_by: String,
+        format: ExportFormat,
+        from_time: Option<chrono::DateTime<chrono::Utc>>,
+        to_time: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            room_id,
+            requested_by,
+            format: format.as_str().to_string(),
+            from_time,
+            to_time,
+            status: ExportStatus::Pending.as_str().to_string(),
+            claimed_by: None,
+            total_messages: 0,
+            processed_messages: 0,
+            part_count: 0,
+            size_bytes: 0,
+            error: None,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+        }
+    }
+
+    pub fn export_format(&self) -> ExportFormat {
+        ExportFormat::parse(&self.format).unwrap_or(ExportFormat::Jsonl)
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.status == ExportStatus::Completed.as_str()
+    }
+
+    /// 第index个文件分片的存储键，从0开始
+    pub fn part_key(&self, index: i32) -> String {
+        format!("exports/{}/part-{:05}", self.id, index)
+    }
+
+    pub fn file_name(&self) -> String {
+        format!(
+            "room-{}-{}.{}",
+            self.room_id,
+            self.created_at.format("%Y%m%d%H%M%S"),
+            self.format
+        )
+    }
+
+    pub fn to_info(&self) -> ExportJobInfo {
+        let progress = if self.is_completed() {
+            100
+        } else if self.total_messages > 0 {
+            (self.processed_messages * 100 / self.total_messages).min(99) as u8
+        } else {
+            0
+        };
+        ExportJobInfo {
+            job: self.clone(),
+            progress,
+            download_url: self
+                .is_completed()
+                .then(|| format!("/api/exports/{}/download", self.id)),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::models::{Attachment, LinkPreview, Message, PollInfo};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_HISTORY_LIMIT: u32 = 50;
+pub const MAX_HISTORY_LIMIT: u32 = 200;
+
+/// 历史消息游标，由消息的 (created_at, id) 组成，对客户端不透明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageCursor {
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub id: String,
+}
+
+impl MessageCursor {
+    pub fn from_message(message: &Message) -> Self {
+        Self {
+            created_at: message.created_at,
+            id: message.id.clone(),
+        }
+    }
+
+    /// 编码为十六进制字符串，HTTP查询参数和gRPC字段使用同一格式
+    pub fn encode(&self) -> String {
+        format!("{}:{}", self.created_at.timestamp_micros(), self.id)
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// 解析游标，格式错误返回None
+    pub fn decode(cursor: &str) -> Option<Self> {
+        if cursor.len() % 2 != 0 {
+            return None;
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(cursor.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let raw = String::from_utf8(bytes).ok()?;
+
+        let (micros, id) = raw.split_once(':')?;
+        if id.is_empty() {
+            return None;
+        }
+        let created_at = chrono::DateTime::from_timestamp_micros(micros.parse().ok()?)?;
+
+        Some(Self {
+            created_at,
+            id: id.to_string(),
+        })
+    }
+}
+
+/// 历史消息的加载位置
+#[derive(Debug, Clone)]
+pub enum HistoryAnchor {
+    /// 最新的消息
+    Latest,
+    /// 游标之前（更早）的消息
+    Before(MessageCursor),
+    /// 游标之后（更新）的消息
+    After(MessageCursor),
+    /// 以游标指向的消息为中心，包含该消息
+    Around(MessageCursor),
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageHistoryQuery {
+    pub anchor: HistoryAnchor,
+    pub limit: u32,
+}
+
+impl MessageHistoryQuery {
+    pub fn new(anchor: HistoryAnchor, limit: Option<u32>) -> Self {
+        let limit = match limit {
+            Some(0) | None => DEFAULT_HISTORY_LIMIT,
+            Some(limit) => limit.min(MAX_HISTORY_LIMIT),
+        };
+        Self { anchor, limit }
+    }
+
+    /// 根据before/after/around参数构造查询，最多只能指定一个，空字符串视为未指定
+    pub fn from_params(
+        before: Option<&str>,
+        after: Option<&str>,
+        around: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Self, String> {
+        let params: Vec<(&str, &str)> = [("before", before), ("after", after), ("around", around)]
+            .into_iter()
+            .filter_map(|(name, value)| value.filter(|v| !v.is_empty()).map(|v| (name, v)))
+            .collect();
+
+        let anchor = match params.as_slice() {
+            [] => HistoryAnchor::Latest,
+            [(name, value)] => {
+                let cursor = MessageCursor::decode(value)
+                    .ok_or_else(|| format!("无效的游标参数: {}", name))?;
+                match *name {
+                    "before" => HistoryAnchor::Before(cursor),
+                    "after" => HistoryAnchor::After(cursor),
+                    _ => HistoryAnchor::Around(cursor),
+                }
+            }
+            _ => return Err("before、after、around只能指定一个".to_string()),
+        };
+
+        Ok(Self::new(anchor, limit))
+    }
+}
+
+/// 一页历史消息，messages按时间从旧到新排列
+#[derive(Debug, Clone)]
+pub struct MessagePage {
+    pub messages: Vec<Message>,
+    pub has_more_before: bool,
+    pub has_more_after: bool,
+    /// 本页消息的附件，由调用方按需加载
+    pub attachments: Vec<Attachment>,
+    /// 本页消息的链接预览，由调用方按需加载
+    pub link_previews: Vec<LinkPreview>,
+    /// 本页投票消息的投票信息，由调用方按需加载
+    pub polls: Vec<PollInfo>,
+}
+
+impl MessagePage {
+    pub fn message_ids(&self) -> Vec<String> {
+        self.messages.iter().map(|m| m.id.clone()).collect()
+    }
+
+    /// 加载更早消息使用的游标（本页最旧的消息）
+    pub fn before_cursor(&self) -> Option<String> {
+        self.messages
+            .first()
+            .map(|m| MessageCursor::from_message(m).encode())
+    }
+
+    /// 加载更新消息使用的游标（本页最新的消息）
+    pub fn after_cursor(&self) -> Option<String> {
+        self.messages
+            .last()
+            .map(|m| MessageCursor::from_message(m).encode())
+    }
+
+    pub fn to_response(&self) -> MessagePageResponse {
+        MessagePageResponse {
+            messages: self
+                .messages
+                .iter()
+                .map(|m| {
+                    m.to_grpc_with_details(&self.attachments, &self.link_previews, &self.polls)
+                })
+                .collect(),
+            before_cursor: self.before_cursor(),
+            after_cursor: self.after_cursor(),
+            has_more_before: self.has_more_before,
+            has_more_after: self.has_more_after,
+        }
+    }
+}
+
+/// HTTP返回的历史消息页
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePageResponse {
+    pub messages: Vec<crate::chat::ChatMessage>,
+    pub before_cursor: Option<String>,
+    pub after_cursor: Option<String>,
+    pub has_more_before: bool,
+    pub has_more_after: bool,
+}
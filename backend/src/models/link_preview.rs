@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 每条消息最多生成预览的链接数
+pub const MAX_PREVIEWS_PER_MESSAGE: usize = 3;
+
+const MAX_TITLE_CHARS: usize = 300;
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LinkPreview {
+    pub id: String,
+    pub message_id: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 返回给客户端的链接预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPreviewInfo {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+}
+
+impl LinkPreview {
+    pub fn new(message_id: String, url: String, info: LinkPreviewInfo) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            message_id,
+            url,
+            title: info.title.map(|t| truncate_chars(&t, MAX_TITLE_CHARS)),
+            description: info
+                .description
+                .map(|d| truncate_chars(&d, MAX_DESCRIPTION_CHARS)),
+            image_url: info.image_url,
+            site_name: info.site_name.map(|s| truncate_chars(&s, MAX_TITLE_CHARS)),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// 没有任何可展示内容的预览不保存
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.image_url.is_none()
+    }
+
+    pub fn to_info(&self) -> LinkPreviewInfo {
+        LinkPreviewInfo {
+            url: self.url.clone(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            image_url: self.image_url.clone(),
+            site_name: self.site_name.clone(),
+        }
+    }
+
+    pub fn to_grpc(&self) -> crate::chat::LinkPreview {
+        self.to_info().into_grpc()
+    }
+}
+
+impl LinkPreviewInfo {
+    pub fn into_grpc(self) -> crate::chat::LinkPreview {
+        crate::chat::LinkPreview {
+            url: self.url,
+            title: self.title.unwrap_or_default(),
+            description: self.description.unwrap_or_default(),
+            image_url: self.image_url.unwrap_or_default(),
+            site_name: self.site_name.unwrap_or_default(),
+        }
+    }
+}
+
+/// 提取消息中的http(s)链接，去重后最多返回`MAX_PREVIEWS_PER_MESSAGE`个
+pub fn extract_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+
+    for token in content.split_whitespace() {
+        let Some(start) = token.find("http://").or_else(|| token.find("https://")) else {
+            continue;
+        };
+        // 去掉结尾的标点和括号
+        let url = token[start..]
+            .trim_end_matches(|c: char| ".,;:!?)]}>'\"，。！？）".contains(c))
+            .to_string();
+
+        if reqwest::Url::parse(&url).is_ok() && !urls.contains(&url) {
+            urls.push(url);
+        }
+        if urls.len() >= MAX_PREVIEWS_PER_MESSAGE {
+            break;
+        }
+    }
+
+    urls
+}
+
+fn truncate_chars(value: &str, max_chars: usize) -> String {
+    let value = value.trim();
+    match value.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &value[..index]),
+        None => value.to_string(),
+    }
+}
@@ -0,0 +1,61 @@
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, html};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// 渲染消息中支持的Markdown子集：加粗、行内代码、链接和代码块
+///
+/// 其余语法（标题、列表、图片、原始HTML等）只保留文字内容，
+/// 渲染结果再经过白名单清洗，客户端可以直接作为HTML展示。
+pub fn render_markdown(content: &str) -> String {
+    let events = Parser::new_ext(content, Options::empty()).filter_map(restrict_event);
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events);
+
+    sanitizer().clean(&rendered).to_string()
+}
+
+fn restrict_event(event: Event<'_>) -> Option<Event<'_>> {
+    match event {
+        Event::Start(tag) => restrict_tag(tag).map(Event::Start),
+        Event::End(tag) => match tag {
+            // 不支持的块级元素结束时换行，保留原有的分行
+            Tag::Heading(..) | Tag::Item | Tag::BlockQuote => Some(Event::HardBreak),
+            tag => restrict_tag(tag).map(Event::End),
+        },
+        Event::Text(_) | Event::Code(_) | Event::SoftBreak | Event::HardBreak => Some(event),
+        // 原始HTML按普通文本输出，由push_html转义
+        Event::Html(raw) => Some(Event::Text(raw)),
+        Event::Rule => Some(Event::Text(CowStr::Borrowed("---"))),
+        _ => None,
+    }
+}
+
+fn restrict_tag(tag: Tag<'_>) -> Option<Tag<'_>> {
+    match tag {
+        Tag::Paragraph | Tag::Strong => Some(tag),
+        // 不保留代码块的语言标记，避免输出class属性
+        Tag::CodeBlock(_) => Some(Tag::CodeBlock(CodeBlockKind::Indented)),
+        Tag::Link(_, ref dest, _) if is_safe_link(dest) => Some(tag),
+        _ => None,
+    }
+}
+
+fn is_safe_link(dest: &str) -> bool {
+    let dest = dest.trim().to_ascii_lowercase();
+    dest.starts_with("http://") || dest.starts_with("https://") || dest.starts_with("mailto:")
+}
+
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::empty();
+        builder
+            .tags(HashSet::from(["p", "br", "strong", "code", "pre", "a"]))
+            .tag_attributes(HashMap::from([("a", HashSet::from(["href"]))]))
+            .url_schemes(HashSet::from(["http", "https", "mailto"]))
+            .url_relative(ammonia::UrlRelative::Deny)
+            .link_rel(Some("noopener noreferrer nofollow"));
+        builder
+    })
+}
@@ -1,3 +1,4 @@
+use crate::models::{MessageReference, render_markdown};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -8,9 +9,26 @@ pub struct Message {
     pub user_id: String,
     pub username: String,
     pub content: String,
+    /// 服务端渲染并清洗后的HTML，旧消息可能为空
+    pub content_html: Option<String>,
     pub room_id: String,
     pub message_type: MessageType,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// 过期时间，过期后不再返回并由后台任务删除
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 转发或引用的原消息快照（JSON），见`MessageReference`
+    pub reference: Option<String>,
+}
+
+/// 消息有效期上限（秒），单条消息和房间默认值都不能超过
+pub const MAX_MESSAGE_TTL_SECONDS: u32 = 30 * 24 * 3600;
+
+/// 检查消息有效期，0表示不过期或使用房间默认值
+pub fn validate_message_ttl(ttl_seconds: u32) -> Result<(), String> {
+    if ttl_seconds > MAX_MESSAGE_TTL_SECONDS {
+        return Err(format!("消息有效期不能超过{}秒", MAX_MESSAGE_TTL_SECONDS));
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type)]
@@ -20,6 +38,8 @@ pub enum MessageType {
     Image,
     File,
     System,
+    /// 投票，内容为投票问题
+    Poll,
 }
 
 impl std::fmt::Display for MessageType {
@@ -29,6 +49,21 @@ impl std::fmt::Display for MessageType {
             MessageType::Image => write!(f, "image"),
             MessageType::File => write!(f, "file"),
             MessageType::System => write!(f, "system"),
+            MessageType::Poll => write!(f, "poll"),
+        }
+    }
+}
+
+impl MessageType {
+    /// 严格解析消息类型，未知类型返回None
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(MessageType::Text),
+            "image" => Some(MessageType::Image),
+            "file" => Some(MessageType::File),
+            "system" => Some(MessageType::System),
+            "poll" => Some(MessageType::Poll),
+            _ => None,
         }
     }
 }
@@ -45,11 +80,42 @@ impl Message {
             id: Uuid::new_v4().to_string(),
             user_id,
             username,
+            content_html: Some(render_markdown(&content)),
             content,
             room_id,
             message_type,
             created_at: chrono::Utc::now(),
+            expires_at: None,
+            reference: None,
+        }
+    }
+
+    /// 附带转发或引用的原消息快照
+    pub fn with_reference(mut self, reference: &MessageReference) -> Self {
+        self.reference = Some(reference.to_json());
+        self
+    }
+
+    /// 解析原消息快照，格式不正确时忽略
+    pub fn reference(&self) -> Option<MessageReference> {
+        self.reference
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+    }
+
+    /// 设置单条消息的有效期，为0时保存时使用房间默认值
+    pub fn with_ttl(mut self, ttl_seconds: u32) -> Self {
+        if ttl_seconds > 0 {
+            self.expires_at = Some(self.created_at + chrono::Duration::seconds(ttl_seconds as i64));
         }
+        self
+    }
+
+    /// 渲染后的HTML，旧消息没有保存时现场渲染
+    pub fn rendered_content(&self) -> String {
+        self.content_html
+            .clone()
+            .unwrap_or_else(|| render_markdown(&self.content))
     }
 
     pub fn to_grpc(&self) -> crate::chat::ChatMessage {
@@ -58,11 +124,42 @@ impl Message {
             user_id: self.user_id.clone(),
             username: self.username.clone(),
             content: self.content.clone(),
+            content_html: self.rendered_content(),
             room_id: self.room_id.clone(),
             message_type: self.message_type.clone() as i32,
             timestamp: self.created_at.timestamp(),
+            attachments: Vec::new(),
+            link_previews: Vec::new(),
+            poll: None,
+            expires_at: self.expires_at.map(|t| t.timestamp()).unwrap_or_default(),
+            reference: self.reference().map(|r| r.to_grpc()),
         }
     }
+
+    /// 转换为gRPC消息并带上附件、链接预览和投票
+    pub fn to_grpc_with_details(
+        &self,
+        attachments: &[crate::models::Attachment],
+        link_previews: &[crate::models::LinkPreview],
+        polls: &[crate::models::PollInfo],
+    ) -> crate::chat::ChatMessage {
+        let mut message = self.to_grpc();
+        message.attachments = attachments
+            .iter()
+            .filter(|a| a.message_id.as_deref() == Some(self.id.as_str()))
+            .map(|a| a.to_grpc())
+            .collect();
+        message.link_previews = link_previews
+            .iter()
+            .filter(|p| p.message_id == self.id)
+            .map(|p| p.to_grpc())
+            .collect();
+        message.poll = polls
+            .iter()
+            .find(|p| p.message_id == self.id)
+            .map(|p| p.clone().into_grpc());
+        message
+    }
 }
 
 impl From<i32> for MessageType {
@@ -72,6 +169,7 @@ impl From<i32> for MessageType {
             1 => MessageType::Image,
             2 => MessageType::File,
             3 => MessageType::System,
+            4 => MessageType::Poll,
             _ => MessageType::Text,
         }
     }
@@ -85,6 +183,7 @@ impl From<Option<String>> for MessageType {
                 "image" => MessageType::Image,
                 "file" => MessageType::File,
                 "system" => MessageType::System,
+                "poll" => MessageType::Poll,
                 _ => MessageType::Text,
             },
             None => MessageType::Text,
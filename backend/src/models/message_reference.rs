@@ -0,0 +1,106 @@
+use crate::models::{Message, MessageType};
+use serde::{Deserialize, Serialize};
+
+/// 引用快照中最多保留的内容长度（字符）
+pub const MAX_QUOTE_SNAPSHOT_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    /// 转发，新消息内容与原消息相同，快照中不再保存内容
+    Forward,
+    /// 引用回复，新消息内容是回复，原消息只作为快照附带
+    Quote,
+}
+
+impl ReferenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferenceKind::Forward => "forward",
+            ReferenceKind::Quote => "quote",
+        }
+    }
+}
+
+/// 转发或引用的原消息快照，created_at为原消息的Unix时间戳（秒）
+///
+/// 保存在消息的reference列中，原消息被删除或过期后仍能显示来源。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageReference {
+    pub kind: ReferenceKind,
+    pub message_id: String,
+    pub room_id: String,
+    pub room_name: String,
+    pub user_id: String,
+    pub username: String,
+    pub content: String,
+    pub message_type: String,
+    pub created_at: i64,
+}
+
+/// 转发消息的请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct ForwardMessage {
+    pub room_id: String,
+}
+
+/// 引用回复的请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuoteMessage {
+    pub room_id: String,
+    pub content: String,
+}
+
+impl MessageReference {
+    /// 根据原消息生成快照，引用时内容过长会被截断
+    pub fn new(kind: ReferenceKind, message: &Message, room_name: &str) -> Self {
+        let content = match kind {
+            ReferenceKind::Forward => String::new(),
+            ReferenceKind::Quote => truncate(&message.content, MAX_QUOTE_SNAPSHOT_CHARS),
+        };
+        Self {
+            kind,
+            message_id: message.id.clone(),
+            room_id: message.room_id.clone(),
+            room_name: room_name.to_string(),
+            user_id: message.user_id.clone(),
+            username: message.username.clone(),
+            content,
+            message_type: message.message_type.to_string(),
+            created_at: message.created_at.timestamp(),
+        }
+    }
+
+    /// 原消息本身是转发来的时，保留最初的来源
+    pub fn forward_of(message: &Message, room_name: &str) -> Self {
+        match message.reference() {
+            Some(reference) if reference.kind == ReferenceKind::Forward => reference,
+            _ => Self::new(ReferenceKind::Forward, message, room_name),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn to_grpc(&self) -> crate::chat::MessageReference {
+        crate::chat::MessageReference {
+            kind: self.kind.as_str().to_string(),
+            message_id: self.message_id.clone(),
+            room_id: self.room_id.clone(),
+            room_name: self.room_name.clone(),
+            user_id: self.user_id.clone(),
+            username: self.username.clone(),
+            content: self.content.clone(),
+            message_type: MessageType::from(Some(self.message_type.clone())) as i32,
+            timestamp: self.created_at,
+        }
+    }
+}
+
+fn truncate(content: &str, max_chars: usize) -> String {
+    match content.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &content[..index]),
+        None => content.to_string(),
+    }
+}
@@ -1,7 +1,39 @@
+pub mod attachment;
+pub mod audit;
+pub mod bookmark;
+pub mod content_filter;
+pub mod export;
+pub mod history;
+pub mod link_preview;
+pub mod markdown;
 pub mod message;
+pub mod message_reference;
+pub mod moderation;
+pub mod pin;
+pub mod poll;
+pub mod report;
+pub mod retention;
 pub mod room;
+pub mod scheduled_message;
+pub mod search;
 pub mod user;
 
+pub use attachment::*;
+pub use audit::*;
+pub use bookmark::*;
+pub use content_filter::*;
+pub use export::*;
+pub use history::*;
+pub use link_preview::*;
+pub use markdown::*;
 pub use message::*;
+pub use message_reference::*;
+pub use moderation::*;
+pub use pin::*;
+pub use poll::*;
+pub use report::*;
+pub use retention::*;
 pub use room::*;
+pub use scheduled_message::*;
+pub use search::*;
 pub use user::*;
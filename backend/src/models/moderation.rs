@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 系统自动禁言时使用的操作者ID
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// 版主手动禁言的最长时长（秒）
+pub const MAX_MUTE_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// 禁言来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MuteSource {
+    /// 反垃圾检测自动禁言
+    Spam,
+    /// 版主手动禁言
+    Moderator,
+}
+
+impl MuteSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MuteSource::Spam => "spam",
+            MuteSource::Moderator => "moderator",
+        }
+    }
+}
+
+/// 禁言记录，room_id为空表示全局禁言
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserMute {
+    pub id: String,
+    pub user_id: String,
+    pub room_id: Option<String>,
+    pub reason: String,
+    pub source: String,
+    pub muted_by: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserMute {
+    pub fn new(
+        user_id: String,
+        room_id: Option<String>,
+        reason: String,
+        source: MuteSource,
+        muted_by: String,
+        duration: chrono::Duration,
+    ) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            room_id,
+            reason,
+            source: source.as_str().to_string(),
+            muted_by,
+            expires_at: now + duration,
+            created_at: now,
+        }
+    }
+}
+
+/// 反垃圾规则的一次触发记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SpamDecision {
+    pub id: String,
+    pub user_id: String,
+    pub room_id: String,
+    pub rule: String,
+    /// 实际观测值和触发阈值
+    pub observed: i32,
+    pub threshold: i32,
+    pub action: String,
+    pub mute_seconds: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl SpamDecision {
+    pub fn new(
+        user_id: String,
+        room_id: String,
+        rule: &str,
+        observed: i32,
+        threshold: i32,
+        action: &str,
+        mute_seconds: i32,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            room_id,
+            rule: rule.to_string(),
+            observed,
+            threshold,
+            action: action.to_string(),
+            mute_seconds,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 每个房间最多置顶的消息数
+pub const MAX_PINS_PER_ROOM: i64 = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MessagePin {
+    pub message_id: String,
+    pub room_id: String,
+    pub pinned_by: String,
+    pub pinned_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MessagePin {
+    pub fn new(message_id: String, room_id: String, pinned_by: String) -> Self {
+        Self {
+            message_id,
+            room_id,
+            pinned_by,
+            pinned_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// 置顶结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinOutcome {
+    Pinned,
+    AlreadyPinned,
+    LimitReached,
+}
+
+/// 置顶消息及完整的消息内容，pinned_at为Unix时间戳（秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinInfo {
+    pub message: crate::chat::ChatMessage,
+    pub pinned_by: String,
+    pub pinned_at: i64,
+}
+
+impl PinInfo {
+    pub fn new(pin: &MessagePin, message: crate::chat::ChatMessage) -> Self {
+        Self {
+            message,
+            pinned_by: pin.pinned_by.clone(),
+            pinned_at: pin.pinned_at.timestamp(),
+        }
+    }
+
+    pub fn into_grpc(self) -> crate::chat::PinnedMessage {
+        crate::chat::PinnedMessage {
+            message: Some(self.message),
+            pinned_by: self.pinned_by,
+            pinned_at: self.pinned_at,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 投票问题和选项的最大长度
+pub const MAX_POLL_QUESTION_LENGTH: usize = 300;
+pub const MAX_POLL_OPTION_LENGTH: usize = 200;
+/// 选项数量范围
+pub const MIN_POLL_OPTIONS: usize = 2;
+pub const MAX_POLL_OPTIONS: usize = 10;
+/// 截止时间最多设置在多少天之后
+pub const MAX_POLL_DURATION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Poll {
+    pub id: String,
+    pub message_id: String,
+    pub room_id: String,
+    pub created_by: String,
+    pub question: String,
+    pub multi_choice: bool,
+    pub anonymous: bool,
+    pub closes_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub closed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PollOption {
+    pub id: String,
+    pub poll_id: String,
+    pub position: i32,
+    pub text: String,
+}
+
+/// 选票中选中的一个选项
+#[derive(Debug, Clone, FromRow)]
+pub struct PollVote {
+    pub poll_id: String,
+    pub user_id: String,
+    pub option_id: String,
+}
+
+/// 投票结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteOutcome {
+    Recorded,
+    AlreadyVoted,
+    Closed,
+}
+
+/// 创建投票请求，closes_at为Unix时间戳（秒）
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatePoll {
+    pub question: String,
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub multi_choice: bool,
+    #[serde(default)]
+    pub anonymous: bool,
+    #[serde(default)]
+    pub closes_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VotePoll {
+    pub option_ids: Vec<String>,
+}
+
+/// 返回给客户端的投票及当前票数，匿名投票不包含投票人
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollInfo {
+    pub poll_id: String,
+    pub message_id: String,
+    pub question: String,
+    pub multi_choice: bool,
+    pub anonymous: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closes_at: Option<i64>,
+    pub closed: bool,
+    /// 参与投票的人数，多选时不等于票数之和
+    pub total_voters: i64,
+    pub options: Vec<PollOptionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollOptionInfo {
+    pub id: String,
+    pub text: String,
+    pub votes: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub voter_ids: Vec<String>,
+}
+
+impl Poll {
+    pub fn new(
+        message_id: String,
+        room_id: String,
+        created_by: String,
+        question: String,
+        multi_choice: bool,
+        anonymous: bool,
+        closes_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            message_id,
+            room_id,
+            created_by,
+            question,
+            multi_choice,
+            anonymous,
+            closes_at,
+            closed_at: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// 已手动结束或已过截止时间
+    pub fn is_closed(&self) -> bool {
+        self.closed_at.is_some()
+            || self
+                .closes_at
+                .map(|closes_at| closes_at <= chrono::Utc::now())
+                .unwrap_or(false)
+    }
+
+    /// 汇总选项和选票，options和votes可以包含其他投票的记录
+    pub fn to_info(&self, options: &[PollOption], votes: &[PollVote]) -> PollInfo {
+        let votes: Vec<&PollVote> = votes.iter().filter(|v| v.poll_id == self.id).collect();
+
+        let mut voters_by_option: HashMap<&str, Vec<String>> = HashMap::new();
+        for vote in &votes {
+            voters_by_option
+                .entry(vote.option_id.as_str())
+                .or_default()
+                .push(vote.user_id.clone());
+        }
+        let mut voters: Vec<&str> = votes.iter().map(|v| v.user_id.as_str()).collect();
+        voters.sort_unstable();
+        voters.dedup();
+
+        let mut options: Vec<&PollOption> =
+            options.iter().filter(|o| o.poll_id == self.id).collect();
+        options.sort_by_key(|o| o.position);
+
+        PollInfo {
+            poll_id: self.id.clone(),
+            message_id: self.message_id.clone(),
+            question: self.question.clone(),
+            multi_choice: self.multi_choice,
+            anonymous: self.anonymous,
+            closes_at: self.closes_at.map(|t| t.timestamp()),
+            closed: self.is_closed(),
+            total_voters: voters.len() as i64,
+            options: options
+                .into_iter()
+                .map(|option| {
+                    let voter_ids = voters_by_option
+                        .remove(option.id.as_str())
+                        .unwrap_or_default();
+                    PollOptionInfo {
+                        id: option.id.clone(),
+                        text: option.text.clone(),
+                        votes: voter_ids.len() as i64,
+                        voter_ids: if self.anonymous {
+                            Vec::new()
+                        } else {
+                            voter_ids
+                        },
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PollOption {
+    pub fn new(poll_id: String, position: i32, text: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            poll_id,
+            position,
+            text,
+        }
+    }
+}
+
+impl PollInfo {
+    pub fn into_grpc(self) -> crate::chat::Poll {
+        crate::chat::Poll {
+            id: self.poll_id,
+            message_id: self.message_id,
+            question: self.question,
+            multi_choice: self.multi_choice,
+            anonymous: self.anonymous,
+            closes_at: self.closes_at.unwrap_or_default(),
+            closed: self.closed,
+            total_voters: self.total_voters,
+            options: self
+                .options
+                .into_iter()
+                .map(|option| crate::chat::PollOption {
+                    id: option.id,
+                    text: option.text,
+                    votes: option.votes,
+                    voter_ids: option.voter_ids,
+                })
+                .collect(),
+        }
+    }
+}
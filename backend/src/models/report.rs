@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 举报对象类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportTargetType {
+    Message,
+    User,
+}
+
+impl ReportTargetType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "message" => Some(ReportTargetType::Message),
+            "user" => Some(ReportTargetType::User),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportTargetType::Message => "message",
+            ReportTargetType::User => "user",
+        }
+    }
+}
+
+/// 举报处理状态：open -> claimed -> resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    Open,
+    Claimed,
+    Resolved,
+}
+
+impl ReportStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(ReportStatus::Open),
+            "claimed" => Some(ReportStatus::Claimed),
+            "resolved" => Some(ReportStatus::Resolved),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportStatus::Open => "open",
+            ReportStatus::Claimed => "claimed",
+            ReportStatus::Resolved => "resolved",
+        }
+    }
+}
+
+/// 举报处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportResolution {
+    /// 驳回，不做处理
+    Dismiss,
+    /// 删除被举报的消息
+    DeleteMessage,
+    /// 禁言被举报用户
+    Mute,
+    /// 封禁被举报用户
+    Ban,
+}
+
+impl ReportResolution {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dismiss" => Some(ReportResolution::Dismiss),
+            "delete_message" => Some(ReportResolution::DeleteMessage),
+            "mute" => Some(ReportResolution::Mute),
+            "ban" => Some(ReportResolution::Ban),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportResolution::Dismiss => "dismiss",
+            ReportResolution::DeleteMessage => "delete_message",
+            ReportResolution::Mute => "mute",
+            ReportResolution::Ban => "ban",
+        }
+    }
+
+    /// 通知举报人时使用的描述
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ReportResolution::Dismiss => "经审核未发现违规",
+            ReportResolution::DeleteMessage => "相关消息已被删除",
+            ReportResolution::Mute => "被举报用户已被禁言",
+            ReportResolution::Ban => "被举报用户已被封禁",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Report {
+    pub id: String,
+    pub reporter_id: String,
+    pub target_type: String,
+    pub target_user_id: String,
+    pub message_id: Option<String>,
+    pub room_id: Option<String>,
+    pub reason: String,
+    pub status: String,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub resolution: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Report {
+    pub fn new(
+        reporter_id: String,
+        target_type: ReportTargetType,
+        target_user_id: String,
+        message_id: Option<String>,
+        room_id: Option<String>,
+        reason: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            reporter_id,
+            target_type: target_type.as_str().to_string(),
+            target_user_id,
+            message_id,
+            room_id,
+            reason,
+            status: ReportStatus::Open.as_str().to_string(),
+            claimed_by: None,
+            claimed_at: None,
+            resolution: None,
+            resolution_note: None,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn to_grpc(&self) -> crate::chat::Report {
+        crate::chat::Report {
+            id: self.id.clone(),
+            reporter_id: self.reporter_id.clone(),
+            target_type: self.target_type.clone(),
+            target_user_id: self.target_user_id.clone(),
+            message_id: self.message_id.clone().unwrap_or_default(),
+            room_id: self.room_id.clone().unwrap_or_default(),
+            reason: self.reason.clone(),
+            status: self.status.clone(),
+            claimed_by: self.claimed_by.clone().unwrap_or_default(),
+            resolution: self.resolution.clone().unwrap_or_default(),
+            resolution_note: self.resolution_note.clone().unwrap_or_default(),
+            resolved_by: self.resolved_by.clone().unwrap_or_default(),
+            created_at: self.created_at.timestamp(),
+            resolved_at: self.resolved_at.map(|t| t.timestamp()).unwrap_or_default(),
+        }
+    }
+}
+
+/// 提交举报，举报消息时填message_id，举报用户时填user_id
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmitReport {
+    pub target_type: String,
+    #[serde(default)]
+    pub message_id: Option<String>,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub reason: String,
+}
+
+/// 处理举报
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolveReport {
+    pub action: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 禁言时长，只在action为mute时使用
+    #[serde(default)]
+    pub mute_seconds: Option<i64>,
+}
@@ -0,0 +1,116 @@
+use crate::models::Room;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 保留天数上限
+pub const MAX_RETENTION_DAYS: u32 = 3650;
+
+/// 消息保留策略配置，房间没有单独设置时使用全局默认值
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    /// 全局默认保留天数，None表示永久保留
+    pub default_days: Option<u32>,
+    /// 两轮清理的间隔
+    pub interval: Duration,
+    /// 每批删除的消息数
+    pub batch_size: i64,
+    /// 两批之间的停顿，避免长时间占用数据库
+    pub batch_pause: Duration,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            default_days: None,
+            interval: Duration::from_secs(3600),
+            batch_size: 200,
+            batch_pause: Duration::from_millis(200),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// 从环境变量读取配置：MESSAGE_RETENTION_DAYS、RETENTION_INTERVAL_SECS、
+    /// RETENTION_BATCH_SIZE、RETENTION_BATCH_PAUSE_MS
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(days) = std::env::var("MESSAGE_RETENTION_DAYS") {
+            match days.trim().parse::<u32>() {
+                Ok(0) => config.default_days = None,
+                Ok(days) if days <= MAX_RETENTION_DAYS => config.default_days = Some(days),
+                _ => eprintln!("忽略无效的MESSAGE_RETENTION_DAYS: {}", days),
+            }
+        }
+
+        if let Ok(secs) = std::env::var("RETENTION_INTERVAL_SECS") {
+            match secs.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => config.interval = Duration::from_secs(secs),
+                _ => eprintln!("忽略无效的RETENTION_INTERVAL_SECS: {}", secs),
+            }
+        }
+
+        if let Ok(size) = std::env::var("RETENTION_BATCH_SIZE") {
+            match size.trim().parse::<i64>() {
+                Ok(size) if size > 0 => config.batch_size = size,
+                _ => eprintln!("忽略无效的RETENTION_BATCH_SIZE: {}", size),
+            }
+        }
+
+        if let Ok(millis) = std::env::var("RETENTION_BATCH_PAUSE_MS") {
+            match millis.trim().parse::<u64>() {
+                Ok(millis) => config.batch_pause = Duration::from_millis(millis),
+                _ => eprintln!("忽略无效的RETENTION_BATCH_PAUSE_MS: {}", millis),
+            }
+        }
+
+        config
+    }
+
+    /// 房间实际使用的保留天数和来源，永久保留时返回None
+    pub fn policy_for(&self, room: &Room) -> Option<(u32, RetentionSource)> {
+        match room.retention_days {
+            Some(days) if days > 0 => Some((days as u32, RetentionSource::Room)),
+            Some(_) => None,
+            None => self
+                .default_days
+                .map(|days| (days, RetentionSource::Default)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionSource {
+    /// 房间单独设置
+    Room,
+    /// 全局默认值
+    Default,
+}
+
+/// 房间保留策略设置，days为空表示使用全局默认值，0表示永久保留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetRetention {
+    #[serde(default)]
+    pub days: Option<u32>,
+}
+
+/// 早于截止时间的数据量
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionStats {
+    pub messages: i64,
+    pub attachments: i64,
+    pub attachment_bytes: i64,
+}
+
+/// 保留策略预演结果，列出每个房间按当前策略会删除的数据
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    pub room_id: String,
+    pub room_name: String,
+    pub retention_days: u32,
+    pub source: RetentionSource,
+    pub cutoff: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub stats: RetentionStats,
+}
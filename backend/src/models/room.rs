@@ -11,8 +11,17 @@ pub struct Room {
     pub created_by: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// 慢速模式间隔（秒），0表示关闭
+    pub slow_mode_seconds: i32,
+    /// 新消息默认有效期（秒），0表示不过期
+    pub message_ttl_seconds: i32,
+    /// 消息保留天数，为空时使用全局默认值，0表示永久保留
+    pub retention_days: Option<i32>,
 }
 
+/// 慢速模式最长间隔
+pub const MAX_SLOW_MODE_SECONDS: u32 = 3600;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateRoom {
     pub name: String,
@@ -36,6 +45,13 @@ impl Room {
             created_by,
             created_at: now,
             updated_at: now,
+            slow_mode_seconds: 0,
+            message_ttl_seconds: 0,
+            retention_days: None,
         }
     }
+
+    pub fn is_public_room(&self) -> bool {
+        self.is_public == Some(1)
+    }
 }
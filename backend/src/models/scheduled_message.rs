@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// 定时消息最多提前多少天安排
+pub const MAX_SCHEDULE_AHEAD_DAYS: i64 = 365;
+/// 每个用户最多同时存在的待发送定时消息数
+pub const MAX_PENDING_SCHEDULED_MESSAGES: i64 = 100;
+
+/// 定时消息状态：pending -> sending -> sent/failed，pending时可以取消
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledStatus {
+    Pending,
+    Sending,
+    Sent,
+    Failed,
+    Cancelled,
+}
+
+impl ScheduledStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(ScheduledStatus::Pending),
+            "sending" => Some(ScheduledStatus::Sending),
+            "sent" => Some(ScheduledStatus::Sent),
+            "failed" => Some(ScheduledStatus::Failed),
+            "cancelled" => Some(ScheduledStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduledStatus::Pending => "pending",
+            ScheduledStatus::Sending => "sending",
+            ScheduledStatus::Sent => "sent",
+            ScheduledStatus::Failed => "failed",
+            ScheduledStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScheduledMessage {
+    pub id: String,
+    /// 发送后消息的ID，创建时生成
+    pub message_id: String,
+    pub user_id: String,
+    pub room_id: String,
+    pub content: String,
+    pub send_at: chrono::DateTime<chrono::Utc>,
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub claimed_by: Option<String>,
+    #[serde(skip_serializing)]
+    pub claimed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 发送失败的原因
+    pub error: Option<String>,
+    pub sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 创建定时消息请求，send_at为Unix时间戳（秒）
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleMessage {
+    pub content: String,
+    pub send_at: i64,
+}
+
+/// 修改定时消息，只修改提供的字段
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateScheduledMessage {
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub send_at: Option<i64>,
+}
+
+impl ScheduledMessage {
+    pub fn new(
+        user_id: String,
+        room_id: String,
+        content: String,
+        send_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            message_id: Uuid::new_v4().to_string(),
+            user_id,
+            room_id,
+            content,
+            send_at,
+            status: ScheduledStatus::Pending.as_str().to_string(),
+            claimed_by: None,
+            claimed_at: None,
+            error: None,
+            sent_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn to_grpc(&self) -> crate::chat::ScheduledMessage {
+        crate::chat::ScheduledMessage {
+            id: self.id.clone(),
+            message_id: self.message_id.clone(),
+            user_id: self.user_id.clone(),
+            room_id: self.room_id.clone(),
+            content: self.content.clone(),
+            send_at: self.send_at.timestamp(),
+            status: self.status.clone(),
+            error: self.error.clone().unwrap_or_default(),
+            sent_at: self.sent_at.map(|t| t.timestamp()).unwrap_or_default(),
+            created_at: self.created_at.timestamp(),
+        }
+    }
+}
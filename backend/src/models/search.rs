@@ -0,0 +1,156 @@
+use crate::models::{Message, MessageType};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SEARCH_PAGE_SIZE: u32 = 20;
+pub const MAX_SEARCH_PAGE_SIZE: u32 = 100;
+
+/// 摘要中匹配位置之前保留的字符数
+const SNIPPET_CONTEXT_CHARS: usize = 30;
+/// 摘要的最大字符数
+const SNIPPET_MAX_CHARS: usize = 120;
+
+/// 消息搜索条件，room_ids必须已经按用户可读权限过滤
+#[derive(Debug, Clone)]
+pub struct MessageSearchQuery {
+    pub text: String,
+    pub room_ids: Vec<String>,
+    pub user_id: Option<String>,
+    pub message_type: Option<MessageType>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl MessageSearchQuery {
+    pub fn new(text: String, room_ids: Vec<String>) -> Self {
+        Self {
+            text,
+            room_ids,
+            user_id: None,
+            message_type: None,
+            from: None,
+            to: None,
+            page: 1,
+            page_size: DEFAULT_SEARCH_PAGE_SIZE,
+        }
+    }
+
+    /// 设置分页参数，页码从1开始，每页数量限制在合理范围内
+    pub fn with_page(mut self, page: u32, page_size: u32) -> Self {
+        self.page = page.max(1);
+        self.page_size = if page_size == 0 {
+            DEFAULT_SEARCH_PAGE_SIZE
+        } else {
+            page_size.min(MAX_SEARCH_PAGE_SIZE)
+        };
+        self
+    }
+
+    pub fn offset(&self) -> u32 {
+        (self.page - 1) * self.page_size
+    }
+
+    /// 拆分出的搜索词，去掉全文检索的特殊运算符
+    pub fn terms(&self) -> Vec<String> {
+        self.text
+            .split_whitespace()
+            .map(|term| {
+                term.chars()
+                    .filter(|c| !"+-<>()~*\"@".contains(*c))
+                    .collect::<String>()
+            })
+            .filter(|term| !term.is_empty())
+            .collect()
+    }
+
+    /// 生成BOOLEAN MODE的检索表达式，要求所有搜索词都出现
+    pub fn boolean_expression(&self) -> String {
+        self.terms()
+            .iter()
+            .map(|term| format!("+\"{}\"", term))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// 单条搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSearchHit {
+    pub message: Message,
+    /// 截取后的内容摘要，已转义HTML，匹配部分用<mark>标记
+    pub snippet: String,
+}
+
+/// 一页搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSearchPage {
+    pub hits: Vec<MessageSearchHit>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// 生成高亮摘要：围绕第一个匹配位置截取内容，并用<mark>标记所有匹配
+pub fn highlight_snippet(content: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let terms: Vec<Vec<char>> = terms
+        .iter()
+        .map(|term| term.chars().collect::<Vec<char>>())
+        .filter(|term| !term.is_empty())
+        .collect();
+
+    let first_match = (0..chars.len())
+        .find(|&i| terms.iter().any(|term| matches_at(&chars, i, term)))
+        .unwrap_or(0);
+    let start = first_match.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (start + SNIPPET_MAX_CHARS).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+
+    let mut i = start;
+    while i < end {
+        if let Some(term) = terms
+            .iter()
+            .find(|term| i + term.len() <= end && matches_at(&chars, i, term))
+        {
+            snippet.push_str("<mark>");
+            for c in &chars[i..i + term.len()] {
+                push_escaped(&mut snippet, *c);
+            }
+            snippet.push_str("</mark>");
+            i += term.len();
+        } else {
+            push_escaped(&mut snippet, chars[i]);
+            i += 1;
+        }
+    }
+
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// 忽略大小写判断内容在指定位置是否匹配搜索词
+fn matches_at(chars: &[char], index: usize, term: &[char]) -> bool {
+    index + term.len() <= chars.len()
+        && chars[index..index + term.len()]
+            .iter()
+            .zip(term)
+            .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+}
+
+fn push_escaped(output: &mut String, c: char) {
+    match c {
+        '<' => output.push_str("&lt;"),
+        '>' => output.push_str("&gt;"),
+        '&' => output.push_str("&amp;"),
+        '"' => output.push_str("&quot;"),
+        '\'' => output.push_str("&#39;"),
+        _ => output.push(c),
+    }
+}
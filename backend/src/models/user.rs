@@ -8,10 +8,101 @@ pub struct User {
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    /// 旧版本的头像地址，上传新头像后不再使用
     pub avatar: Option<String>,
+    /// 头像版本号，由上传接口生成
+    pub avatar_version: Option<String>,
     pub is_online: Option<i8>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub presence_status: Option<String>,
+    pub last_seen_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 用户角色：user/moderator/admin
+    pub role: String,
+    pub banned_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ban_reason: Option<String>,
+}
+
+/// 用户角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl UserRole {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(UserRole::User),
+            "moderator" => Some(UserRole::Moderator),
+            "admin" => Some(UserRole::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::User => "user",
+            UserRole::Moderator => "moderator",
+            UserRole::Admin => "admin",
+        }
+    }
+}
+
+/// 用户可选的在线状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceStatus {
+    Online,
+    Away,
+    Dnd,
+    Invisible,
+}
+
+impl PresenceStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "online" => Some(PresenceStatus::Online),
+            "away" => Some(PresenceStatus::Away),
+            "dnd" => Some(PresenceStatus::Dnd),
+            "invisible" => Some(PresenceStatus::Invisible),
+            _ => None,
+        }
+    }
+
+    /// 其他用户看到的状态，隐身对外显示为离线
+    pub fn visible_status(&self) -> &'static str {
+        match self {
+            PresenceStatus::Invisible => "offline",
+            other => other.as_str(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PresenceStatus::Online => "online",
+            PresenceStatus::Away => "away",
+            PresenceStatus::Dnd => "dnd",
+            PresenceStatus::Invisible => "invisible",
+        }
+    }
+}
+
+impl std::fmt::Display for PresenceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<Option<String>> for PresenceStatus {
+    fn from(value: Option<String>) -> Self {
+        value
+            .as_deref()
+            .and_then(PresenceStatus::parse)
+            .unwrap_or(PresenceStatus::Online)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,10 +112,34 @@ pub struct CreateUser {
     pub password: String,
 }
 
+/// 头像只能通过上传接口修改
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateUser {
     pub username: Option<String>,
-    pub avatar: Option<String>,
+}
+
+/// 头像的正方形边长，默认使用中间尺寸
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 256];
+pub const DEFAULT_AVATAR_SIZE: u32 = 128;
+/// 头像原图的最大字节数
+pub const MAX_AVATAR_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 头像在存储中的键，version在每次上传时重新生成
+pub fn avatar_storage_key(user_id: &str, version: &str, size: u32) -> String {
+    format!("avatars/{}/{}_{}", user_id, version, size)
+}
+
+/// 头像地址，版本号变化后地址随之变化，因此可以长期缓存
+pub fn avatar_url(user_id: &str, version: &str, size: u32) -> String {
+    format!("/api/avatars/{}/{}/{}", user_id, version, size)
+}
+
+/// 被替换的头像版本，宽限期过后删除文件
+#[derive(Debug, Clone, FromRow)]
+pub struct RetiredAvatar {
+    pub user_id: String,
+    pub version: String,
+    pub retired_at: chrono::DateTime<chrono::Utc>,
 }
 
 impl User {
@@ -36,19 +151,57 @@ impl User {
             email,
             password_hash,
             avatar: None,
+            avatar_version: None,
             is_online: Some(0),
             created_at: now,
             updated_at: now,
+            presence_status: Some(PresenceStatus::Online.to_string()),
+            last_seen_at: None,
+            role: UserRole::User.as_str().to_string(),
+            banned_at: None,
+            ban_reason: None,
         }
     }
 
+    pub fn role(&self) -> UserRole {
+        UserRole::parse(&self.role).unwrap_or(UserRole::User)
+    }
+
+    /// 版主和管理员都可以处理违规
+    pub fn is_moderator(&self) -> bool {
+        matches!(self.role(), UserRole::Moderator | UserRole::Admin)
+    }
+
+    pub fn is_banned(&self) -> bool {
+        self.banned_at.is_some()
+    }
+
+    pub fn presence(&self) -> PresenceStatus {
+        PresenceStatus::from(self.presence_status.clone())
+    }
+
     pub fn to_public(&self) -> PublicUser {
+        let presence = self.presence();
+        let is_online = self.is_online.unwrap_or(0) != 0;
+        // 隐身用户对外表现为离线
+        let visible_online = is_online && presence != PresenceStatus::Invisible;
+
         PublicUser {
             id: self.id.clone(),
             username: self.username.clone(),
             email: self.email.clone(),
-            avatar: self.avatar.clone(),
-            is_online: self.is_online,
+            avatar: self
+                .avatar_version
+                .as_deref()
+                .map(|version| avatar_url(&self.id, version, DEFAULT_AVATAR_SIZE))
+                .or_else(|| self.avatar.clone()),
+            is_online: Some(visible_online as i8),
+            status: if visible_online {
+                presence.visible_status().to_string()
+            } else {
+                "offline".to_string()
+            },
+            last_seen_at: self.last_seen_at.map(|t| t.timestamp()),
             created_at: self.created_at.timestamp(),
             updated_at: self.updated_at.timestamp(),
         }
@@ -60,8 +213,11 @@ pub struct PublicUser {
     pub id: String,
     pub username: String,
     pub email: String,
+    /// 默认尺寸的头像地址，把末尾的尺寸替换为`AVATAR_SIZES`中的其他值可获取其他尺寸
     pub avatar: Option<String>,
     pub is_online: Option<i8>,
+    pub status: String,
+    pub last_seen_at: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
 }
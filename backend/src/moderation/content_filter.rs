@@ -0,0 +1,292 @@
+use crate::database::ContentFilterRepository;
+use crate::models::{FilterAction, FilterKind, FilterRule, Message, MessageFlag};
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// 房间规则的本地缓存时间，修改规则后其他实例最多延迟这么久生效
+const ROOM_RULES_TTL: Duration = Duration::from_secs(30);
+/// 编译后正则的大小上限，避免规则过于复杂
+const REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// 过滤结果
+#[derive(Debug, Clone)]
+pub enum FilterDecision {
+    /// 允许发送，content为处理后的内容
+    Allow {
+        content: String,
+        flags: Vec<FilterFlag>,
+    },
+    Reject {
+        rule: String,
+        reason: String,
+    },
+}
+
+/// 命中flag规则的记录
+#[derive(Debug, Clone)]
+pub struct FilterFlag {
+    pub rule: String,
+    pub reason: Option<String>,
+}
+
+enum Matcher {
+    Pattern(Regex),
+    MaxLength(usize),
+}
+
+struct CompiledRule {
+    name: String,
+    matcher: Matcher,
+    action: FilterAction,
+    reason: Option<String>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &FilterRule) -> Result<Self, String> {
+        let matcher = match rule.kind {
+            FilterKind::WordList => {
+                let words: Vec<String> = rule
+                    .pattern
+                    .split([',', '\n'])
+                    .map(str::trim)
+                    .filter(|word| !word.is_empty())
+                    .map(regex::escape)
+                    .collect();
+                if words.is_empty() {
+                    return Err(format!("规则 {} 的词列表为空", rule.name));
+                }
+                Matcher::Pattern(build_regex(&words.join("|"), true, &rule.name)?)
+            }
+            FilterKind::Regex => Matcher::Pattern(build_regex(&rule.pattern, false, &rule.name)?),
+            FilterKind::MaxLength => Matcher::MaxLength(
+                rule.pattern
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("规则 {} 的最大长度无效", rule.name))?,
+            ),
+        };
+
+        Ok(Self {
+            name: rule.name.clone(),
+            matcher,
+            action: rule.action,
+            reason: rule.reason.clone(),
+        })
+    }
+
+    fn is_match(&self, content: &str) -> bool {
+        match &self.matcher {
+            Matcher::Pattern(regex) => regex.is_match(content),
+            Matcher::MaxLength(max) => content.chars().count() > *max,
+        }
+    }
+
+    fn mask(&self, content: &str) -> String {
+        match &self.matcher {
+            Matcher::Pattern(regex) => regex
+                .replace_all(content, |caps: &regex::Captures| {
+                    "*".repeat(caps[0].chars().count())
+                })
+                .into_owned(),
+            Matcher::MaxLength(max) => content.chars().take(*max).collect(),
+        }
+    }
+
+    fn reject_reason(&self) -> String {
+        self.reason.clone().unwrap_or_else(|| match self.matcher {
+            Matcher::Pattern(_) => "消息包含不允许的内容".to_string(),
+            Matcher::MaxLength(max) => format!("消息长度不能超过{}个字符", max),
+        })
+    }
+}
+
+fn build_regex(pattern: &str, case_insensitive: bool, name: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("规则 {} 的正则无效: {}", name, e))
+}
+
+/// 一组按顺序执行的过滤规则
+pub struct FilterPipeline {
+    rules: Vec<CompiledRule>,
+}
+
+impl FilterPipeline {
+    /// 全局规则在前，房间规则在后，与全局规则同名的房间规则被忽略，未启用的规则被忽略
+    pub fn compile(global_rules: &[FilterRule], room_rules: &[FilterRule]) -> Result<Self, String> {
+        let mut merged: Vec<&FilterRule> = global_rules.iter().collect();
+        merged.extend(
+            room_rules
+                .iter()
+                .filter(|rule| !global_rules.iter().any(|r| r.name == rule.name)),
+        );
+
+        let rules = merged
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .map(CompiledRule::compile)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// 依次执行规则：reject立即返回，mask修改内容后继续，flag只做记录
+    pub fn apply(&self, content: &str) -> FilterDecision {
+        let mut content = content.to_string();
+        let mut flags = Vec::new();
+
+        for rule in &self.rules {
+            if !rule.is_match(&content) {
+                continue;
+            }
+            match rule.action {
+                FilterAction::Reject => {
+                    return FilterDecision::Reject {
+                        rule: rule.name.clone(),
+                        reason: rule.reject_reason(),
+                    };
+                }
+                FilterAction::Mask => content = rule.mask(&content),
+                FilterAction::Flag => flags.push(FilterFlag {
+                    rule: rule.name.clone(),
+                    reason: rule.reason.clone(),
+                }),
+            }
+        }
+
+        FilterDecision::Allow { content, flags }
+    }
+}
+
+struct CachedPipeline {
+    pipeline: Arc<FilterPipeline>,
+    loaded_at: Instant,
+}
+
+/// 消息内容过滤，WebSocket、HTTP和gRPC发送消息时在保存前调用
+#[derive(Clone)]
+pub struct ContentFilter {
+    global_rules: Arc<Vec<FilterRule>>,
+    repo: Arc<ContentFilterRepository>,
+    cache: Arc<RwLock<HashMap<String, CachedPipeline>>>,
+}
+
+impl ContentFilter {
+    pub fn new(
+        global_rules: Vec<FilterRule>,
+        repo: Arc<ContentFilterRepository>,
+    ) -> Result<Self, String> {
+        FilterPipeline::compile(&global_rules, &[])?;
+
+        Ok(Self {
+            global_rules: Arc::new(global_rules),
+            repo,
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// 从CONTENT_FILTER_RULES指定的JSON文件加载全局规则，未配置时没有全局规则
+    pub fn load_global_rules() -> Result<Vec<FilterRule>, Box<dyn std::error::Error>> {
+        match std::env::var("CONTENT_FILTER_RULES") {
+            Ok(path) if !path.is_empty() => {
+                let data = std::fs::read_to_string(&path)?;
+                Ok(serde_json::from_str(&data)?)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    pub fn global_rules(&self) -> &[FilterRule] {
+        &self.global_rules
+    }
+
+    /// 检查房间规则能否和全局规则一起编译
+    pub fn validate_room_rules(&self, room_rules: &[FilterRule]) -> Result<(), String> {
+        for (index, rule) in room_rules.iter().enumerate() {
+            if rule.name.trim().is_empty() || rule.name.chars().count() > 64 {
+                return Err("规则名称不能为空且不能超过64个字符".to_string());
+            }
+            if room_rules[..index].iter().any(|r| r.name == rule.name) {
+                return Err(format!("规则名称重复: {}", rule.name));
+            }
+            if self.global_rules.iter().any(|r| r.name == rule.name) {
+                return Err(format!("规则名称与全局规则重复: {}", rule.name));
+            }
+        }
+        FilterPipeline::compile(&self.global_rules, room_rules).map(|_| ())
+    }
+
+    /// 房间规则修改后清除本地缓存
+    pub fn invalidate(&self, room_id: &str) {
+        self.cache.write().unwrap().remove(room_id);
+    }
+
+    pub async fn check(&self, room_id: &str, content: &str) -> Result<FilterDecision, sqlx::Error> {
+        Ok(self.pipeline_for(room_id).await?.apply(content))
+    }
+
+    /// 保存命中flag规则的记录，等待审核
+    pub async fn record_flags(
+        &self,
+        message: &Message,
+        flags: &[FilterFlag],
+    ) -> Result<(), sqlx::Error> {
+        for flag in flags {
+            println!(
+                "消息 {} 命中过滤规则 {}，已标记待审核",
+                message.id, flag.rule
+            );
+            self.repo
+                .create_flag(&MessageFlag::new(
+                    message.id.clone(),
+                    message.room_id.clone(),
+                    message.user_id.clone(),
+                    flag.rule.clone(),
+                    flag.reason.clone(),
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn pipeline_for(&self, room_id: &str) -> Result<Arc<FilterPipeline>, sqlx::Error> {
+        if let Some(cached) = self.cache.read().unwrap().get(room_id) {
+            if cached.loaded_at.elapsed() < ROOM_RULES_TTL {
+                return Ok(cached.pipeline.clone());
+            }
+        }
+
+        let room_rules: Vec<FilterRule> = self
+            .repo
+            .find_room_rules(room_id)
+            .await?
+            .iter()
+            .filter_map(|rule| rule.to_rule())
+            .collect();
+
+        // 房间规则在保存时已校验，这里编译失败时退回只使用全局规则
+        let pipeline = match FilterPipeline::compile(&self.global_rules, &room_rules) {
+            Ok(pipeline) => pipeline,
+            Err(e) => {
+                eprintln!("房间 {} 的过滤规则无效: {}", room_id, e);
+                FilterPipeline::compile(&self.global_rules, &[])
+                    .unwrap_or(FilterPipeline { rules: Vec::new() })
+            }
+        };
+        let pipeline = Arc::new(pipeline);
+
+        self.cache.write().unwrap().insert(
+            room_id.to_string(),
+            CachedPipeline {
+                pipeline: pipeline.clone(),
+                loaded_at: Instant::now(),
+            },
+        );
+
+        Ok(pipeline)
+    }
+}
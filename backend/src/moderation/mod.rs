@@ -0,0 +1,9 @@
+pub mod content_filter;
+pub mod reports;
+pub mod send_guard;
+pub mod spam_detector;
+
+pub use content_filter::*;
+pub use reports::*;
+pub use send_guard::*;
+pub use spam_detector::*;
@@ -0,0 +1,396 @@
+use crate::audit::AuditLogger;
+use crate::cleanup::MessagePurger;
+use crate::database::{
+    MessageRepository, ModerationRepository, ReportRepository, RoomRepository, UserRepository,
+};
+use crate::models::{
+    AuditAction, AuditEntry, MAX_MUTE_SECONDS, MuteSource, Report, ReportResolution, ReportStatus,
+    ReportTargetType, ResolveReport, SubmitReport, User, UserMute,
+};
+use crate::redis::SessionManager;
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+
+/// 举报理由和处理备注的最大长度
+pub const MAX_REPORT_REASON_LENGTH: usize = 500;
+
+/// 举报处理禁言的默认时长（秒）
+const DEFAULT_MUTE_SECONDS: i64 = 24 * 60 * 60;
+
+/// 每页最多返回的举报数
+const MAX_REPORT_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("举报不存在")]
+    NotFound,
+    #[error("只有版主和管理员可以处理举报")]
+    Forbidden,
+    #[error("举报已被其他版主认领或已处理")]
+    Conflict,
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type ReportResult<T> = Result<T, ReportError>;
+
+/// 举报提交和审核，WebSocket、HTTP和gRPC共用
+#[derive(Clone)]
+pub struct ReportService {
+    report_repo: Arc<ReportRepository>,
+    message_repo: Arc<MessageRepository>,
+    user_repo: Arc<UserRepository>,
+    room_repo: Arc<RoomRepository>,
+    moderation_repo: Arc<ModerationRepository>,
+    session_manager: SessionManager,
+    event_bus: RoomEventBus,
+    audit_logger: AuditLogger,
+    message_purger: MessagePurger,
+}
+
+impl ReportService {
+    pub fn new(
+        report_repo: Arc<ReportRepository>,
+        message_repo: Arc<MessageRepository>,
+        user_repo: Arc<UserRepository>,
+        room_repo: Arc<RoomRepository>,
+        moderation_repo: Arc<ModerationRepository>,
+        session_manager: SessionManager,
+        event_bus: RoomEventBus,
+        audit_logger: AuditLogger,
+        message_purger: MessagePurger,
+    ) -> Self {
+        Self {
+            report_repo,
+            message_repo,
+            user_repo,
+            room_repo,
+            moderation_repo,
+            session_manager,
+            event_bus,
+            audit_logger,
+            message_purger,
+        }
+    }
+
+    /// 举报消息或用户，举报消息时举报人必须能看到该消息
+    pub async fn submit(&self, reporter: &User, req: SubmitReport) -> ReportResult<Report> {
+        let reason = req.reason.trim();
+        if reason.is_empty() {
+            return Err(ReportError::Invalid("请填写举报理由".to_string()));
+        }
+        if reason.chars().count() > MAX_REPORT_REASON_LENGTH {
+            return Err(ReportError::Invalid(format!(
+                "举报理由不能超过{}个字符",
+                MAX_REPORT_REASON_LENGTH
+            )));
+        }
+
+        let target_type = ReportTargetType::parse(&req.target_type)
+            .ok_or_else(|| ReportError::Invalid(format!("无效的举报类型: {}", req.target_type)))?;
+
+        let (target_user_id, message_id, room_id) = match target_type {
+            ReportTargetType::Message => {
+                let message_id = non_empty(req.message_id)
+                    .ok_or_else(|| ReportError::Invalid("缺少消息ID".to_string()))?;
+                let message = self
+                    .message_repo
+                    .find_by_id(&message_id)
+                    .await?
+                    .ok_or_else(|| ReportError::Invalid("消息不存在".to_string()))?;
+                if !self
+                    .room_repo
+                    .can_read(&reporter.id, &message.room_id)
+                    .await?
+                {
+                    return Err(ReportError::Invalid("消息不存在".to_string()));
+                }
+                (message.user_id, Some(message_id), Some(message.room_id))
+            }
+            ReportTargetType::User => {
+                let user_id = non_empty(req.user_id)
+                    .ok_or_else(|| ReportError::Invalid("缺少用户ID".to_string()))?;
+                if self.user_repo.find_by_id(&user_id).await?.is_none() {
+                    return Err(ReportError::Invalid("用户不存在".to_string()));
+                }
+                (user_id, None, None)
+            }
+        };
+
+        if target_user_id == reporter.id {
+            return Err(ReportError::Invalid("不能举报自己".to_string()));
+        }
+
+        let report = Report::new(
+            reporter.id.clone(),
+            target_type,
+            target_user_id,
+            message_id,
+            room_id,
+            reason.to_string(),
+        );
+        self.report_repo.create(&report).await?;
+        println!(
+            "用户 {} 举报了 {} {}",
+            reporter.id, report.target_type, report.target_user_id
+        );
+
+        Ok(report)
+    }
+
+    /// 审核队列，默认只列出未处理的举报
+    pub async fn list(
+        &self,
+        moderator: &User,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> ReportResult<Vec<Report>> {
+        ensure_moderator(moderator)?;
+        let status = match status.filter(|s| !s.is_empty()) {
+            Some("all") => None,
+            Some(status) => Some(
+                ReportStatus::parse(status)
+                    .ok_or_else(|| ReportError::Invalid(format!("无效的举报状态: {}", status)))?,
+            ),
+            None => Some(ReportStatus::Open),
+        };
+
+        let limit = if limit <= 0 {
+            50
+        } else {
+            limit.min(MAX_REPORT_PAGE_SIZE)
+        };
+        Ok(self
+            .report_repo
+            .list(status.map(|s| s.as_str()), limit, offset.max(0))
+            .await?)
+    }
+
+    /// 认领举报，避免多个版主重复处理
+    pub async fn claim(
+        &self,
+        moderator: &User,
+        report_id: &str,
+        ip: Option<&str>,
+    ) -> ReportResult<Report> {
+        ensure_moderator(moderator)?;
+        if self.report_repo.find_by_id(report_id).await?.is_none() {
+            return Err(ReportError::NotFound);
+        }
+        if !self.report_repo.claim(report_id, &moderator.id).await? {
+            return Err(ReportError::Conflict);
+        }
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::ReportClaimed)
+                .actor(&moderator.id)
+                .target("report", report_id)
+                .ip(ip),
+        );
+
+        self.report_repo
+            .find_by_id(report_id)
+            .await?
+            .ok_or(ReportError::NotFound)
+    }
+
+    /// 处理举报并执行对应操作，处理结果会通知举报人
+    pub async fn resolve(
+        &self,
+        moderator: &User,
+        report_id: &str,
+        req: ResolveReport,
+        ip: Option<&str>,
+    ) -> ReportResult<Report> {
+        ensure_moderator(moderator)?;
+        let resolution = ReportResolution::parse(&req.action)
+            .ok_or_else(|| ReportError::Invalid(format!("无效的处理方式: {}", req.action)))?;
+        let note = non_empty(req.note.map(|note| note.trim().to_string()));
+        if note
+            .as_ref()
+            .is_some_and(|note| note.chars().count() > MAX_REPORT_REASON_LENGTH)
+        {
+            return Err(ReportError::Invalid(format!(
+                "处理备注不能超过{}个字符",
+                MAX_REPORT_REASON_LENGTH
+            )));
+        }
+
+        let report = self
+            .report_repo
+            .find_by_id(report_id)
+            .await?
+            .ok_or(ReportError::NotFound)?;
+
+        let mute_seconds = req.mute_seconds.unwrap_or(DEFAULT_MUTE_SECONDS);
+        match resolution {
+            ReportResolution::DeleteMessage if report.message_id.is_none() => {
+                return Err(ReportError::Invalid("该举报不是针对消息的".to_string()));
+            }
+            ReportResolution::Mute if !(1..=MAX_MUTE_SECONDS).contains(&mute_seconds) => {
+                return Err(ReportError::Invalid(format!(
+                    "禁言时长必须在1到{}秒之间",
+                    MAX_MUTE_SECONDS
+                )));
+            }
+            ReportResolution::Mute | ReportResolution::Ban => {
+                let target = self.user_repo.find_by_id(&report.target_user_id).await?;
+                if target.is_some_and(|target| target.is_moderator()) {
+                    return Err(ReportError::Invalid("不能处罚版主或管理员".to_string()));
+                }
+            }
+            _ => {}
+        }
+
+        // 先标记为已处理，保证同一举报只执行一次操作
+        if !self
+            .report_repo
+            .resolve(
+                report_id,
+                &moderator.id,
+                resolution.as_str(),
+                note.as_deref(),
+            )
+            .await?
+        {
+            return Err(ReportError::Conflict);
+        }
+
+        let reason = note.clone().unwrap_or_else(|| report.reason.clone());
+        match resolution {
+            ReportResolution::Dismiss => {}
+            ReportResolution::DeleteMessage => {
+                let (Some(message_id), Some(room_id)) = (&report.message_id, &report.room_id)
+                else {
+                    return Err(ReportError::Invalid("该举报不是针对消息的".to_string()));
+                };
+                // 通过清理器删除，附件文件和缩略图一并从存储后端删除
+                let message = self.message_repo.find_by_id(message_id).await?;
+                let deleted = match message {
+                    Some(message) => !self.message_purger.purge(vec![message]).await?.is_empty(),
+                    None => false,
+                };
+                if deleted {
+                    self.audit_logger.record(
+                        AuditEntry::new(AuditAction::MessageDeleted)
+                            .actor(&moderator.id)
+                            .target("message", message_id)
+                            .ip(ip)
+                            .details(serde_json::json!({
+                                "room_id": room_id,
+                                "author_id": report.target_user_id,
+                                "report_id": report_id,
+                            })),
+                    );
+                    let event = WebSocketMessage::MessageDeleted {
+                        room_id: room_id.clone(),
+                        message_id: message_id.clone(),
+                    };
+                    if let Err(e) = self.event_bus.publish(room_id, event).await {
+                        eprintln!("广播消息删除失败: {}", e);
+                    }
+                }
+            }
+            ReportResolution::Mute => {
+                let mute = UserMute::new(
+                    report.target_user_id.clone(),
+                    None,
+                    reason,
+                    MuteSource::Moderator,
+                    moderator.id.clone(),
+                    chrono::Duration::seconds(mute_seconds),
+                );
+                self.moderation_repo.create_mute(&mute).await?;
+                self.audit_logger.record(
+                    AuditEntry::new(AuditAction::UserMuted)
+                        .actor(&moderator.id)
+                        .target("user", &report.target_user_id)
+                        .ip(ip)
+                        .details(serde_json::json!({
+                            "mute_id": mute.id,
+                            "seconds": mute_seconds,
+                            "reason": mute.reason,
+                            "report_id": report_id,
+                        })),
+                );
+            }
+            ReportResolution::Ban => {
+                self.user_repo.ban(&report.target_user_id, &reason).await?;
+                self.audit_logger.record(
+                    AuditEntry::new(AuditAction::UserBanned)
+                        .actor(&moderator.id)
+                        .target("user", &report.target_user_id)
+                        .ip(ip)
+                        .details(serde_json::json!({ "reason": reason, "report_id": report_id })),
+                );
+                self.revoke_session(moderator, &report.target_user_id, ip)
+                    .await;
+            }
+        }
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::ReportResolved)
+                .actor(&moderator.id)
+                .target("report", report_id)
+                .ip(ip)
+                .details(serde_json::json!({
+                    "resolution": resolution.as_str(),
+                    "note": note,
+                    "target_user_id": report.target_user_id,
+                    "message_id": report.message_id,
+                })),
+        );
+        println!(
+            "版主 {} 处理举报 {}: {}",
+            moderator.id,
+            report_id,
+            resolution.as_str()
+        );
+
+        let event = WebSocketMessage::ReportResolved {
+            report_id: report_id.to_string(),
+            resolution: resolution.as_str().to_string(),
+            message: format!("你提交的举报已处理：{}", resolution.describe()),
+        };
+        if let Err(e) = self
+            .event_bus
+            .publish_to_user(&report.reporter_id, event)
+            .await
+        {
+            eprintln!("通知举报人失败: {}", e);
+        }
+
+        self.report_repo
+            .find_by_id(report_id)
+            .await?
+            .ok_or(ReportError::NotFound)
+    }
+
+    /// 封禁后删除用户的登录会话，已签发的令牌由with_auth按封禁状态拒绝
+    async fn revoke_session(&self, moderator: &User, user_id: &str, ip: Option<&str>) {
+        match self.session_manager.delete_user_session(user_id).await {
+            Ok(Some(session_id)) => self.audit_logger.record(
+                AuditEntry::new(AuditAction::TokenRevoked)
+                    .actor(&moderator.id)
+                    .target("user", user_id)
+                    .ip(ip)
+                    .details(serde_json::json!({ "session_id": session_id, "reason": "ban" })),
+            ),
+            Ok(None) => {}
+            Err(e) => eprintln!("删除用户会话失败: {}", e),
+        }
+    }
+}
+
+fn ensure_moderator(user: &User) -> ReportResult<()> {
+    if user.is_moderator() {
+        Ok(())
+    } else {
+        Err(ReportError::Forbidden)
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|value| !value.is_empty())
+}
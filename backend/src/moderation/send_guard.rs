@@ -0,0 +1,206 @@
+use crate::database::{ModerationRepository, RoomRepository};
+use crate::models::User;
+use crate::moderation::SpamDetector;
+use crate::redis::{RateLimitDecision, RateLimiter};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 消息不能发送的原因
+#[derive(Debug, Clone)]
+pub enum SendRejection {
+    /// 发送过于频繁或受慢速模式限制
+    RateLimited {
+        reason: String,
+        retry_after_secs: u64,
+    },
+    /// 账号已被封禁
+    Banned { reason: String },
+    /// 用户处于禁言中
+    Muted {
+        reason: String,
+        until: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl SendRejection {
+    pub fn message(&self) -> String {
+        match self {
+            SendRejection::RateLimited {
+                reason,
+                retry_after_secs,
+            } => format!("{}，请在{}秒后重试", reason, retry_after_secs),
+            SendRejection::Banned { reason } => format!("账号已被封禁，原因: {}", reason),
+            SendRejection::Muted { reason, until } => format!(
+                "你已被禁言至{}，原因: {}",
+                until
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                reason
+            ),
+        }
+    }
+}
+
+/// 发送消息前的检查，WebSocket、HTTP和gRPC共用
+#[derive(Clone)]
+pub struct SendGuard {
+    rate_limiter: RateLimiter,
+    room_repo: Arc<RoomRepository>,
+    moderation_repo: Arc<ModerationRepository>,
+    spam_detector: SpamDetector,
+}
+
+impl SendGuard {
+    pub fn new(
+        rate_limiter: RateLimiter,
+        room_repo: Arc<RoomRepository>,
+        moderation_repo: Arc<ModerationRepository>,
+        spam_detector: SpamDetector,
+    ) -> Self {
+        Self {
+            rate_limiter,
+            room_repo,
+            moderation_repo,
+            spam_detector,
+        }
+    }
+
+    /// 依次检查封禁、禁言、发送频率、房间慢速模式和垃圾消息
+    ///
+    /// Redis不可用时放行，避免限流故障导致无法聊天。版主和管理员不做垃圾消息检测。
+    pub async fn check(
+        &self,
+        user: &User,
+        room_id: &str,
+        content: &str,
+    ) -> Result<Option<SendRejection>, sqlx::Error> {
+        if let Some(rejection) = self.check_banned_or_muted(user, room_id).await? {
+            return Ok(Some(rejection));
+        }
+
+        match self.rate_limiter.check_user("send_message", &user.id).await {
+            Ok(decision @ RateLimitDecision::Limited { .. }) => {
+                return Ok(Some(SendRejection::RateLimited {
+                    reason: "发送消息过于频繁".to_string(),
+                    retry_after_secs: decision.retry_after_secs(),
+                }));
+            }
+            Ok(RateLimitDecision::Allowed) => {}
+            Err(e) => eprintln!("限流检查失败: {}", e),
+        }
+
+        if let Some(rejection) = self.check_slow_mode(&user.id, room_id).await? {
+            return Ok(Some(rejection));
+        }
+
+        if user.is_moderator() {
+            return Ok(None);
+        }
+        Ok(self
+            .spam_detector
+            .inspect(user, room_id, content)
+            .await?
+            .map(|mute| SendRejection::Muted {
+                reason: mute.reason,
+                until: mute.expires_at,
+            }))
+    }
+
+    /// 执行斜杠命令前的检查：封禁和命令频率
+    ///
+    /// 会在房间中发送通知的命令同样受禁言和慢速模式限制。
+    pub async fn check_command(
+        &self,
+        user: &User,
+        room_id: &str,
+        posts_notice: bool,
+    ) -> Result<Option<SendRejection>, sqlx::Error> {
+        if user.is_banned() {
+            return Ok(Some(banned(user)));
+        }
+
+        match self
+            .rate_limiter
+            .check_user("slash_command", &user.id)
+            .await
+        {
+            Ok(decision @ RateLimitDecision::Limited { .. }) => {
+                return Ok(Some(SendRejection::RateLimited {
+                    reason: "执行命令过于频繁".to_string(),
+                    retry_after_secs: decision.retry_after_secs(),
+                }));
+            }
+            Ok(RateLimitDecision::Allowed) => {}
+            Err(e) => eprintln!("限流检查失败: {}", e),
+        }
+
+        if !posts_notice {
+            return Ok(None);
+        }
+        if let Some(rejection) = self.check_banned_or_muted(user, room_id).await? {
+            return Ok(Some(rejection));
+        }
+        self.check_slow_mode(&user.id, room_id).await
+    }
+
+    async fn check_banned_or_muted(
+        &self,
+        user: &User,
+        room_id: &str,
+    ) -> Result<Option<SendRejection>, sqlx::Error> {
+        if user.is_banned() {
+            return Ok(Some(banned(user)));
+        }
+
+        Ok(self
+            .moderation_repo
+            .find_active_mute(&user.id, room_id)
+            .await?
+            .map(|mute| SendRejection::Muted {
+                reason: mute.reason,
+                until: mute.expires_at,
+            }))
+    }
+
+    /// 房间慢速模式，房间创建者不受限制
+    async fn check_slow_mode(
+        &self,
+        user_id: &str,
+        room_id: &str,
+    ) -> Result<Option<SendRejection>, sqlx::Error> {
+        let Some(room) = self.room_repo.find_by_id(room_id).await? else {
+            return Ok(None);
+        };
+        if room.slow_mode_seconds <= 0 || room.created_by == user_id {
+            return Ok(None);
+        }
+
+        let interval = Duration::from_secs(room.slow_mode_seconds as u64);
+        match self
+            .rate_limiter
+            .check_slow_mode(room_id, user_id, interval)
+            .await
+        {
+            Ok(decision @ RateLimitDecision::Limited { .. }) => {
+                Ok(Some(SendRejection::RateLimited {
+                    reason: format!(
+                        "房间已开启慢速模式，每{}秒只能发送一条消息",
+                        room.slow_mode_seconds
+                    ),
+                    retry_after_secs: decision.retry_after_secs(),
+                }))
+            }
+            Ok(RateLimitDecision::Allowed) => Ok(None),
+            Err(e) => {
+                eprintln!("慢速模式检查失败: {}", e);
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn banned(user: &User) -> SendRejection {
+    SendRejection::Banned {
+        reason: user.ban_reason.clone().unwrap_or_default(),
+    }
+}
@@ -0,0 +1,461 @@
+use crate::audit::AuditLogger;
+use crate::database::{ModerationRepository, UserRepository};
+use crate::models::{
+    AuditAction, AuditEntry, MuteSource, SYSTEM_ACTOR, SpamDecision, User, UserMute,
+};
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use redis::{Client, RedisResult, Script};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 固定窗口计数，窗口从第一次计数开始
+const WINDOW_COUNTER_SCRIPT: &str = r#"
+local count = redis.call('INCRBY', KEYS[1], ARGV[1])
+if count == tonumber(ARGV[1]) then
+    redis.call('EXPIRE', KEYS[1], ARGV[2])
+end
+return count
+"#;
+
+/// 固定窗口内的去重集合，返回集合大小
+const WINDOW_SET_SCRIPT: &str = r#"
+redis.call('SADD', KEYS[1], ARGV[1])
+local size = redis.call('SCARD', KEYS[1])
+if size == 1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[2])
+end
+return size
+"#;
+
+/// 反垃圾规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamRule {
+    /// 短时间内重复发送相同内容
+    DuplicateMessages,
+    /// 短时间内发送大量链接
+    LinkBurst,
+    /// 新账号短时间内在多个房间发言
+    NewAccountRoomHopping,
+    /// 单条消息提及过多用户
+    MassMentions,
+}
+
+impl SpamRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpamRule::DuplicateMessages => "duplicate",
+            SpamRule::LinkBurst => "links",
+            SpamRule::NewAccountRoomHopping => "new_account_rooms",
+            SpamRule::MassMentions => "mentions",
+        }
+    }
+
+    /// 告知用户的禁言原因
+    pub fn reason(&self) -> &'static str {
+        match self {
+            SpamRule::DuplicateMessages => "短时间内重复发送相同内容",
+            SpamRule::LinkBurst => "短时间内发送过多链接",
+            SpamRule::NewAccountRoomHopping => "新注册账号短时间内在多个房间发言",
+            SpamRule::MassMentions => "单条消息提及过多用户",
+        }
+    }
+}
+
+/// 窗口内最多允许limit次，超过即触发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowLimit {
+    pub limit: u32,
+    pub window_secs: u64,
+}
+
+impl WindowLimit {
+    pub const fn new(limit: u32, window_secs: u64) -> Self {
+        Self { limit, window_secs }
+    }
+
+    /// 解析`次数/窗口秒数`格式，如`3/60`
+    fn parse(value: &str) -> Option<Self> {
+        let (limit, window) = value.trim().split_once('/')?;
+        let limit = Self::new(limit.trim().parse().ok()?, window.trim().parse().ok()?);
+        (limit.limit > 0 && limit.window_secs > 0).then_some(limit)
+    }
+}
+
+/// 反垃圾阈值配置
+#[derive(Debug, Clone)]
+pub struct SpamConfig {
+    pub duplicate: WindowLimit,
+    pub links: WindowLimit,
+    pub new_account_rooms: WindowLimit,
+    /// 单条消息最多提及的用户数
+    pub max_mentions: u32,
+    /// 注册时间不足该时长的账号视为新账号
+    pub new_account_age: chrono::Duration,
+    /// 逐次递增的禁言时长（秒），超过次数后使用最后一档
+    pub mute_durations: Vec<i64>,
+    /// 统计历史禁言次数的时间范围
+    pub escalation_window: chrono::Duration,
+}
+
+impl Default for SpamConfig {
+    fn default() -> Self {
+        Self {
+            duplicate: WindowLimit::new(3, 60),
+            links: WindowLimit::new(5, 30),
+            new_account_rooms: WindowLimit::new(3, 60),
+            max_mentions: 5,
+            new_account_age: chrono::Duration::hours(24),
+            mute_durations: vec![300, 1800, 7200, 86400],
+            escalation_window: chrono::Duration::days(7),
+        }
+    }
+}
+
+impl SpamConfig {
+    /// 使用默认配置，并用以下环境变量覆盖：
+    ///
+    /// - SPAM_THRESHOLDS：如`duplicate=3/60,links=5/30,new_account_rooms=3/60,mentions=5`
+    /// - SPAM_NEW_ACCOUNT_HOURS：新账号判定时长
+    /// - SPAM_MUTE_DURATIONS：递增的禁言秒数，如`300,1800,7200,86400`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(thresholds) = std::env::var("SPAM_THRESHOLDS") {
+            for item in thresholds.split(',').filter(|item| !item.trim().is_empty()) {
+                let applied = match item.split_once('=') {
+                    Some((rule, value)) => config.apply_threshold(rule.trim(), value),
+                    None => false,
+                };
+                if !applied {
+                    eprintln!("忽略无效的反垃圾配置: {}", item);
+                }
+            }
+        }
+
+        if let Ok(hours) = std::env::var("SPAM_NEW_ACCOUNT_HOURS") {
+            match hours.trim().parse::<i64>() {
+                Ok(hours) if hours >= 0 => config.new_account_age = chrono::Duration::hours(hours),
+                _ => eprintln!("忽略无效的SPAM_NEW_ACCOUNT_HOURS: {}", hours),
+            }
+        }
+
+        if let Ok(durations) = std::env::var("SPAM_MUTE_DURATIONS") {
+            let parsed: Option<Vec<i64>> = durations
+                .split(',')
+                .map(|value| value.trim().parse().ok().filter(|secs| *secs > 0))
+                .collect();
+            match parsed {
+                Some(parsed) if !parsed.is_empty() => config.mute_durations = parsed,
+                _ => eprintln!("忽略无效的SPAM_MUTE_DURATIONS: {}", durations),
+            }
+        }
+
+        config
+    }
+
+    fn apply_threshold(&mut self, rule: &str, value: &str) -> bool {
+        let target = match rule {
+            "duplicate" => &mut self.duplicate,
+            "links" => &mut self.links,
+            "new_account_rooms" => &mut self.new_account_rooms,
+            "mentions" => {
+                return match value.trim().parse() {
+                    Ok(limit) if limit > 0 => {
+                        self.max_mentions = limit;
+                        true
+                    }
+                    _ => false,
+                };
+            }
+            _ => return false,
+        };
+        match WindowLimit::parse(value) {
+            Some(limit) => {
+                *target = limit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 第offenses次违规（从0开始）对应的禁言时长
+    fn mute_duration(&self, offenses: usize) -> i64 {
+        let index = offenses.min(self.mute_durations.len().saturating_sub(1));
+        self.mute_durations.get(index).copied().unwrap_or(300)
+    }
+}
+
+/// 一次规则命中
+#[derive(Debug, Clone, Copy)]
+pub struct SpamHit {
+    pub rule: SpamRule,
+    pub observed: u32,
+    pub threshold: u32,
+}
+
+/// 发送消息时的反垃圾检测，命中后自动全局禁言并通知版主
+///
+/// 计数保存在Redis中，多实例共享；每次处罚都会写入spam_decisions便于调整阈值。
+#[derive(Clone)]
+pub struct SpamDetector {
+    client: Client,
+    config: Arc<SpamConfig>,
+    moderation_repo: Arc<ModerationRepository>,
+    user_repo: Arc<UserRepository>,
+    event_bus: RoomEventBus,
+    audit_logger: AuditLogger,
+    counter_script: Arc<Script>,
+    set_script: Arc<Script>,
+}
+
+impl SpamDetector {
+    pub fn new(
+        client: Client,
+        config: SpamConfig,
+        moderation_repo: Arc<ModerationRepository>,
+        user_repo: Arc<UserRepository>,
+        event_bus: RoomEventBus,
+        audit_logger: AuditLogger,
+    ) -> Self {
+        Self {
+            client,
+            config: Arc::new(config),
+            moderation_repo,
+            user_repo,
+            event_bus,
+            audit_logger,
+            counter_script: Arc::new(Script::new(WINDOW_COUNTER_SCRIPT)),
+            set_script: Arc::new(Script::new(WINDOW_SET_SCRIPT)),
+        }
+    }
+
+    /// 检查即将发送的消息，命中规则时返回新建的禁言
+    ///
+    /// Redis不可用时只做本地能判断的规则。
+    pub async fn inspect(
+        &self,
+        user: &User,
+        room_id: &str,
+        content: &str,
+    ) -> Result<Option<UserMute>, sqlx::Error> {
+        let hit = match self.detect(user, room_id, content).await {
+            Ok(hit) => hit,
+            Err(e) => {
+                eprintln!("反垃圾计数失败: {}", e);
+                self.check_mentions(content)
+            }
+        };
+
+        match hit {
+            Some(hit) => self.mute(user, room_id, hit).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn detect(
+        &self,
+        user: &User,
+        room_id: &str,
+        content: &str,
+    ) -> RedisResult<Option<SpamHit>> {
+        if let Some(hit) = self.check_mentions(content) {
+            return Ok(Some(hit));
+        }
+
+        let mut conn = self.client.get_async_connection().await?;
+        let config = &self.config;
+
+        let normalized = normalize(content);
+        if !normalized.is_empty() {
+            let digest = hex::encode(Sha256::digest(normalized.as_bytes()));
+            let count: u32 = self
+                .counter_script
+                .key(format!("spam:dup:{}:{}", user.id, &digest[..16]))
+                .arg(1)
+                .arg(config.duplicate.window_secs)
+                .invoke_async(&mut conn)
+                .await?;
+            if count > config.duplicate.limit {
+                return Ok(Some(SpamHit {
+                    rule: SpamRule::DuplicateMessages,
+                    observed: count,
+                    threshold: config.duplicate.limit,
+                }));
+            }
+        }
+
+        let links = count_links(content);
+        if links > 0 {
+            let count: u32 = self
+                .counter_script
+                .key(format!("spam:links:{}", user.id))
+                .arg(links)
+                .arg(config.links.window_secs)
+                .invoke_async(&mut conn)
+                .await?;
+            if count > config.links.limit {
+                return Ok(Some(SpamHit {
+                    rule: SpamRule::LinkBurst,
+                    observed: count,
+                    threshold: config.links.limit,
+                }));
+            }
+        }
+
+        if chrono::Utc::now() - user.created_at < config.new_account_age {
+            let rooms: u32 = self
+                .set_script
+                .key(format!("spam:rooms:{}", user.id))
+                .arg(room_id)
+                .arg(config.new_account_rooms.window_secs)
+                .invoke_async(&mut conn)
+                .await?;
+            if rooms > config.new_account_rooms.limit {
+                return Ok(Some(SpamHit {
+                    rule: SpamRule::NewAccountRoomHopping,
+                    observed: rooms,
+                    threshold: config.new_account_rooms.limit,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn check_mentions(&self, content: &str) -> Option<SpamHit> {
+        let mentions = count_mentions(content);
+        (mentions > self.config.max_mentions).then_some(SpamHit {
+            rule: SpamRule::MassMentions,
+            observed: mentions,
+            threshold: self.config.max_mentions,
+        })
+    }
+
+    /// 按近期被自动禁言的次数递增禁言时长
+    async fn mute(
+        &self,
+        user: &User,
+        room_id: &str,
+        hit: SpamHit,
+    ) -> Result<UserMute, sqlx::Error> {
+        let since = chrono::Utc::now() - self.config.escalation_window;
+        let offenses = self
+            .moderation_repo
+            .count_mutes_since(&user.id, MuteSource::Spam.as_str(), since)
+            .await?;
+        let mute_seconds = self.config.mute_duration(offenses.max(0) as usize);
+
+        let mute = UserMute::new(
+            user.id.clone(),
+            None,
+            hit.rule.reason().to_string(),
+            MuteSource::Spam,
+            SYSTEM_ACTOR.to_string(),
+            chrono::Duration::seconds(mute_seconds),
+        );
+        self.moderation_repo.create_mute(&mute).await?;
+
+        println!(
+            "反垃圾: 用户 {} 在房间 {} 触发规则 {} ({}/{})，禁言{}秒",
+            user.id,
+            room_id,
+            hit.rule.as_str(),
+            hit.observed,
+            hit.threshold,
+            mute_seconds
+        );
+
+        self.record_decision(user, room_id, hit, mute_seconds);
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::UserMuted)
+                .actor(SYSTEM_ACTOR)
+                .target("user", &user.id)
+                .details(serde_json::json!({
+                    "mute_id": mute.id,
+                    "seconds": mute_seconds,
+                    "reason": mute.reason,
+                    "rule": hit.rule.as_str(),
+                    "room_id": room_id,
+                })),
+        );
+        self.alert_moderators(user, room_id, hit, &mute);
+
+        Ok(mute)
+    }
+
+    /// 记录处罚决策，不阻塞发送流程
+    fn record_decision(&self, user: &User, room_id: &str, hit: SpamHit, mute_seconds: i64) {
+        let decision = SpamDecision::new(
+            user.id.clone(),
+            room_id.to_string(),
+            hit.rule.as_str(),
+            hit.observed as i32,
+            hit.threshold as i32,
+            "mute",
+            mute_seconds as i32,
+        );
+        let moderation_repo = self.moderation_repo.clone();
+        tokio::spawn(async move {
+            if let Err(e) = moderation_repo.create_spam_decision(&decision).await {
+                eprintln!("记录反垃圾决策失败: {}", e);
+            }
+        });
+    }
+
+    /// 通知所有在线的版主和管理员
+    fn alert_moderators(&self, user: &User, room_id: &str, hit: SpamHit, mute: &UserMute) {
+        let alert = WebSocketMessage::ModerationAlert {
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            room_id: room_id.to_string(),
+            rule: hit.rule.as_str().to_string(),
+            reason: format!("{} ({}/{})", mute.reason, hit.observed, hit.threshold),
+            muted_until: mute.expires_at.timestamp(),
+        };
+        let user_repo = self.user_repo.clone();
+        let event_bus = self.event_bus.clone();
+        tokio::spawn(async move {
+            let moderator_ids = match user_repo.get_moderator_ids().await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    eprintln!("获取版主列表失败: {}", e);
+                    return;
+                }
+            };
+            for moderator_id in moderator_ids {
+                if let Err(e) = event_bus
+                    .publish_to_user(&moderator_id, alert.clone())
+                    .await
+                {
+                    eprintln!("发送版主告警失败: {}", e);
+                }
+            }
+        });
+    }
+}
+
+/// 忽略大小写和空白差异
+fn normalize(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn count_links(content: &str) -> u32 {
+    content
+        .split_whitespace()
+        .filter(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .count() as u32
+}
+
+/// 统计不重复的@提及
+fn count_mentions(content: &str) -> u32 {
+    content
+        .split_whitespace()
+        .filter(|word| word.len() > 1 && word.starts_with('@'))
+        .collect::<HashSet<_>>()
+        .len() as u32
+}
@@ -0,0 +1,199 @@
+use crate::audit::AuditLogger;
+use crate::database::{
+    AttachmentRepository, LinkPreviewRepository, MessageRepository, PinRepository, PollRepository,
+    RoomRepository,
+};
+use crate::models::{
+    AuditAction, AuditEntry, MAX_PINS_PER_ROOM, Message, MessagePin, PinInfo, PinOutcome, User,
+};
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PinError {
+    #[error("消息不存在")]
+    NotFound,
+    #[error("房间不存在")]
+    RoomNotFound,
+    #[error("只有房间创建者、版主和管理员可以置顶消息")]
+    Forbidden,
+    #[error("消息已经置顶")]
+    AlreadyPinned,
+    #[error("消息没有置顶")]
+    NotPinned,
+    #[error("每个房间最多置顶{}条消息", MAX_PINS_PER_ROOM)]
+    LimitReached,
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type PinResult<T> = Result<T, PinError>;
+
+/// 房间置顶消息，WebSocket、HTTP和gRPC共用
+///
+/// 置顶和取消置顶通过事件总线广播message_pinned/message_unpinned，
+/// 加入房间时把当前置顶消息发送给加入者。
+#[derive(Clone)]
+pub struct PinService {
+    pin_repo: Arc<PinRepository>,
+    message_repo: Arc<MessageRepository>,
+    room_repo: Arc<RoomRepository>,
+    attachment_repo: Arc<AttachmentRepository>,
+    link_preview_repo: Arc<LinkPreviewRepository>,
+    poll_repo: Arc<PollRepository>,
+    event_bus: RoomEventBus,
+    audit_logger: AuditLogger,
+}
+
+impl PinService {
+    pub fn new(
+        pin_repo: Arc<PinRepository>,
+        message_repo: Arc<MessageRepository>,
+        room_repo: Arc<RoomRepository>,
+        attachment_repo: Arc<AttachmentRepository>,
+        link_preview_repo: Arc<LinkPreviewRepository>,
+        poll_repo: Arc<PollRepository>,
+        event_bus: RoomEventBus,
+        audit_logger: AuditLogger,
+    ) -> Self {
+        Self {
+            pin_repo,
+            message_repo,
+            room_repo,
+            attachment_repo,
+            link_preview_repo,
+            poll_repo,
+            event_bus,
+            audit_logger,
+        }
+    }
+
+    pub async fn pin(&self, user: &User, message_id: &str, ip: Option<&str>) -> PinResult<PinInfo> {
+        let message = self.find_manageable(user, message_id).await?;
+
+        let pin = MessagePin::new(message.id.clone(), message.room_id.clone(), user.id.clone());
+        match self.pin_repo.pin(&pin).await? {
+            PinOutcome::Pinned => {}
+            PinOutcome::AlreadyPinned => return Err(PinError::AlreadyPinned),
+            PinOutcome::LimitReached => return Err(PinError::LimitReached),
+        }
+
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::MessagePinned)
+                .actor(&user.id)
+                .target("message", &message.id)
+                .ip(ip)
+                .details(serde_json::json!({ "room_id": message.room_id })),
+        );
+
+        let info = self
+            .to_infos(vec![pin], vec![message])
+            .await?
+            .pop()
+            .ok_or(PinError::NotFound)?;
+        let event = WebSocketMessage::MessagePinned {
+            room_id: info.message.room_id.clone(),
+            pin: info.clone(),
+        };
+        if let Err(e) = self.event_bus.publish(&info.message.room_id, event).await {
+            eprintln!("广播置顶消息失败: {}", e);
+        }
+        Ok(info)
+    }
+
+    pub async fn unpin(&self, user: &User, message_id: &str, ip: Option<&str>) -> PinResult<()> {
+        let message = self.find_manageable(user, message_id).await?;
+        if !self.pin_repo.unpin(&message.id).await? {
+            return Err(PinError::NotPinned);
+        }
+
+        self.audit_logger.record(
+            AuditEntry::new(AuditAction::MessageUnpinned)
+                .actor(&user.id)
+                .target("message", &message.id)
+                .ip(ip)
+                .details(serde_json::json!({ "room_id": message.room_id })),
+        );
+
+        let event = WebSocketMessage::MessageUnpinned {
+            room_id: message.room_id.clone(),
+            message_id: message.id.clone(),
+            unpinned_by: user.id.clone(),
+        };
+        if let Err(e) = self.event_bus.publish(&message.room_id, event).await {
+            eprintln!("广播取消置顶失败: {}", e);
+        }
+        Ok(())
+    }
+
+    pub async fn list(&self, user: &User, room_id: &str) -> PinResult<Vec<PinInfo>> {
+        if !self.room_repo.can_read(&user.id, room_id).await? {
+            return Err(PinError::RoomNotFound);
+        }
+        Ok(self.room_pins(room_id).await?)
+    }
+
+    /// 房间当前的置顶消息，加入房间时由调用方完成权限检查
+    pub async fn room_pins(&self, room_id: &str) -> Result<Vec<PinInfo>, sqlx::Error> {
+        let pins = self.pin_repo.find_by_room(room_id).await?;
+        if pins.is_empty() {
+            return Ok(Vec::new());
+        }
+        let messages = self.pin_repo.find_pinned_messages(room_id).await?;
+        self.to_infos(pins, messages).await
+    }
+
+    /// 按置顶顺序组装完整的消息内容，已过期的消息不返回
+    async fn to_infos(
+        &self,
+        pins: Vec<MessagePin>,
+        messages: Vec<Message>,
+    ) -> Result<Vec<PinInfo>, sqlx::Error> {
+        let message_ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+        let attachments = self
+            .attachment_repo
+            .find_by_message_ids(&message_ids)
+            .await?;
+        let link_previews = self
+            .link_preview_repo
+            .find_by_message_ids(&message_ids)
+            .await?;
+        let polls = self
+            .poll_repo
+            .find_infos_by_message_ids(&message_ids)
+            .await?;
+
+        Ok(pins
+            .iter()
+            .filter_map(|pin| {
+                let message = messages.iter().find(|m| m.id == pin.message_id)?;
+                Some(PinInfo::new(
+                    pin,
+                    message.to_grpc_with_details(&attachments, &link_previews, &polls),
+                ))
+            })
+            .collect())
+    }
+
+    /// 看不到所在房间的消息按不存在处理
+    async fn find_manageable(&self, user: &User, message_id: &str) -> PinResult<Message> {
+        let message = self
+            .message_repo
+            .find_by_id(message_id)
+            .await?
+            .ok_or(PinError::NotFound)?;
+        if !self.room_repo.can_read(&user.id, &message.room_id).await? {
+            return Err(PinError::NotFound);
+        }
+
+        let room = self
+            .room_repo
+            .find_by_id(&message.room_id)
+            .await?
+            .ok_or(PinError::NotFound)?;
+        if room.created_by != user.id && !user.is_moderator() {
+            return Err(PinError::Forbidden);
+        }
+        Ok(message)
+    }
+}
@@ -0,0 +1,286 @@
+use crate::database::{MessageRepository, PollRepository, RoomRepository};
+use crate::models::{
+    CreatePoll, MAX_POLL_DURATION_DAYS, MAX_POLL_OPTION_LENGTH, MAX_POLL_OPTIONS,
+    MAX_POLL_QUESTION_LENGTH, MIN_POLL_OPTIONS, Message, MessageType, Poll, PollInfo, PollOption,
+    User, VoteOutcome,
+};
+use crate::moderation::{ContentFilter, FilterDecision, FilterFlag, SendGuard, SendRejection};
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PollError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("投票不存在")]
+    NotFound,
+    #[error("只有发起人、版主和管理员可以结束投票")]
+    Forbidden,
+    #[error("投票已结束")]
+    Closed,
+    #[error("你已经投过票了")]
+    AlreadyVoted,
+    #[error("{}", .0.message())]
+    Rejected(SendRejection),
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type PollResult<T> = Result<T, PollError>;
+
+/// 投票的创建、投票和结束，WebSocket、HTTP和gRPC共用
+///
+/// 投票本身是一条poll类型的消息，票数变化通过事件总线广播poll_updated。
+#[derive(Clone)]
+pub struct PollService {
+    poll_repo: Arc<PollRepository>,
+    message_repo: Arc<MessageRepository>,
+    room_repo: Arc<RoomRepository>,
+    content_filter: ContentFilter,
+    send_guard: SendGuard,
+    event_bus: RoomEventBus,
+}
+
+impl PollService {
+    pub fn new(
+        poll_repo: Arc<PollRepository>,
+        message_repo: Arc<MessageRepository>,
+        room_repo: Arc<RoomRepository>,
+        content_filter: ContentFilter,
+        send_guard: SendGuard,
+        event_bus: RoomEventBus,
+    ) -> Self {
+        Self {
+            poll_repo,
+            message_repo,
+            room_repo,
+            content_filter,
+            send_guard,
+            event_bus,
+        }
+    }
+
+    /// 发起投票，和普通消息一样经过发送检查和内容过滤
+    pub async fn create(
+        &self,
+        user: &User,
+        room_id: &str,
+        req: CreatePoll,
+    ) -> PollResult<(Message, PollInfo)> {
+        let question = req.question.trim().to_string();
+        if question.is_empty() || question.chars().count() > MAX_POLL_QUESTION_LENGTH {
+            return Err(PollError::Invalid(format!(
+                "投票问题不能为空且不能超过{}个字符",
+                MAX_POLL_QUESTION_LENGTH
+            )));
+        }
+        let options: Vec<String> = req
+            .options
+            .iter()
+            .map(|option| option.trim().to_string())
+            .collect();
+        if !(MIN_POLL_OPTIONS..=MAX_POLL_OPTIONS).contains(&options.len()) {
+            return Err(PollError::Invalid(format!(
+                "选项数量必须在{}到{}之间",
+                MIN_POLL_OPTIONS, MAX_POLL_OPTIONS
+            )));
+        }
+        if options
+            .iter()
+            .any(|option| option.is_empty() || option.chars().count() > MAX_POLL_OPTION_LENGTH)
+        {
+            return Err(PollError::Invalid(format!(
+                "选项不能为空且不能超过{}个字符",
+                MAX_POLL_OPTION_LENGTH
+            )));
+        }
+        for (index, option) in options.iter().enumerate() {
+            if options[..index].contains(option) {
+                return Err(PollError::Invalid(format!("选项重复: {}", option)));
+            }
+        }
+
+        let closes_at = match req.closes_at.filter(|ts| *ts > 0) {
+            Some(ts) => {
+                let now = chrono::Utc::now();
+                let closes_at = chrono::DateTime::from_timestamp(ts, 0)
+                    .filter(|t| {
+                        *t > now && *t <= now + chrono::Duration::days(MAX_POLL_DURATION_DAYS)
+                    })
+                    .ok_or_else(|| {
+                        PollError::Invalid(format!(
+                            "截止时间必须在{}天以内的将来",
+                            MAX_POLL_DURATION_DAYS
+                        ))
+                    })?;
+                Some(closes_at)
+            }
+            None => None,
+        };
+
+        if !self.room_repo.can_read(&user.id, room_id).await? {
+            return Err(PollError::Invalid("房间不存在".to_string()));
+        }
+        if let Some(rejection) = self.send_guard.check(user, room_id, &question).await? {
+            return Err(PollError::Rejected(rejection));
+        }
+
+        // 问题和选项都要经过内容过滤，只记录问题的过滤标记
+        let (question, flags) = self.filter(room_id, &question).await?;
+        let mut filtered_options = Vec::with_capacity(options.len());
+        for option in &options {
+            filtered_options.push(self.filter(room_id, option).await?.0);
+        }
+
+        let message = self
+            .message_repo
+            .create(Message::new(
+                user.id.clone(),
+                user.username.clone(),
+                question.clone(),
+                room_id.to_string(),
+                MessageType::Poll,
+            ))
+            .await?;
+
+        let poll = Poll::new(
+            message.id.clone(),
+            room_id.to_string(),
+            user.id.clone(),
+            question,
+            req.multi_choice,
+            req.anonymous,
+            closes_at,
+        );
+        let options: Vec<PollOption> = filtered_options
+            .into_iter()
+            .enumerate()
+            .map(|(position, text)| PollOption::new(poll.id.clone(), position as i32, text))
+            .collect();
+        if let Err(e) = self.poll_repo.create(&poll, &options).await {
+            // 投票保存失败时不留下没有投票的poll消息
+            if let Err(e) = self.message_repo.delete(&message.id).await {
+                eprintln!("删除投票消息失败: {}", e);
+            }
+            return Err(e.into());
+        }
+        if let Err(e) = self.content_filter.record_flags(&message, &flags).await {
+            eprintln!("保存过滤标记失败: {}", e);
+        }
+
+        let info = poll.to_info(&options, &[]);
+        let event = WebSocketMessage::ChatMessage {
+            message_id: Some(message.id.clone()),
+            room_id: room_id.to_string(),
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            content: message.content.clone(),
+            content_html: message.content_html.clone(),
+            message_type: MessageType::Poll.to_string(),
+            attachment_ids: Vec::new(),
+            attachments: Vec::new(),
+            poll: Some(info.clone()),
+            ttl_seconds: None,
+            expires_at: message.expires_at.map(|t| t.timestamp()),
+            reference: None,
+        };
+        if let Err(e) = self.event_bus.publish(room_id, event).await {
+            eprintln!("广播投票消息失败: {}", e);
+        }
+        println!("用户 {} 在房间 {} 发起投票 {}", user.id, room_id, poll.id);
+
+        Ok((message, info))
+    }
+
+    /// 投票，单选投票只能选一个选项，每人只能投一次
+    pub async fn vote(
+        &self,
+        user: &User,
+        poll_id: &str,
+        option_ids: Vec<String>,
+    ) -> PollResult<PollInfo> {
+        let poll = self.find_readable(user, poll_id).await?;
+        if poll.is_closed() {
+            return Err(PollError::Closed);
+        }
+
+        let mut option_ids = option_ids;
+        option_ids.sort();
+        option_ids.dedup();
+        if option_ids.is_empty() {
+            return Err(PollError::Invalid("请至少选择一个选项".to_string()));
+        }
+        if !poll.multi_choice && option_ids.len() > 1 {
+            return Err(PollError::Invalid("该投票只能选择一个选项".to_string()));
+        }
+        let options = self.poll_repo.find_options(&poll.id).await?;
+        if option_ids
+            .iter()
+            .any(|id| !options.iter().any(|option| &option.id == id))
+        {
+            return Err(PollError::Invalid("选项不存在".to_string()));
+        }
+
+        match self.poll_repo.vote(&poll.id, &user.id, &option_ids).await? {
+            VoteOutcome::Recorded => {}
+            VoteOutcome::AlreadyVoted => return Err(PollError::AlreadyVoted),
+            VoteOutcome::Closed => return Err(PollError::Closed),
+        }
+
+        let info = self.poll_repo.find_info(&poll).await?;
+        self.publish_update(&poll.room_id, &info).await;
+        Ok(info)
+    }
+
+    /// 提前结束投票
+    pub async fn close(&self, user: &User, poll_id: &str) -> PollResult<PollInfo> {
+        let poll = self.find_readable(user, poll_id).await?;
+        if poll.created_by != user.id && !user.is_moderator() {
+            return Err(PollError::Forbidden);
+        }
+        if poll.is_closed() || !self.poll_repo.close(&poll.id).await? {
+            return Err(PollError::Closed);
+        }
+
+        let poll = self
+            .poll_repo
+            .find_by_id(&poll.id)
+            .await?
+            .ok_or(PollError::NotFound)?;
+        let info = self.poll_repo.find_info(&poll).await?;
+        self.publish_update(&poll.room_id, &info).await;
+        Ok(info)
+    }
+
+    /// 看不到所在房间的投票按不存在处理
+    async fn find_readable(&self, user: &User, poll_id: &str) -> PollResult<Poll> {
+        let poll = self
+            .poll_repo
+            .find_by_id(poll_id)
+            .await?
+            .ok_or(PollError::NotFound)?;
+        if !self.room_repo.can_read(&user.id, &poll.room_id).await? {
+            return Err(PollError::NotFound);
+        }
+        Ok(poll)
+    }
+
+    async fn filter(&self, room_id: &str, content: &str) -> PollResult<(String, Vec<FilterFlag>)> {
+        match self.content_filter.check(room_id, content).await? {
+            FilterDecision::Allow { content, flags } => Ok((content, flags)),
+            FilterDecision::Reject { reason, .. } => {
+                Err(PollError::Invalid(format!("投票被拒绝: {}", reason)))
+            }
+        }
+    }
+
+    async fn publish_update(&self, room_id: &str, info: &PollInfo) {
+        let event = WebSocketMessage::PollUpdated {
+            room_id: room_id.to_string(),
+            poll: info.clone(),
+        };
+        if let Err(e) = self.event_bus.publish(room_id, event).await {
+            eprintln!("广播投票更新失败: {}", e);
+        }
+    }
+}
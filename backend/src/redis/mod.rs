@@ -1,5 +1,11 @@
 pub mod connection;
+pub mod presence_manager;
+pub mod rate_limiter;
 pub mod session_manager;
+pub mod typing_manager;
 
 pub use connection::*;
+pub use presence_manager::*;
+pub use rate_limiter::*;
 pub use session_manager::*;
+pub use typing_manager::*;
@@ -0,0 +1,113 @@
+use redis::{AsyncCommands, Client, RedisResult};
+
+/// 连接在最后一次心跳后仍被视为存活的时间
+pub const PRESENCE_TTL_MS: i64 = 60_000;
+
+const PRESENCE_USERS_KEY: &str = "presence_users";
+
+/// 在线状态管理器，按连接记录心跳，一个用户的多个设备互不影响
+#[derive(Clone)]
+pub struct PresenceManager {
+    client: Client,
+}
+
+impl PresenceManager {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// 记录连接心跳，返回true表示该用户此前没有任何存活连接
+    pub async fn touch_connection(&self, user_id: &str, connection_id: &str) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let connections_key = format!("presence:conns:{}", user_id);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let (previous, added): (i64, i64) = redis::pipe()
+            .atomic()
+            .zcard(&connections_key)
+            .zadd(&connections_key, connection_id, now)
+            .sadd(PRESENCE_USERS_KEY, user_id)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(previous == 0 && added > 0)
+    }
+
+    /// 移除连接，返回true表示这是该用户最后一个存活连接
+    pub async fn remove_connection(&self, user_id: &str, connection_id: &str) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let connections_key = format!("presence:conns:{}", user_id);
+
+        let (removed, remaining): (i64, i64) = redis::pipe()
+            .atomic()
+            .zrem(&connections_key, connection_id)
+            .zcard(&connections_key)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(removed > 0 && remaining == 0)
+    }
+
+    /// 用户是否还有存活连接
+    pub async fn is_online(&self, user_id: &str) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let connections_key = format!("presence:conns:{}", user_id);
+
+        let count: i64 = conn.zcard(&connections_key).await?;
+        Ok(count > 0)
+    }
+
+    /// 清理心跳超时的连接，返回因此变为离线的用户
+    ///
+    /// 每次移除和计数在同一个事务中完成，多个实例同时扫描时
+    /// 只有移除最后一个连接的实例会得到该用户。
+    pub async fn take_expired(&self) -> RedisResult<Vec<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let cutoff = chrono::Utc::now().timestamp_millis() - PRESENCE_TTL_MS;
+
+        let users: Vec<String> = conn.smembers(PRESENCE_USERS_KEY).await?;
+        let mut offline_users = Vec::new();
+
+        for user_id in users {
+            let connections_key = format!("presence:conns:{}", user_id);
+            let stale: Vec<String> = conn.zrangebyscore(&connections_key, "-inf", cutoff).await?;
+
+            for connection_id in stale {
+                let (removed, remaining): (i64, i64) = redis::pipe()
+                    .atomic()
+                    .zrem(&connections_key, &connection_id)
+                    .zcard(&connections_key)
+                    .query_async(&mut conn)
+                    .await?;
+
+                if removed > 0 && remaining == 0 {
+                    offline_users.push(user_id.clone());
+                }
+            }
+
+            let remaining: i64 = conn.zcard(&connections_key).await?;
+            if remaining == 0 {
+                conn.srem::<_, _, ()>(PRESENCE_USERS_KEY, &user_id).await?;
+            }
+        }
+
+        Ok(offline_users)
+    }
+
+    pub async fn set_status(&self, user_id: &str, status: &str) -> RedisResult<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        let status_key = format!("presence:status:{}", user_id);
+
+        conn.set::<_, _, ()>(&status_key, status).await?;
+        Ok(())
+    }
+
+    pub async fn get_status(&self, user_id: &str) -> RedisResult<Option<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let status_key = format!("presence:status:{}", user_id);
+
+        let status: Option<String> = conn.get(&status_key).await?;
+        Ok(status)
+    }
+}
@@ -0,0 +1,198 @@
+use redis::{Client, RedisResult, Script};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 令牌桶脚本，使用Redis服务器时间，保证多实例共享同一个桶
+///
+/// 返回 {是否允许, 需要等待的毫秒数}
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(bucket[1]) or capacity
+local ts = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - ts) * rate)
+
+local allowed = 0
+local retry_after = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+else
+    retry_after = math.ceil((1 - tokens) / rate)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tokens, 'ts', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / rate) + 1000)
+return {allowed, retry_after}
+"#;
+
+/// 令牌桶参数：最多积累capacity个令牌，每秒补充refill_per_sec个
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub refill_per_sec: f64,
+}
+
+impl RateLimit {
+    pub const fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+        }
+    }
+
+    /// 解析`容量/每秒补充数`格式，如`5/0.1`
+    fn parse(value: &str) -> Option<Self> {
+        let (capacity, refill) = value.trim().split_once('/')?;
+        let limit = Self::new(capacity.trim().parse().ok()?, refill.trim().parse().ok()?);
+        (limit.capacity > 0 && limit.refill_per_sec > 0.0).then_some(limit)
+    }
+}
+
+/// 默认限流配置，可以通过RATE_LIMITS环境变量按操作覆盖
+///
+/// - login/register/upload/search：按IP限制的HTTP和gRPC接口
+/// - send_message：按用户限制，WebSocket、HTTP和gRPC共用同一个桶
+/// - ws_frame：每个WebSocket连接收到的所有帧
+/// - slash_command：按用户限制斜杠命令，三种发送方式共用
+const DEFAULT_LIMITS: &[(&str, RateLimit)] = &[
+    ("login", RateLimit::new(5, 0.1)),
+    ("register", RateLimit::new(3, 0.02)),
+    ("upload", RateLimit::new(10, 0.2)),
+    ("search", RateLimit::new(10, 1.0)),
+    ("send_message", RateLimit::new(10, 1.0)),
+    ("join_room", RateLimit::new(10, 0.5)),
+    ("ws_frame", RateLimit::new(60, 20.0)),
+    ("slash_command", RateLimit::new(5, 0.2)),
+];
+
+/// 限流结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed,
+    Limited { retry_after: Duration },
+}
+
+impl RateLimitDecision {
+    /// 客户端需要等待的秒数，向上取整
+    pub fn retry_after_secs(&self) -> u64 {
+        match self {
+            RateLimitDecision::Allowed => 0,
+            RateLimitDecision::Limited { retry_after } => {
+                retry_after.as_millis().div_ceil(1000).max(1) as u64
+            }
+        }
+    }
+}
+
+/// 基于Redis令牌桶的分布式限流器，按操作和用户/IP分桶
+#[derive(Clone)]
+pub struct RateLimiter {
+    client: Client,
+    limits: Arc<HashMap<String, RateLimit>>,
+    script: Arc<Script>,
+}
+
+impl RateLimiter {
+    pub fn new(client: Client, limits: HashMap<String, RateLimit>) -> Self {
+        Self {
+            client,
+            limits: Arc::new(limits),
+            script: Arc::new(Script::new(TOKEN_BUCKET_SCRIPT)),
+        }
+    }
+
+    /// 使用默认配置，并用RATE_LIMITS覆盖，格式如`login=5/0.1,send_message=20/2`
+    pub fn from_env(client: Client) -> Self {
+        let mut limits: HashMap<String, RateLimit> = DEFAULT_LIMITS
+            .iter()
+            .map(|(operation, limit)| (operation.to_string(), *limit))
+            .collect();
+
+        if let Ok(config) = std::env::var("RATE_LIMITS") {
+            for item in config.split(',').filter(|item| !item.trim().is_empty()) {
+                match item.split_once('=').and_then(|(operation, value)| {
+                    RateLimit::parse(value).map(|limit| (operation.trim().to_string(), limit))
+                }) {
+                    Some((operation, limit)) => {
+                        limits.insert(operation, limit);
+                    }
+                    None => eprintln!("忽略无效的限流配置: {}", item),
+                }
+            }
+        }
+
+        Self::new(client, limits)
+    }
+
+    /// 按用户限流
+    pub async fn check_user(
+        &self,
+        operation: &str,
+        user_id: &str,
+    ) -> RedisResult<RateLimitDecision> {
+        self.check(operation, &format!("user:{}", user_id)).await
+    }
+
+    /// 按客户端IP限流
+    pub async fn check_ip(&self, operation: &str, ip: &str) -> RedisResult<RateLimitDecision> {
+        self.check(operation, &format!("ip:{}", ip)).await
+    }
+
+    /// 消耗一个令牌，没有配置限流的操作直接放行
+    pub async fn check(&self, operation: &str, key: &str) -> RedisResult<RateLimitDecision> {
+        let Some(limit) = self.limits.get(operation) else {
+            return Ok(RateLimitDecision::Allowed);
+        };
+
+        let mut conn = self.client.get_async_connection().await?;
+        let (allowed, retry_after_ms): (i64, i64) = self
+            .script
+            .key(format!("rate_limit:{}:{}", operation, key))
+            .arg(limit.capacity)
+            .arg(limit.refill_per_sec / 1000.0)
+            .invoke_async(&mut conn)
+            .await?;
+
+        if allowed == 1 {
+            Ok(RateLimitDecision::Allowed)
+        } else {
+            Ok(RateLimitDecision::Limited {
+                retry_after: Duration::from_millis(retry_after_ms.max(1) as u64),
+            })
+        }
+    }
+
+    /// 房间慢速模式：同一用户在房间内两次发言至少间隔interval
+    pub async fn check_slow_mode(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        interval: Duration,
+    ) -> RedisResult<RateLimitDecision> {
+        let mut conn = self.client.get_async_connection().await?;
+        let key = format!("slow_mode:{}:{}", room_id, user_id);
+
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(1)
+            .arg("NX")
+            .arg("PX")
+            .arg(interval.as_millis() as u64)
+            .query_async(&mut conn)
+            .await?;
+        if acquired.is_some() {
+            return Ok(RateLimitDecision::Allowed);
+        }
+
+        let ttl_ms: i64 = redis::cmd("PTTL").arg(&key).query_async(&mut conn).await?;
+        Ok(RateLimitDecision::Limited {
+            retry_after: Duration::from_millis(ttl_ms.max(1) as u64),
+        })
+    }
+}
@@ -116,12 +116,27 @@ impl SessionManager {
         Ok(())
     }
 
+    /// 删除用户当前的会话，返回被删除的会话ID
+    pub async fn delete_user_session(&self, user_id: &str) -> RedisResult<Option<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let session_id: Option<String> = conn.get(format!("user_session:{}", user_id)).await?;
+
+        if let Some(session_id) = &session_id {
+            self.delete_session(session_id).await?;
+        }
+
+        Ok(session_id)
+    }
+
     pub async fn add_user_to_room(&self, user_id: &str, room_id: &str) -> RedisResult<()> {
         let mut conn = self.client.get_async_connection().await?;
         let room_users_key = format!("room_users:{}", room_id);
+        let user_rooms_key = format!("user_rooms:{}", user_id);
 
         conn.sadd::<_, _, ()>(&room_users_key, user_id).await?;
         conn.expire::<_, ()>(&room_users_key, 60 * 60).await?; // 1小时过期
+        conn.sadd::<_, _, ()>(&user_rooms_key, room_id).await?;
+        conn.expire::<_, ()>(&user_rooms_key, 60 * 60).await?;
 
         Ok(())
     }
@@ -129,12 +144,65 @@ impl SessionManager {
     pub async fn remove_user_from_room(&self, user_id: &str, room_id: &str) -> RedisResult<()> {
         let mut conn = self.client.get_async_connection().await?;
         let room_users_key = format!("room_users:{}", room_id);
+        let user_rooms_key = format!("user_rooms:{}", user_id);
 
         conn.srem::<_, _, ()>(&room_users_key, user_id).await?;
+        conn.srem::<_, _, ()>(&user_rooms_key, room_id).await?;
 
         Ok(())
     }
 
+    /// 记录某个连接加入房间，同一用户的多个连接分别记录
+    pub async fn join_room_connection(
+        &self,
+        user_id: &str,
+        room_id: &str,
+        connection_id: &str,
+    ) -> RedisResult<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        let room_connections_key = format!("room_conns:{}:{}", room_id, user_id);
+
+        conn.sadd::<_, _, ()>(&room_connections_key, connection_id)
+            .await?;
+        conn.expire::<_, ()>(&room_connections_key, 60 * 60).await?;
+
+        self.add_user_to_room(user_id, room_id).await
+    }
+
+    /// 记录某个连接离开房间，返回true表示该用户已没有连接留在房间中
+    pub async fn leave_room_connection(
+        &self,
+        user_id: &str,
+        room_id: &str,
+        connection_id: &str,
+    ) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let room_connections_key = format!("room_conns:{}:{}", room_id, user_id);
+
+        let (_, remaining): (i64, i64) = redis::pipe()
+            .atomic()
+            .srem(&room_connections_key, connection_id)
+            .scard(&room_connections_key)
+            .query_async(&mut conn)
+            .await?;
+
+        if remaining > 0 {
+            return Ok(false);
+        }
+
+        self.remove_user_from_room(user_id, room_id).await?;
+        Ok(true)
+    }
+
+    /// 获取用户当前所在的房间
+    pub async fn get_user_rooms(&self, user_id: &str) -> RedisResult<Vec<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let user_rooms_key = format!("user_rooms:{}", user_id);
+
+        let rooms: Vec<String> = conn.smembers(&user_rooms_key).await?;
+        Ok(rooms)
+    }
+
     pub async fn get_room_users(&self, room_id: &str) -> RedisResult<Vec<String>> {
         let mut conn = self.client.get_async_connection().await?;
         let room_users_key = format!("room_users:{}", room_id);
@@ -147,8 +215,8 @@ impl SessionManager {
         let mut conn = self.client.get_async_connection().await?;
         let online_users_key = "online_users";
 
+        // 由在线状态跟踪器在连接建立和断开时维护，不再设置整体过期时间
         conn.sadd::<_, _, ()>(online_users_key, user_id).await?;
-        conn.expire::<_, ()>(online_users_key, 60 * 60).await?; // 1小时过期
 
         Ok(())
     }
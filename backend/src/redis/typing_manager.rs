@@ -0,0 +1,133 @@
+use redis::{AsyncCommands, Client, RedisResult, Script};
+use std::sync::Arc;
+
+/// 输入状态的存活时间，客户端需要在此之前刷新
+pub const TYPING_TTL_MS: i64 = 6_000;
+/// 同一用户在同一房间内两次输入事件之间的最小间隔
+pub const TYPING_THROTTLE_MS: i64 = 2_000;
+
+const TYPING_ROOMS_KEY: &str = "typing_rooms";
+
+/// 房间没有正在输入的用户时从typing_rooms中移除，检查和移除在同一个脚本中完成，
+/// 避免与其他实例的mark_typing交错导致房间被误删
+const REMOVE_IDLE_ROOM_SCRIPT: &str = r#"
+if redis.call('ZCARD', KEYS[1]) == 0 then
+    return redis.call('SREM', KEYS[2], ARGV[1])
+end
+return 0
+"#;
+
+/// 输入状态管理器，使用Redis保存各房间正在输入的用户，保证多实例间一致
+#[derive(Clone)]
+pub struct TypingManager {
+    client: Client,
+    remove_idle_room: Arc<Script>,
+}
+
+impl TypingManager {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            remove_idle_room: Arc::new(Script::new(REMOVE_IDLE_ROOM_SCRIPT)),
+        }
+    }
+
+    /// 尝试获取节流窗口，返回false表示该用户发送过于频繁
+    pub async fn try_acquire_throttle(&self, room_id: &str, user_id: &str) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let throttle_key = format!("typing_throttle:{}:{}", room_id, user_id);
+
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(&throttle_key)
+            .arg(1)
+            .arg("NX")
+            .arg("PX")
+            .arg(TYPING_THROTTLE_MS)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(acquired.is_some())
+    }
+
+    /// 标记用户正在输入并刷新过期时间，返回true表示是新的输入状态
+    pub async fn mark_typing(&self, room_id: &str, user_id: &str) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let typing_key = format!("typing:{}", room_id);
+        let expires_at = chrono::Utc::now().timestamp_millis() + TYPING_TTL_MS;
+
+        let (added,): (i64,) = redis::pipe()
+            .zadd(&typing_key, user_id, expires_at)
+            .expire(&typing_key, 60)
+            .ignore()
+            .sadd(TYPING_ROOMS_KEY, room_id)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(added > 0)
+    }
+
+    /// 清除用户的输入状态，返回true表示状态确实被当前调用清除
+    pub async fn clear_typing(&self, room_id: &str, user_id: &str) -> RedisResult<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let typing_key = format!("typing:{}", room_id);
+
+        let removed: i64 = conn.zrem(&typing_key, user_id).await?;
+        if removed > 0 {
+            self.remove_if_idle(&mut conn, room_id).await?;
+        }
+        Ok(removed > 0)
+    }
+
+    /// 获取房间内正在输入的用户
+    pub async fn get_typing_users(&self, room_id: &str) -> RedisResult<Vec<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let typing_key = format!("typing:{}", room_id);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let users: Vec<String> = conn.zrangebyscore(&typing_key, now, "+inf").await?;
+        Ok(users)
+    }
+
+    /// 取出所有已过期的输入状态
+    ///
+    /// 多个实例可能同时扫描，只有成功执行ZREM的实例会得到对应条目，
+    /// 因此每个过期事件只会被处理一次。
+    pub async fn take_expired(&self) -> RedisResult<Vec<(String, String)>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let rooms: Vec<String> = conn.smembers(TYPING_ROOMS_KEY).await?;
+        let mut expired = Vec::new();
+
+        for room_id in rooms {
+            let typing_key = format!("typing:{}", room_id);
+            let users: Vec<String> = conn.zrangebyscore(&typing_key, "-inf", now).await?;
+
+            for user_id in users {
+                let removed: i64 = conn.zrem(&typing_key, &user_id).await?;
+                if removed > 0 {
+                    expired.push((room_id.clone(), user_id));
+                }
+            }
+            self.remove_if_idle(&mut conn, &room_id).await?;
+        }
+
+        Ok(expired)
+    }
+
+    async fn remove_if_idle(
+        &self,
+        conn: &mut redis::aio::Connection,
+        room_id: &str,
+    ) -> RedisResult<()> {
+        let _: i64 = self
+            .remove_idle_room
+            .key(format!("typing:{}", room_id))
+            .key(TYPING_ROOMS_KEY)
+            .arg(room_id)
+            .invoke_async(conn)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::database::{MessageRepository, ScheduledMessageRepository, UserRepository};
+use crate::models::{Message, MessageType, ScheduledMessage};
+use crate::moderation::{ContentFilter, FilterDecision, SendGuard, SendRejection};
+use crate::unfurl::LinkUnfurler;
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 检查到期定时消息的间隔
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(5);
+/// 每轮最多发送的定时消息数
+const DISPATCH_BATCH_SIZE: i64 = 50;
+/// 认领后超过该时间仍未完成，认为认领的实例已经退出
+const STALE_CLAIM_SECONDS: i64 = 5 * 60;
+
+/// 定时消息发送任务
+///
+/// 定时消息保存在数据库中，重启后继续发送。多个实例同时运行时通过条件更新认领，
+/// 消息ID在创建定时消息时就已确定，即使认领超时被重新发送，消息表的主键也能保证只发送一次。
+pub struct ScheduledDispatcher {
+    instance_id: String,
+    scheduled_repo: Arc<ScheduledMessageRepository>,
+    message_repo: Arc<MessageRepository>,
+    user_repo: Arc<UserRepository>,
+    send_guard: SendGuard,
+    content_filter: ContentFilter,
+    link_unfurler: LinkUnfurler,
+    event_bus: RoomEventBus,
+}
+
+impl ScheduledDispatcher {
+    pub fn new(
+        scheduled_repo: Arc<ScheduledMessageRepository>,
+        message_repo: Arc<MessageRepository>,
+        user_repo: Arc<UserRepository>,
+        send_guard: SendGuard,
+        content_filter: ContentFilter,
+        link_unfurler: LinkUnfurler,
+        event_bus: RoomEventBus,
+    ) -> Self {
+        Self {
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            scheduled_repo,
+            message_repo,
+            user_repo,
+            send_guard,
+            content_filter,
+            link_unfurler,
+            event_bus,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.recover_stale().await {
+                eprintln!("恢复超时的定时消息失败: {}", e);
+            }
+            if let Err(e) = self.dispatch_due().await {
+                eprintln!("发送定时消息失败: {}", e);
+            }
+        }
+    }
+
+    async fn dispatch_due(&self) -> Result<(), sqlx::Error> {
+        for scheduled in self.scheduled_repo.find_due(DISPATCH_BATCH_SIZE).await? {
+            if !self
+                .scheduled_repo
+                .claim(&scheduled.id, &self.instance_id)
+                .await?
+            {
+                // 已被其他实例认领，或刚被用户修改/取消
+                continue;
+            }
+            if let Err(e) = self.dispatch(&scheduled).await {
+                // 保持sending状态，超时后由recover_stale根据消息是否已保存决定是否重发
+                eprintln!("发送定时消息 {} 失败: {}", scheduled.id, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 与普通消息走同样的检查、过滤和广播
+    async fn dispatch(&self, scheduled: &ScheduledMessage) -> Result<(), sqlx::Error> {
+        let Some(user) = self.user_repo.find_by_id(&scheduled.user_id).await? else {
+            return self
+                .scheduled_repo
+                .mark_failed(&scheduled.id, "用户不存在")
+                .await;
+        };
+
+        match self
+            .send_guard
+            .check(&user, &scheduled.room_id, &scheduled.content)
+            .await?
+        {
+            Some(SendRejection::RateLimited {
+                retry_after_secs, ..
+            }) => {
+                let retry_at =
+                    chrono::Utc::now() + chrono::Duration::seconds(retry_after_secs.max(1) as i64);
+                return self.scheduled_repo.release(&scheduled.id, retry_at).await;
+            }
+            Some(rejection) => {
+                return self
+                    .scheduled_repo
+                    .mark_failed(&scheduled.id, &rejection.message())
+                    .await;
+            }
+            None => {}
+        }
+
+        let (content, flags) = match self
+            .content_filter
+            .check(&scheduled.room_id, &scheduled.content)
+            .await?
+        {
+            FilterDecision::Allow { content, flags } => (content, flags),
+            FilterDecision::Reject { reason, .. } => {
+                return self
+                    .scheduled_repo
+                    .mark_failed(&scheduled.id, &format!("消息被拒绝: {}", reason))
+                    .await;
+            }
+        };
+
+        let mut message = Message::new(
+            user.id.clone(),
+            user.username.clone(),
+            content,
+            scheduled.room_id.clone(),
+            MessageType::Text,
+        );
+        message.id = scheduled.message_id.clone();
+        let message = match self.message_repo.create(message).await {
+            Ok(message) => message,
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                // 之前的实例已经保存过这条消息
+                return self.scheduled_repo.mark_sent(&scheduled.id).await;
+            }
+            Err(e) => return Err(e),
+        };
+        self.scheduled_repo.mark_sent(&scheduled.id).await?;
+
+        if let Err(e) = self.content_filter.record_flags(&message, &flags).await {
+            eprintln!("保存过滤标记失败: {}", e);
+        }
+        self.link_unfurler.enqueue(&message);
+
+        let event = WebSocketMessage::ChatMessage {
+            message_id: Some(message.id.clone()),
+            room_id: message.room_id.clone(),
+            user_id: message.user_id.clone(),
+            username: message.username.clone(),
+            content: message.content.clone(),
+            content_html: message.content_html.clone(),
+            message_type: message.message_type.to_string(),
+            attachment_ids: Vec::new(),
+            attachments: Vec::new(),
+            poll: None,
+            ttl_seconds: None,
+            expires_at: message.expires_at.map(|t| t.timestamp()),
+            reference: None,
+        };
+        if let Err(e) = self.event_bus.publish(&message.room_id, event).await {
+            eprintln!("广播定时消息失败: {}", e);
+        }
+        println!("定时消息 {} 已发送到房间 {}", scheduled.id, message.room_id);
+
+        Ok(())
+    }
+
+    /// 处理认领后长时间没有完成的定时消息：消息已保存的标记为已发送，否则放回队列
+    async fn recover_stale(&self) -> Result<(), sqlx::Error> {
+        let claimed_before = chrono::Utc::now() - chrono::Duration::seconds(STALE_CLAIM_SECONDS);
+        for scheduled in self
+            .scheduled_repo
+            .find_stale_sending(claimed_before)
+            .await?
+        {
+            if self.message_repo.exists(&scheduled.message_id).await? {
+                self.scheduled_repo.mark_sent(&scheduled.id).await?;
+            } else {
+                println!("定时消息 {} 认领超时，重新放回队列", scheduled.id);
+                self.scheduled_repo
+                    .release(&scheduled.id, chrono::Utc::now())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod dispatcher;
+pub mod service;
+
+pub use dispatcher::*;
+pub use service::*;
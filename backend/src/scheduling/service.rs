@@ -0,0 +1,169 @@
+use crate::database::{RoomRepository, ScheduledMessageRepository};
+use crate::models::{
+    MAX_PENDING_SCHEDULED_MESSAGES, MAX_SCHEDULE_AHEAD_DAYS, ScheduleMessage, ScheduledMessage,
+    ScheduledStatus, UpdateScheduledMessage, User,
+};
+use std::sync::Arc;
+
+/// 每页最多返回的定时消息数
+const MAX_SCHEDULED_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("定时消息不存在")]
+    NotFound,
+    #[error("定时消息已发送或已取消")]
+    Conflict,
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub type ScheduleResult<T> = Result<T, ScheduleError>;
+
+/// 定时消息的创建、查询、修改和取消，发送由`ScheduledDispatcher`负责
+#[derive(Clone)]
+pub struct ScheduledMessageService {
+    scheduled_repo: Arc<ScheduledMessageRepository>,
+    room_repo: Arc<RoomRepository>,
+}
+
+impl ScheduledMessageService {
+    pub fn new(
+        scheduled_repo: Arc<ScheduledMessageRepository>,
+        room_repo: Arc<RoomRepository>,
+    ) -> Self {
+        Self {
+            scheduled_repo,
+            room_repo,
+        }
+    }
+
+    pub async fn schedule(
+        &self,
+        user: &User,
+        room_id: &str,
+        req: ScheduleMessage,
+    ) -> ScheduleResult<ScheduledMessage> {
+        let content = validate_content(&req.content)?;
+        let send_at = validate_send_at(req.send_at)?;
+        if !self.room_repo.can_read(&user.id, room_id).await? {
+            return Err(ScheduleError::Invalid("房间不存在".to_string()));
+        }
+        if self.scheduled_repo.count_pending(&user.id).await? >= MAX_PENDING_SCHEDULED_MESSAGES {
+            return Err(ScheduleError::Invalid(format!(
+                "最多只能有{}条待发送的定时消息",
+                MAX_PENDING_SCHEDULED_MESSAGES
+            )));
+        }
+
+        let scheduled =
+            ScheduledMessage::new(user.id.clone(), room_id.to_string(), content, send_at);
+        self.scheduled_repo.create(&scheduled).await?;
+        println!(
+            "用户 {} 安排了定时消息 {}，发送时间 {}",
+            user.id, scheduled.id, scheduled.send_at
+        );
+
+        Ok(scheduled)
+    }
+
+    /// 用户自己的定时消息，status为空时只列出待发送的
+    pub async fn list(
+        &self,
+        user: &User,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> ScheduleResult<Vec<ScheduledMessage>> {
+        let status = match status.filter(|s| !s.is_empty()) {
+            Some("all") => None,
+            Some(status) => Some(ScheduledStatus::parse(status).ok_or_else(|| {
+                ScheduleError::Invalid(format!("无效的定时消息状态: {}", status))
+            })?),
+            None => Some(ScheduledStatus::Pending),
+        };
+
+        let limit = if limit <= 0 {
+            50
+        } else {
+            limit.min(MAX_SCHEDULED_PAGE_SIZE)
+        };
+        Ok(self
+            .scheduled_repo
+            .list_by_user(&user.id, status.map(|s| s.as_str()), limit, offset.max(0))
+            .await?)
+    }
+
+    /// 修改内容或发送时间，只能修改还未开始发送的
+    pub async fn update(
+        &self,
+        user: &User,
+        id: &str,
+        req: UpdateScheduledMessage,
+    ) -> ScheduleResult<ScheduledMessage> {
+        let scheduled = self.find_own(user, id).await?;
+
+        let content = match req.content {
+            Some(content) => validate_content(&content)?,
+            None => scheduled.content,
+        };
+        let send_at = match req.send_at {
+            Some(send_at) => validate_send_at(send_at)?,
+            None => scheduled.send_at,
+        };
+        if !self
+            .scheduled_repo
+            .update_pending(id, &user.id, &content, send_at)
+            .await?
+        {
+            return Err(ScheduleError::Conflict);
+        }
+
+        self.scheduled_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(ScheduleError::NotFound)
+    }
+
+    pub async fn cancel(&self, user: &User, id: &str) -> ScheduleResult<ScheduledMessage> {
+        self.find_own(user, id).await?;
+        if !self.scheduled_repo.cancel(id, &user.id).await? {
+            return Err(ScheduleError::Conflict);
+        }
+
+        self.scheduled_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(ScheduleError::NotFound)
+    }
+
+    /// 别人的定时消息按不存在处理
+    async fn find_own(&self, user: &User, id: &str) -> ScheduleResult<ScheduledMessage> {
+        match self.scheduled_repo.find_by_id(id).await? {
+            Some(scheduled) if scheduled.user_id == user.id => Ok(scheduled),
+            _ => Err(ScheduleError::NotFound),
+        }
+    }
+}
+
+fn validate_content(content: &str) -> ScheduleResult<String> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Err(ScheduleError::Invalid("消息内容不能为空".to_string()));
+    }
+    Ok(content.to_string())
+}
+
+fn validate_send_at(send_at: i64) -> ScheduleResult<chrono::DateTime<chrono::Utc>> {
+    let now = chrono::Utc::now();
+    chrono::DateTime::from_timestamp(send_at, 0)
+        .filter(|t| *t > now && *t <= now + chrono::Duration::days(MAX_SCHEDULE_AHEAD_DAYS))
+        .ok_or_else(|| {
+            ScheduleError::Invalid(format!(
+                "发送时间必须在{}天以内的将来",
+                MAX_SCHEDULE_AHEAD_DAYS
+            ))
+        })
+}
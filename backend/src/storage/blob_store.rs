@@ -0,0 +1,60 @@
+use super::{LocalBlobStore, S3BlobStore, S3Config};
+use bytes::Bytes;
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("对象不存在: {0}")]
+    NotFound(String),
+    #[error("无效的存储键: {0}")]
+    InvalidKey(String),
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("存储服务错误: {0}")]
+    Backend(String),
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// 二进制对象存储，附件、缩略图等文件都通过它读写
+#[async_trait::async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> StorageResult<()>;
+
+    async fn get(&self, key: &str) -> StorageResult<Bytes>;
+
+    async fn delete(&self, key: &str) -> StorageResult<()>;
+}
+
+/// 存储键只允许字母、数字和`-_./`，且不能包含`..`，避免路径穿越
+pub fn validate_key(key: &str) -> StorageResult<()> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && !key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "..")
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+/// 根据环境变量创建存储后端：STORAGE_BACKEND=local（默认）或 s3
+pub fn create_blob_store() -> Result<Arc<dyn BlobStore>, Box<dyn std::error::Error>> {
+    let backend = std::env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+
+    match backend.as_str() {
+        "local" => {
+            let root =
+                std::env::var("STORAGE_LOCAL_PATH").unwrap_or_else(|_| "./data/blobs".to_string());
+            Ok(Arc::new(LocalBlobStore::new(root)))
+        }
+        "s3" => Ok(Arc::new(S3BlobStore::new(S3Config::from_env()?))),
+        other => Err(format!("不支持的存储后端: {}", other).into()),
+    }
+}
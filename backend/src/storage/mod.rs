@@ -0,0 +1,7 @@
+pub mod blob_store;
+pub mod local_store;
+pub mod s3_store;
+
+pub use blob_store::*;
+pub use local_store::*;
+pub use s3_store::*;
@@ -0,0 +1,184 @@
+use super::{BlobStore, StorageError, StorageResult, validate_key};
+use bytes::Bytes;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// S3兼容存储的连接配置，endpoint可以指向AWS S3或本地MinIO
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl S3Config {
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let required =
+            |name: &str| std::env::var(name).map_err(|_| format!("缺少S3配置环境变量: {}", name));
+
+        Ok(Self {
+            endpoint: std::env::var("S3_ENDPOINT")
+                .unwrap_or_else(|_| "http://localhost:9000".to_string()),
+            bucket: required("S3_BUCKET")?,
+            region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            access_key: required("S3_ACCESS_KEY")?,
+            secret_key: required("S3_SECRET_KEY")?,
+        })
+    }
+}
+
+/// S3兼容存储，使用path-style地址和SigV4签名，兼容MinIO
+pub struct S3BlobStore {
+    config: S3Config,
+    client: Client,
+}
+
+impl S3BlobStore {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        body: Bytes,
+        content_type: Option<&str>,
+    ) -> StorageResult<reqwest::Response> {
+        validate_key(key)?;
+
+        let path = format!("/{}/{}", self.config.bucket, key);
+        let url = Url::parse(&format!(
+            "{}{}",
+            self.config.endpoint.trim_end_matches('/'),
+            path
+        ))
+        .map_err(|e| StorageError::Backend(format!("无效的S3地址: {}", e)))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => return Err(StorageError::Backend("S3地址缺少主机名".to_string())),
+        };
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let authorization = self.authorization(
+            method.as_str(),
+            &path,
+            &host,
+            &amz_date,
+            &date,
+            &payload_hash,
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("host", host)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization);
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+
+        request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))
+    }
+
+    /// 计算AWS SigV4签名，签名头只包含host和x-amz-*
+    fn authorization(
+        &self,
+        method: &str,
+        path: &str,
+        host: &str,
+        amz_date: &str,
+        date: &str,
+        payload_hash: &str,
+    ) -> String {
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [date, self.config.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.config.secret_key).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key, scope, signed_headers, signature
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> StorageResult<()> {
+        let response = self
+            .send(Method::PUT, key, data, Some(content_type))
+            .await?;
+        check_status(key, response).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> StorageResult<Bytes> {
+        let response = self.send(Method::GET, key, Bytes::new(), None).await?;
+        let response = check_status(key, response).await?;
+        response
+            .bytes()
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        let response = self.send(Method::DELETE, key, Bytes::new(), None).await?;
+        // S3删除不存在的对象同样返回成功
+        check_status(key, response).await?;
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC可以接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+async fn check_status(key: &str, response: reqwest::Response) -> StorageResult<reqwest::Response> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND => Err(StorageError::NotFound(key.to_string())),
+        status => {
+            let body = response.text().await.unwrap_or_default();
+            Err(StorageError::Backend(format!("S3返回{}: {}", status, body)))
+        }
+    }
+}
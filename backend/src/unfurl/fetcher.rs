@@ -0,0 +1,231 @@
+use reqwest::Url;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+/// 请求超时（包括读取响应体）
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// 最多读取的页面大小，OpenGraph标签都在head中，超出部分直接丢弃
+pub const MAX_PAGE_BYTES: usize = 1024 * 1024;
+/// 最多跟随的重定向次数，每一跳都会重新做地址检查
+pub const MAX_REDIRECTS: usize = 3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnfurlError {
+    #[error("无效的链接: {0}")]
+    InvalidUrl(String),
+    #[error("禁止访问的地址: {0}")]
+    BlockedAddress(String),
+    #[error("请求失败: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("响应状态异常: {0}")]
+    Status(u16),
+    #[error("不支持的内容类型: {0}")]
+    UnsupportedContentType(String),
+    #[error("重定向次数过多")]
+    TooManyRedirects,
+}
+
+pub type UnfurlResult<T> = Result<T, UnfurlError>;
+
+/// 抓取到的页面，url为跟随重定向后的最终地址
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    pub url: Url,
+    pub html: String,
+}
+
+/// 页面抓取器，测试时可以替换为本地实现
+#[async_trait::async_trait]
+pub trait PageFetcher: Send + Sync {
+    async fn fetch(&self, url: &Url) -> UnfurlResult<FetchedPage>;
+}
+
+/// 基于HTTP的页面抓取器
+///
+/// 只访问解析到公网地址的http(s)链接，并把连接固定到检查过的地址，
+/// 防止通过DNS重绑定或重定向访问内网服务。
+pub struct HttpPageFetcher {
+    timeout: Duration,
+    max_page_bytes: usize,
+}
+
+impl HttpPageFetcher {
+    pub fn new() -> Self {
+        Self {
+            timeout: FETCH_TIMEOUT,
+            max_page_bytes: MAX_PAGE_BYTES,
+        }
+    }
+
+    /// 每次请求单独创建客户端，把域名解析固定到已检查的地址
+    fn client_for(&self, url: &Url, addr: SocketAddr) -> UnfurlResult<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy()
+            .timeout(self.timeout)
+            .connect_timeout(self.timeout)
+            .user_agent("ChatLinkPreview/1.0");
+
+        if let Some(domain) = url.host_str().filter(|host| host_ip(host).is_none()) {
+            builder = builder.resolve(domain, addr);
+        }
+
+        Ok(builder.build()?)
+    }
+
+    async fn read_body(&self, mut response: reqwest::Response) -> UnfurlResult<Vec<u8>> {
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            let remaining = self.max_page_bytes - body.len();
+            if chunk.len() >= remaining {
+                body.extend_from_slice(&chunk[..remaining]);
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
+
+impl Default for HttpPageFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl PageFetcher for HttpPageFetcher {
+    async fn fetch(&self, url: &Url) -> UnfurlResult<FetchedPage> {
+        let mut current = url.clone();
+
+        for _ in 0..=MAX_REDIRECTS {
+            let addr = resolve_public_addr(&current).await?;
+            let client = self.client_for(&current, addr)?;
+            let response = client.get(current.clone()).send().await?;
+            let status = response.status();
+
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or(UnfurlError::Status(status.as_u16()))?;
+                current = current
+                    .join(location)
+                    .map_err(|e| UnfurlError::InvalidUrl(e.to_string()))?;
+                continue;
+            }
+
+            if !status.is_success() {
+                return Err(UnfurlError::Status(status.as_u16()));
+            }
+
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if !content_type.starts_with("text/html")
+                && !content_type.starts_with("application/xhtml+xml")
+            {
+                return Err(UnfurlError::UnsupportedContentType(content_type));
+            }
+
+            let body = self.read_body(response).await?;
+            return Ok(FetchedPage {
+                url: current,
+                html: String::from_utf8_lossy(&body).into_owned(),
+            });
+        }
+
+        Err(UnfurlError::TooManyRedirects)
+    }
+}
+
+/// 解析链接的目标地址，只要有一个地址不是公网地址就拒绝
+async fn resolve_public_addr(url: &Url) -> UnfurlResult<SocketAddr> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(UnfurlError::InvalidUrl(url.to_string()));
+    }
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| UnfurlError::InvalidUrl(url.to_string()))?;
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| UnfurlError::InvalidUrl(url.to_string()))?;
+
+    let addrs: Vec<SocketAddr> = match host_ip(host) {
+        Some(ip) => vec![SocketAddr::new(ip, port)],
+        None => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| UnfurlError::InvalidUrl(format!("{}: {}", host, e)))?
+            .collect(),
+    };
+
+    if let Some(blocked) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(UnfurlError::BlockedAddress(blocked.ip().to_string()));
+    }
+
+    addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| UnfurlError::InvalidUrl(url.to_string()))
+}
+
+/// 链接中直接写的IP地址，IPv6地址带有方括号
+fn host_ip(host: &str) -> Option<IpAddr> {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// 判断是否为可以访问的公网地址
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // 0.0.0.0/8
+        || octets[0] == 0
+        // 运营商级NAT 100.64.0.0/10
+        || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        // 基准测试 198.18.0.0/15
+        || (octets[0] == 198 && (octets[1] & 0xfe) == 18)
+        // 保留地址 240.0.0.0/4
+        || octets[0] >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(ipv4) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(ipv4);
+    }
+
+    let segments = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // 唯一本地地址 fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // 链路本地 fe80::/10 和已废弃的站点本地 fec0::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] & 0xffc0) == 0xfec0
+        // 文档地址 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // NAT64 64:ff9b::/96 可能映射到内网IPv4
+        || (segments[0] == 0x0064 && segments[1] == 0xff9b))
+}
@@ -0,0 +1,7 @@
+pub mod fetcher;
+pub mod open_graph;
+pub mod worker;
+
+pub use fetcher::*;
+pub use open_graph::*;
+pub use worker::*;
@@ -0,0 +1,55 @@
+use crate::models::LinkPreviewInfo;
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// 从页面中提取OpenGraph信息，缺失时依次回退到Twitter卡片、`<title>`和description
+pub fn parse_open_graph(html: &str, page_url: &Url) -> LinkPreviewInfo {
+    let document = Html::parse_document(html);
+    let meta_selector = Selector::parse("meta").expect("valid selector");
+    let title_selector = Selector::parse("title").expect("valid selector");
+
+    let meta = |keys: &[&str]| -> Option<String> {
+        keys.iter().find_map(|key| {
+            document.select(&meta_selector).find_map(|element| {
+                let element = element.value();
+                let name = element.attr("property").or_else(|| element.attr("name"))?;
+                if !name.eq_ignore_ascii_case(key) {
+                    return None;
+                }
+                non_empty(element.attr("content")?)
+            })
+        })
+    };
+
+    let title = meta(&["og:title", "twitter:title"]).or_else(|| {
+        document
+            .select(&title_selector)
+            .next()
+            .and_then(|element| non_empty(&element.text().collect::<String>()))
+    });
+    let description = meta(&["og:description", "twitter:description", "description"]);
+    // 图片地址可能是相对路径，只保留http(s)链接
+    let image_url = meta(&[
+        "og:image",
+        "og:image:url",
+        "og:image:secure_url",
+        "twitter:image",
+    ])
+    .and_then(|image| page_url.join(&image).ok())
+    .filter(|image| image.scheme() == "http" || image.scheme() == "https")
+    .map(|image| image.to_string());
+    let site_name = meta(&["og:site_name"]).or_else(|| page_url.host_str().map(str::to_string));
+
+    LinkPreviewInfo {
+        url: page_url.to_string(),
+        title,
+        description,
+        image_url,
+        site_name,
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    if value.is_empty() { None } else { Some(value) }
+}
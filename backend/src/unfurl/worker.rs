@@ -0,0 +1,137 @@
+use super::{PageFetcher, parse_open_graph};
+use crate::database::LinkPreviewRepository;
+use crate::models::{LinkPreview, Message, extract_urls};
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use reqwest::Url;
+use std::sync::Arc;
+use tokio::sync::{Semaphore, mpsc};
+
+/// 等待处理的消息数上限，队列满时直接放弃生成预览
+const QUEUE_CAPACITY: usize = 1000;
+/// 同时抓取的消息数
+const MAX_CONCURRENT_JOBS: usize = 4;
+
+#[derive(Debug)]
+struct UnfurlJob {
+    message_id: String,
+    room_id: String,
+    urls: Vec<String>,
+}
+
+/// 链接预览任务入口，消息保存后调用`enqueue`，由后台任务异步抓取
+#[derive(Clone)]
+pub struct LinkUnfurler {
+    sender: mpsc::Sender<UnfurlJob>,
+}
+
+/// 链接预览后台任务，抓取完成后保存预览并向房间发送`message_updated`
+pub struct UnfurlWorker {
+    receiver: mpsc::Receiver<UnfurlJob>,
+    fetcher: Arc<dyn PageFetcher>,
+    preview_repo: Arc<LinkPreviewRepository>,
+    event_bus: RoomEventBus,
+}
+
+impl LinkUnfurler {
+    pub fn new(
+        fetcher: Arc<dyn PageFetcher>,
+        preview_repo: Arc<LinkPreviewRepository>,
+        event_bus: RoomEventBus,
+    ) -> (Self, UnfurlWorker) {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let worker = UnfurlWorker {
+            receiver,
+            fetcher,
+            preview_repo,
+            event_bus,
+        };
+        (Self { sender }, worker)
+    }
+
+    /// 提交消息中的链接，不包含链接的消息直接忽略
+    pub fn enqueue(&self, message: &Message) {
+        let urls = extract_urls(&message.content);
+        if urls.is_empty() {
+            return;
+        }
+
+        let job = UnfurlJob {
+            message_id: message.id.clone(),
+            room_id: message.room_id.clone(),
+            urls,
+        };
+        if let Err(e) = self.sender.try_send(job) {
+            eprintln!("链接预览队列已满，跳过消息 {}: {}", message.id, e);
+        }
+    }
+}
+
+impl UnfurlWorker {
+    pub async fn run(mut self) {
+        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_JOBS));
+        let fetcher = self.fetcher;
+        let preview_repo = self.preview_repo;
+        let event_bus = self.event_bus;
+
+        while let Some(job) = self.receiver.recv().await {
+            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                break;
+            };
+            let fetcher = fetcher.clone();
+            let preview_repo = preview_repo.clone();
+            let event_bus = event_bus.clone();
+
+            tokio::spawn(async move {
+                process_job(job, fetcher.as_ref(), &preview_repo, &event_bus).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+async fn process_job(
+    job: UnfurlJob,
+    fetcher: &dyn PageFetcher,
+    preview_repo: &LinkPreviewRepository,
+    event_bus: &RoomEventBus,
+) {
+    let mut previews = Vec::new();
+
+    for url in job.urls {
+        let Ok(parsed) = Url::parse(&url) else {
+            continue;
+        };
+        let page = match fetcher.fetch(&parsed).await {
+            Ok(page) => page,
+            Err(e) => {
+                println!("抓取链接预览失败 {}: {}", url, e);
+                continue;
+            }
+        };
+
+        // 预览中保留用户发送的原始链接，而不是重定向后的地址
+        let info = parse_open_graph(&page.html, &page.url);
+        let preview = LinkPreview::new(job.message_id.clone(), url, info);
+        if preview.is_empty() {
+            continue;
+        }
+
+        match preview_repo.create(&preview).await {
+            Ok(()) => previews.push(preview),
+            Err(e) => eprintln!("保存链接预览失败: {}", e),
+        }
+    }
+
+    if previews.is_empty() {
+        return;
+    }
+
+    let event = WebSocketMessage::MessageUpdated {
+        room_id: job.room_id.clone(),
+        message_id: job.message_id,
+        link_previews: previews.iter().map(|p| p.to_info()).collect(),
+    };
+    if let Err(e) = event_bus.publish(&job.room_id, event).await {
+        eprintln!("发布消息更新事件失败: {}", e);
+    }
+}
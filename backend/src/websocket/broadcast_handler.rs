@@ -7,12 +7,15 @@ use tokio_tungstenite::tungstenite::Message as WsMessage;
 /// 广播处理器，负责管理房间广播逻辑
 pub struct BroadcastHandler {
     room_channels: HashMap<String, broadcast::Sender<WebSocketMessage>>,
+    // 按用户的通道，发送给该用户在当前实例上的所有连接
+    user_channels: HashMap<String, broadcast::Sender<WebSocketMessage>>,
 }
 
 impl BroadcastHandler {
     pub fn new() -> Self {
         Self {
             room_channels: HashMap::new(),
+            user_channels: HashMap::new(),
         }
     }
 
@@ -50,6 +53,24 @@ impl BroadcastHandler {
         }
     }
 
+    /// 获取或创建用户的广播通道
+    pub fn get_or_create_user_channel(
+        &mut self,
+        user_id: &str,
+    ) -> broadcast::Sender<WebSocketMessage> {
+        self.user_channels
+            .entry(user_id.to_string())
+            .or_insert_with(|| broadcast::channel(100).0)
+            .clone()
+    }
+
+    /// 发送消息给指定用户
+    pub fn broadcast_to_user(&self, user_id: &str, message: &WebSocketMessage) {
+        if let Some(sender) = self.user_channels.get(user_id) {
+            let _ = sender.send(message.clone());
+        }
+    }
+
     /// 处理广播消息，决定是否发送给客户端
     pub fn should_send_to_client(
         &self,
@@ -68,6 +89,24 @@ impl BroadcastHandler {
                     true
                 }
             }
+            WebSocketMessage::TypingStart {
+                user_id: msg_user_id,
+                ..
+            }
+            | WebSocketMessage::TypingStop {
+                user_id: msg_user_id,
+                ..
+            } => {
+                // 输入状态：不回显给自己
+                current_user_id.as_ref() != Some(msg_user_id)
+            }
+            WebSocketMessage::PresenceUpdate {
+                user_id: msg_user_id,
+                ..
+            } => {
+                // 自己的在线状态通过用户通道单独下发
+                current_user_id.as_ref() != Some(msg_user_id)
+            }
             _ => {
                 // 其他类型的消息（如用户上线/下线）直接发送
                 true
@@ -1,22 +1,76 @@
 use crate::websocket::WebSocketMessage;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 
 /// 连接状态管理，跟踪单个WebSocket连接的状态
 pub struct ConnectionState {
+    /// 连接的唯一标识，用于区分同一用户的多个设备
+    pub connection_id: String,
+    /// 客户端IP，用于审计日志
+    pub client_ip: Option<String>,
     pub user_id: Option<String>,
     pub current_room: Option<String>,
     pub room_receiver: Option<broadcast::Receiver<WebSocketMessage>>,
+    pub user_receiver: Option<broadcast::Receiver<WebSocketMessage>>,
+    /// 通过该连接加入且尚未离开的房间，断开时需要逐一离开
+    pub joined_rooms: HashSet<String>,
+    /// 最后一次收到任意帧（包括Pong）的时间，用于检测半开连接
+    pub last_received: Instant,
+    /// 最后一次收到业务消息的时间，用于空闲超时
+    pub last_activity: Instant,
 }
 
 impl ConnectionState {
     pub fn new() -> Self {
         Self {
+            connection_id: uuid::Uuid::new_v4().to_string(),
+            client_ip: None,
             user_id: None,
             current_room: None,
             room_receiver: None,
+            user_receiver: None,
+            joined_rooms: HashSet::new(),
+            last_received: Instant::now(),
+            last_activity: Instant::now(),
         }
     }
 
+    /// 加入房间并切换房间接收器
+    pub fn join_room(&mut self, room_id: String, receiver: broadcast::Receiver<WebSocketMessage>) {
+        self.joined_rooms.insert(room_id.clone());
+        self.current_room = Some(room_id);
+        self.room_receiver = Some(receiver);
+    }
+
+    /// 离开房间，如果是当前房间则同时清除接收器
+    pub fn leave_room(&mut self, room_id: &str) {
+        self.joined_rooms.remove(room_id);
+        if self.current_room.as_deref() == Some(room_id) {
+            self.clear_current_room();
+        }
+    }
+
+    /// 记录收到了一帧数据
+    pub fn mark_received(&mut self) {
+        self.last_received = Instant::now();
+    }
+
+    /// 记录收到了业务消息
+    pub fn mark_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// 是否超过指定时间没有收到任何数据（包括Pong）
+    pub fn is_unresponsive(&self, timeout: Duration) -> bool {
+        self.last_received.elapsed() > timeout
+    }
+
+    /// 是否超过指定时间没有业务消息
+    pub fn is_idle(&self, timeout: Duration) -> bool {
+        self.last_activity.elapsed() > timeout
+    }
+
     /// 设置用户ID
     pub fn set_user_id(&mut self, user_id: String) {
         self.user_id = Some(user_id);
@@ -38,6 +92,11 @@ impl ConnectionState {
         self.room_receiver = Some(receiver);
     }
 
+    /// 设置用户接收器
+    pub fn set_user_receiver(&mut self, receiver: broadcast::Receiver<WebSocketMessage>) {
+        self.user_receiver = Some(receiver);
+    }
+
     /// 获取当前用户ID的引用
     pub fn get_user_id(&self) -> &Option<String> {
         &self.user_id
@@ -1,3 +1,4 @@
+use crate::models::{AttachmentInfo, LinkPreviewInfo, MessageReference, PinInfo, PollInfo};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,16 +10,168 @@ pub enum WebSocketMessage {
     LeaveRoom { room_id: String, user_id: String },
     #[serde(rename = "chat_message")]
     ChatMessage {
+        /// 广播时带上已保存消息的ID，客户端发送时为空
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
         room_id: String,
         user_id: String,
         username: String,
         content: String,
+        /// 服务端渲染的HTML，只在广播时填写，客户端发送的值会被忽略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_html: Option<String>,
         message_type: String,
+        /// 发送时引用的已上传附件ID
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attachment_ids: Vec<String>,
+        /// 广播时附带的附件信息
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attachments: Vec<AttachmentInfo>,
+        /// 投票消息附带的投票信息，只在广播时填写
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        poll: Option<PollInfo>,
+        /// 消息有效期（秒），发送时填写，不填时使用房间默认值
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl_seconds: Option<u32>,
+        /// 过期时间（Unix时间戳，秒），只在广播时填写
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<i64>,
+        /// 转发或引用的原消息快照，只在广播时填写
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reference: Option<MessageReference>,
     },
+    /// 消息内容更新通知，如链接预览生成完成
+    #[serde(rename = "message_updated")]
+    MessageUpdated {
+        room_id: String,
+        message_id: String,
+        link_previews: Vec<LinkPreviewInfo>,
+    },
+    /// 消息被删除通知
+    #[serde(rename = "message_deleted")]
+    MessageDeleted { room_id: String, message_id: String },
+    /// 发起投票，closes_at为Unix时间戳（秒）
+    #[serde(rename = "create_poll")]
+    CreatePoll {
+        user_id: String,
+        room_id: String,
+        question: String,
+        options: Vec<String>,
+        #[serde(default)]
+        multi_choice: bool,
+        #[serde(default)]
+        anonymous: bool,
+        #[serde(default)]
+        closes_at: Option<i64>,
+    },
+    #[serde(rename = "vote_poll")]
+    VotePoll {
+        user_id: String,
+        poll_id: String,
+        option_ids: Vec<String>,
+    },
+    #[serde(rename = "close_poll")]
+    ClosePoll { user_id: String, poll_id: String },
+    /// 投票票数变化或投票结束通知
+    #[serde(rename = "poll_updated")]
+    PollUpdated { room_id: String, poll: PollInfo },
+    /// 置顶或取消置顶消息，只有房间创建者、版主和管理员可以操作
+    #[serde(rename = "pin_message")]
+    PinMessage { user_id: String, message_id: String },
+    #[serde(rename = "unpin_message")]
+    UnpinMessage { user_id: String, message_id: String },
+    /// 消息被置顶通知
+    #[serde(rename = "message_pinned")]
+    MessagePinned { room_id: String, pin: PinInfo },
+    #[serde(rename = "message_unpinned")]
+    MessageUnpinned {
+        room_id: String,
+        message_id: String,
+        unpinned_by: String,
+    },
+    /// 把消息转发到另一个房间
+    #[serde(rename = "forward_message")]
+    ForwardMessage {
+        user_id: String,
+        message_id: String,
+        room_id: String,
+    },
+    /// 引用回复，room_id为发送回复的房间
+    #[serde(rename = "quote_message")]
+    QuoteMessage {
+        user_id: String,
+        message_id: String,
+        room_id: String,
+        content: String,
+    },
+    /// 加入房间时发送给加入者的当前置顶消息
+    #[serde(rename = "room_pins")]
+    RoomPins { room_id: String, pins: Vec<PinInfo> },
     #[serde(rename = "user_online")]
     UserOnline { user_id: String, username: String },
     #[serde(rename = "user_offline")]
     UserOffline { user_id: String },
+    /// 正在输入事件，只广播不持久化
+    #[serde(rename = "typing_start")]
+    TypingStart {
+        room_id: String,
+        user_id: String,
+        username: String,
+    },
+    #[serde(rename = "typing_stop")]
+    TypingStop { room_id: String, user_id: String },
+    /// 客户端心跳，维持当前连接的在线状态
+    #[serde(rename = "heartbeat")]
+    Heartbeat { user_id: String },
+    /// 客户端设置在线状态：online/away/dnd/invisible
+    #[serde(rename = "set_presence")]
+    SetPresence { user_id: String, status: String },
+    /// 在线状态变化通知，发送给所在房间和联系人
+    #[serde(rename = "presence_update")]
+    PresenceUpdate {
+        user_id: String,
+        status: String,
+        last_seen_at: Option<i64>,
+    },
+    /// 反垃圾自动禁言告警，只发送给版主和管理员
+    #[serde(rename = "moderation_alert")]
+    ModerationAlert {
+        user_id: String,
+        username: String,
+        room_id: String,
+        rule: String,
+        reason: String,
+        muted_until: i64,
+    },
+    /// 举报消息或用户，target_type为message时填message_id，为user时填target_user_id
+    #[serde(rename = "submit_report")]
+    SubmitReport {
+        user_id: String,
+        target_type: String,
+        #[serde(default)]
+        message_id: Option<String>,
+        #[serde(default)]
+        target_user_id: Option<String>,
+        reason: String,
+    },
+    /// 举报处理结果，只发送给举报人
+    #[serde(rename = "report_resolved")]
+    ReportResolved {
+        report_id: String,
+        resolution: String,
+        message: String,
+    },
+    /// 斜杠命令的执行结果，只发送给执行者
+    #[serde(rename = "command_result")]
+    CommandResult { command: String, message: String },
+    /// 用户被移出房间通知，被移出的用户收到后离开房间
+    #[serde(rename = "user_kicked")]
+    UserKicked {
+        room_id: String,
+        user_id: String,
+        kicked_by: String,
+        reason: String,
+    },
     #[serde(rename = "error")]
     Error { message: String },
     #[serde(rename = "success")]
@@ -33,4 +186,26 @@ impl WebSocketMessage {
     pub fn from_json(data: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(data)
     }
+
+    /// 客户端发来的消息中携带的用户ID
+    pub fn sender_user_id(&self) -> Option<&str> {
+        match self {
+            WebSocketMessage::JoinRoom { user_id, .. }
+            | WebSocketMessage::LeaveRoom { user_id, .. }
+            | WebSocketMessage::ChatMessage { user_id, .. }
+            | WebSocketMessage::TypingStart { user_id, .. }
+            | WebSocketMessage::TypingStop { user_id, .. }
+            | WebSocketMessage::Heartbeat { user_id }
+            | WebSocketMessage::SetPresence { user_id, .. }
+            | WebSocketMessage::SubmitReport { user_id, .. }
+            | WebSocketMessage::CreatePoll { user_id, .. }
+            | WebSocketMessage::VotePoll { user_id, .. }
+            | WebSocketMessage::ClosePoll { user_id, .. }
+            | WebSocketMessage::PinMessage { user_id, .. }
+            | WebSocketMessage::UnpinMessage { user_id, .. }
+            | WebSocketMessage::ForwardMessage { user_id, .. }
+            | WebSocketMessage::QuoteMessage { user_id, .. } => Some(user_id),
+            _ => None,
+        }
+    }
 }
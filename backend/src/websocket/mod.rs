@@ -2,6 +2,9 @@
 pub mod broadcast_handler;
 pub mod connection_state;
 pub mod message;
+pub mod presence_tracker;
+pub mod room_event_bus;
+pub mod typing_tracker;
 
 // 两种不同的实现方式
 pub mod new_websocket; // 重构后的设计模式实现
@@ -20,3 +23,6 @@ pub use new_websocket::*;
 pub use broadcast_handler::*;
 pub use connection_state::*;
 pub use message::*;
+pub use presence_tracker::*;
+pub use room_event_bus::*;
+pub use typing_tracker::*;
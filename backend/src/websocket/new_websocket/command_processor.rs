@@ -1,5 +1,6 @@
 use super::EventHandlerFactory;
 use super::event_handlers::{MessageContext, MessageEventHandlerEnum, MessageResult};
+use crate::redis::{RateLimitDecision, RateLimiter};
 use crate::websocket::WebSocketMessage;
 use futures_util::SinkExt;
 use std::sync::Arc;
@@ -11,16 +12,19 @@ use tokio_tungstenite::tungstenite::Message as WsMessage;
 pub struct CommandProcessor {
     event_handler_factory: Arc<EventHandlerFactory>,
     broadcast_handler: Arc<Mutex<crate::websocket::BroadcastHandler>>,
+    rate_limiter: RateLimiter,
 }
 
 impl CommandProcessor {
     pub fn new(
         event_handler_factory: Arc<EventHandlerFactory>,
         broadcast_handler: Arc<Mutex<crate::websocket::BroadcastHandler>>,
+        rate_limiter: RateLimiter,
     ) -> Self {
         Self {
             event_handler_factory,
             broadcast_handler,
+            rate_limiter,
         }
     }
 
@@ -37,10 +41,24 @@ impl CommandProcessor {
         // 确定消息类型
         let message_type = self.get_message_type(&message);
 
+        // 所有帧共用ws_frame限流，单独配置过的命令再按命令限流
+        if let Some(retry_after_secs) = self.check_rate_limit(&message_type, connection_state).await
+        {
+            let error = WebSocketMessage::Error {
+                message: format!("操作过于频繁，请在{}秒后重试", retry_after_secs),
+            };
+            if let Ok(json) = error.to_json() {
+                ws_sender.send(WsMessage::Text(json)).await?;
+            }
+            return Ok(());
+        }
+
         // 获取对应的事件处理器
         if let Some(handler) = self.event_handler_factory.get_handler(&message_type) {
             // 创建消息处理上下文
             let mut context = MessageContext::new(self.broadcast_handler.clone());
+            context.connection_id = connection_state.connection_id.clone();
+            context.client_ip = connection_state.client_ip.clone();
             context.user_id = connection_state.get_user_id().clone();
             context.current_room = connection_state.get_current_room().clone();
 
@@ -57,6 +75,55 @@ impl CommandProcessor {
         Ok(())
     }
 
+    /// 直接执行离开房间，不经过限流也不向客户端发送响应，用于连接关闭时的清理
+    pub async fn leave_room(
+        &self,
+        room_id: String,
+        user_id: String,
+        connection_state: &mut crate::websocket::ConnectionState,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(handler) = self.event_handler_factory.get_handler("leave_room") else {
+            return Ok(());
+        };
+
+        let mut context = MessageContext::new(self.broadcast_handler.clone());
+        context.connection_id = connection_state.connection_id.clone();
+        context.client_ip = connection_state.client_ip.clone();
+        context.user_id = Some(user_id.clone());
+        context.current_room = connection_state.get_current_room().clone();
+
+        let message = WebSocketMessage::LeaveRoom { room_id, user_id };
+        if let MessageResult::LeftRoom(room_id) = handler.handle(message, &context).await? {
+            connection_state.leave_room(&room_id);
+        }
+        Ok(())
+    }
+
+    /// 检查当前连接的限流，被限流时返回需要等待的秒数
+    ///
+    /// 已登录时按用户计数，多个连接共享额度；Redis不可用时放行。
+    async fn check_rate_limit(
+        &self,
+        message_type: &str,
+        connection_state: &crate::websocket::ConnectionState,
+    ) -> Option<u64> {
+        let key = match connection_state.get_user_id() {
+            Some(user_id) => format!("user:{}", user_id),
+            None => format!("conn:{}", connection_state.connection_id),
+        };
+
+        for operation in ["ws_frame", message_type] {
+            match self.rate_limiter.check(operation, &key).await {
+                Ok(decision @ RateLimitDecision::Limited { .. }) => {
+                    return Some(decision.retry_after_secs());
+                }
+                Ok(RateLimitDecision::Allowed) => {}
+                Err(e) => eprintln!("限流检查失败: {}", e),
+            }
+        }
+        None
+    }
+
     /// 获取消息类型
     fn get_message_type(&self, message: &WebSocketMessage) -> String {
         match message {
@@ -67,6 +134,29 @@ impl CommandProcessor {
             WebSocketMessage::UserOnline { .. } => "user_online".to_string(),
             WebSocketMessage::UserOffline { .. } => "user_offline".to_string(),
             WebSocketMessage::Success { .. } => "success".to_string(),
+            WebSocketMessage::TypingStart { .. } => "typing_start".to_string(),
+            WebSocketMessage::TypingStop { .. } => "typing_stop".to_string(),
+            WebSocketMessage::Heartbeat { .. } => "heartbeat".to_string(),
+            WebSocketMessage::SetPresence { .. } => "set_presence".to_string(),
+            WebSocketMessage::PresenceUpdate { .. } => "presence_update".to_string(),
+            WebSocketMessage::MessageUpdated { .. } => "message_updated".to_string(),
+            WebSocketMessage::ModerationAlert { .. } => "moderation_alert".to_string(),
+            WebSocketMessage::MessageDeleted { .. } => "message_deleted".to_string(),
+            WebSocketMessage::SubmitReport { .. } => "submit_report".to_string(),
+            WebSocketMessage::ReportResolved { .. } => "report_resolved".to_string(),
+            WebSocketMessage::CreatePoll { .. } => "create_poll".to_string(),
+            WebSocketMessage::VotePoll { .. } => "vote_poll".to_string(),
+            WebSocketMessage::ClosePoll { .. } => "close_poll".to_string(),
+            WebSocketMessage::PollUpdated { .. } => "poll_updated".to_string(),
+            WebSocketMessage::PinMessage { .. } => "pin_message".to_string(),
+            WebSocketMessage::UnpinMessage { .. } => "unpin_message".to_string(),
+            WebSocketMessage::MessagePinned { .. } => "message_pinned".to_string(),
+            WebSocketMessage::MessageUnpinned { .. } => "message_unpinned".to_string(),
+            WebSocketMessage::RoomPins { .. } => "room_pins".to_string(),
+            WebSocketMessage::ForwardMessage { .. } => "forward_message".to_string(),
+            WebSocketMessage::QuoteMessage { .. } => "quote_message".to_string(),
+            WebSocketMessage::CommandResult { .. } => "command_result".to_string(),
+            WebSocketMessage::UserKicked { .. } => "user_kicked".to_string(),
         }
     }
 
@@ -96,6 +186,20 @@ impl CommandProcessor {
             MessageResult::SetRoomReceiver(receiver) => {
                 connection_state.set_room_receiver(receiver);
             }
+            MessageResult::JoinedRoom {
+                room_id,
+                receiver,
+                pins,
+            } => {
+                connection_state.join_room(room_id.clone(), receiver);
+                let snapshot = WebSocketMessage::RoomPins { room_id, pins };
+                if let Ok(json) = snapshot.to_json() {
+                    ws_sender.send(WsMessage::Text(json)).await?;
+                }
+            }
+            MessageResult::LeftRoom(room_id) => {
+                connection_state.leave_room(&room_id);
+            }
             MessageResult::SendResponse(response) => {
                 if let Ok(json) = response.to_json() {
                     ws_sender.send(WsMessage::Text(json)).await?;
@@ -1,8 +1,23 @@
 use super::event_handlers::{
-    ChatMessageHandler, ErrorHandler, JoinRoomHandler, LeaveRoomHandler, MessageEventHandlerEnum,
+    ChatMessageHandler, ErrorHandler, ForwardHandler, JoinRoomHandler, LeaveRoomHandler,
+    MessageEventHandlerEnum, PinHandler, PollHandler, PresenceHandler, ReportHandler,
+    TypingHandler,
 };
-use crate::database::{MessageRepository, UserRepository};
+use crate::audit::AuditLogger;
+use crate::commands::{
+    CommandRegistry, HelpCommand, KickCommand, MeCommand, MuteCommand, NickCommand, RoomNotifier,
+    TopicCommand, WhoisCommand,
+};
+use crate::database::{
+    AttachmentRepository, MessageRepository, ModerationRepository, RoomRepository, UserRepository,
+};
+use crate::forwarding::ForwardService;
+use crate::moderation::{ContentFilter, ReportService, SendGuard};
+use crate::pins::PinService;
+use crate::polls::PollService;
 use crate::redis::SessionManager;
+use crate::unfurl::LinkUnfurler;
+use crate::websocket::{PresenceTracker, RoomEventBus, TypingTracker};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -15,16 +30,66 @@ impl EventHandlerFactory {
     pub fn new(
         user_repo: Arc<UserRepository>,
         message_repo: Arc<MessageRepository>,
+        room_repo: Arc<RoomRepository>,
+        attachment_repo: Arc<AttachmentRepository>,
         session_manager: Arc<SessionManager>,
+        typing_tracker: TypingTracker,
+        presence_tracker: PresenceTracker,
+        link_unfurler: LinkUnfurler,
+        content_filter: ContentFilter,
+        send_guard: SendGuard,
+        report_service: ReportService,
+        audit_logger: AuditLogger,
+        poll_service: PollService,
+        pin_service: PinService,
+        forward_service: ForwardService,
+        moderation_repo: Arc<ModerationRepository>,
+        event_bus: RoomEventBus,
+        command_registry: CommandRegistry,
     ) -> Self {
         let mut handlers: HashMap<String, MessageEventHandlerEnum> = HashMap::new();
 
+        // 注册内置斜杠命令，HTTP和gRPC共用同一个注册表
+        let notifier = RoomNotifier::new(message_repo.clone(), event_bus.clone());
+        command_registry.register(MeCommand);
+        command_registry.register(HelpCommand);
+        command_registry.register(NickCommand::new(
+            user_repo.clone(),
+            notifier.clone(),
+            audit_logger.clone(),
+        ));
+        command_registry.register(WhoisCommand::new(user_repo.clone(), room_repo.clone()));
+        command_registry.register(TopicCommand::new(
+            room_repo.clone(),
+            notifier.clone(),
+            audit_logger.clone(),
+        ));
+        command_registry.register(KickCommand::new(
+            user_repo.clone(),
+            room_repo.clone(),
+            session_manager.clone(),
+            event_bus,
+            notifier.clone(),
+            audit_logger.clone(),
+        ));
+        command_registry.register(MuteCommand::new(
+            user_repo.clone(),
+            moderation_repo,
+            notifier,
+            audit_logger.clone(),
+        ));
+
         // 注册各种消息事件处理器
         handlers.insert(
             "chat_message".to_string(),
             MessageEventHandlerEnum::ChatMessage(ChatMessageHandler::new(
                 user_repo.clone(),
                 message_repo.clone(),
+                attachment_repo,
+                link_unfurler,
+                content_filter,
+                send_guard,
+                command_registry,
             )),
         );
 
@@ -32,15 +97,93 @@ impl EventHandlerFactory {
             "join_room".to_string(),
             MessageEventHandlerEnum::JoinRoom(JoinRoomHandler::new(
                 user_repo.clone(),
+                room_repo.clone(),
                 session_manager.clone(),
+                audit_logger.clone(),
+                pin_service.clone(),
             )),
         );
 
         handlers.insert(
             "leave_room".to_string(),
-            MessageEventHandlerEnum::LeaveRoom(LeaveRoomHandler::new(session_manager.clone())),
+            MessageEventHandlerEnum::LeaveRoom(LeaveRoomHandler::new(
+                session_manager.clone(),
+                audit_logger,
+            )),
+        );
+
+        handlers.insert(
+            "typing_start".to_string(),
+            MessageEventHandlerEnum::Typing(TypingHandler::new(
+                user_repo.clone(),
+                typing_tracker.clone(),
+                "typing_start",
+            )),
+        );
+
+        handlers.insert(
+            "typing_stop".to_string(),
+            MessageEventHandlerEnum::Typing(TypingHandler::new(
+                user_repo.clone(),
+                typing_tracker,
+                "typing_stop",
+            )),
         );
 
+        handlers.insert(
+            "heartbeat".to_string(),
+            MessageEventHandlerEnum::Presence(PresenceHandler::new(
+                presence_tracker.clone(),
+                "heartbeat",
+            )),
+        );
+
+        handlers.insert(
+            "set_presence".to_string(),
+            MessageEventHandlerEnum::Presence(PresenceHandler::new(
+                presence_tracker,
+                "set_presence",
+            )),
+        );
+
+        handlers.insert(
+            "submit_report".to_string(),
+            MessageEventHandlerEnum::Report(ReportHandler::new(user_repo.clone(), report_service)),
+        );
+
+        for message_type in ["create_poll", "vote_poll", "close_poll"] {
+            handlers.insert(
+                message_type.to_string(),
+                MessageEventHandlerEnum::Poll(PollHandler::new(
+                    user_repo.clone(),
+                    poll_service.clone(),
+                    message_type,
+                )),
+            );
+        }
+
+        for message_type in ["pin_message", "unpin_message"] {
+            handlers.insert(
+                message_type.to_string(),
+                MessageEventHandlerEnum::Pin(PinHandler::new(
+                    user_repo.clone(),
+                    pin_service.clone(),
+                    message_type,
+                )),
+            );
+        }
+
+        for message_type in ["forward_message", "quote_message"] {
+            handlers.insert(
+                message_type.to_string(),
+                MessageEventHandlerEnum::Forward(ForwardHandler::new(
+                    user_repo.clone(),
+                    forward_service.clone(),
+                    message_type,
+                )),
+            );
+        }
+
         handlers.insert(
             "error".to_string(),
             MessageEventHandlerEnum::Error(ErrorHandler::new()),
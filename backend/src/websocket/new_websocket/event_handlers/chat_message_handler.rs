@@ -1,6 +1,9 @@
 use super::{MessageContext, MessageEventHandler, MessageResult};
-use crate::database::{MessageRepository, UserRepository};
-use crate::models::{Message, MessageType};
+use crate::commands::{CommandDispatch, CommandError, CommandRegistry};
+use crate::database::{AttachmentRepository, MessageRepository, UserRepository};
+use crate::models::{Message, MessageType, validate_message_ttl};
+use crate::moderation::{ContentFilter, FilterDecision, SendGuard};
+use crate::unfurl::LinkUnfurler;
 use crate::websocket::WebSocketMessage;
 use std::sync::Arc;
 
@@ -8,13 +11,31 @@ use std::sync::Arc;
 pub struct ChatMessageHandler {
     user_repo: Arc<UserRepository>,
     message_repo: Arc<MessageRepository>,
+    attachment_repo: Arc<AttachmentRepository>,
+    link_unfurler: LinkUnfurler,
+    content_filter: ContentFilter,
+    send_guard: SendGuard,
+    command_registry: CommandRegistry,
 }
 
 impl ChatMessageHandler {
-    pub fn new(user_repo: Arc<UserRepository>, message_repo: Arc<MessageRepository>) -> Self {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        message_repo: Arc<MessageRepository>,
+        attachment_repo: Arc<AttachmentRepository>,
+        link_unfurler: LinkUnfurler,
+        content_filter: ContentFilter,
+        send_guard: SendGuard,
+        command_registry: CommandRegistry,
+    ) -> Self {
         Self {
             user_repo,
             message_repo,
+            attachment_repo,
+            link_unfurler,
+            content_filter,
+            send_guard,
+            command_registry,
         }
     }
 }
@@ -26,12 +47,18 @@ impl MessageEventHandler for ChatMessageHandler {
         message: WebSocketMessage,
         context: &MessageContext,
     ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        // 发送者以连接认证的用户为准，忽略帧中的user_id和username
+        let Some(uid) = context.user_id.clone() else {
+            return Ok(MessageResult::NoOp);
+        };
+
         if let WebSocketMessage::ChatMessage {
             room_id,
-            user_id: uid,
             content,
             message_type,
-            username,
+            attachment_ids,
+            ttl_seconds,
+            ..
         } = message
         {
             println!(
@@ -40,39 +67,130 @@ impl MessageEventHandler for ChatMessageHandler {
             );
 
             // 验证用户
-            if let Some(_u) = self.user_repo.find_by_id(&uid).await? {
+            if let Some(user) = self.user_repo.find_by_id(&uid).await? {
+                let username = user.username.clone();
                 println!("找到用户: {}", username);
 
+                // 以/开头的消息先交给命令注册表，命令结果只回复给发送者
+                let content = match self
+                    .command_registry
+                    .dispatch(&user, &room_id, &content, context.client_ip.as_deref())
+                    .await
+                {
+                    Ok(CommandDispatch::Message(content)) => content,
+                    Ok(CommandDispatch::Reply(reply)) => {
+                        return Ok(MessageResult::SendResponse(
+                            WebSocketMessage::CommandResult {
+                                command: reply.command,
+                                message: reply.message,
+                            },
+                        ));
+                    }
+                    Err(CommandError::Database(e)) => return Err(e.into()),
+                    Err(e) => {
+                        return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                            message: e.to_string(),
+                        }));
+                    }
+                };
+
                 let msg_type = match message_type.as_str() {
                     "image" => MessageType::Image,
                     "file" => MessageType::File,
                     _ => MessageType::Text,
                 };
 
+                let ttl_seconds = ttl_seconds.unwrap_or(0);
+                if let Err(message) = validate_message_ttl(ttl_seconds) {
+                    return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                        message,
+                    }));
+                }
+
+                // 附件必须是该用户在该房间上传且尚未发送的
+                let attachments = if attachment_ids.is_empty() {
+                    Vec::new()
+                } else {
+                    match self
+                        .attachment_repo
+                        .find_pending(&attachment_ids, &uid, &room_id)
+                        .await?
+                    {
+                        Some(attachments) => attachments,
+                        None => {
+                            return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                                message: "附件无效或已被使用".to_string(),
+                            }));
+                        }
+                    }
+                };
+
+                // 禁言、发送频率、房间慢速模式和垃圾消息检测
+                if let Some(rejection) = self.send_guard.check(&user, &room_id, &content).await? {
+                    return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                        message: rejection.message(),
+                    }));
+                }
+
+                // 保存前执行内容过滤，广播的也是过滤后的内容
+                let (content, flags) = match self.content_filter.check(&room_id, &content).await? {
+                    FilterDecision::Allow { content, flags } => (content, flags),
+                    FilterDecision::Reject { reason, .. } => {
+                        return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                            message: format!("消息被拒绝: {}", reason),
+                        }));
+                    }
+                };
+
                 let message = Message::new(
                     uid.clone(),
                     username.clone(),
                     content.clone(),
                     room_id.clone(),
                     msg_type,
-                );
+                )
+                .with_ttl(ttl_seconds);
 
                 // 保存到数据库
-                match self.message_repo.create(message).await {
-                    Ok(_) => println!("消息已保存到数据库"),
+                let message = match self.message_repo.create(message).await {
+                    Ok(message) => {
+                        println!("消息已保存到数据库");
+                        message
+                    }
                     Err(e) => {
                         println!("保存消息到数据库失败: {}", e);
                         return Ok(MessageResult::NoOp);
                     }
+                };
+
+                if !attachments.is_empty() {
+                    self.attachment_repo
+                        .attach_to_message(&message.id, &attachment_ids)
+                        .await?;
                 }
 
+                if let Err(e) = self.content_filter.record_flags(&message, &flags).await {
+                    println!("保存过滤标记失败: {}", e);
+                }
+
+                // 链接预览在后台生成，完成后通过message_updated通知房间
+                self.link_unfurler.enqueue(&message);
+
                 // 广播消息到房间
                 let broadcast_msg = WebSocketMessage::ChatMessage {
+                    message_id: Some(message.id.clone()),
                     room_id: room_id.clone(),
                     user_id: uid,
                     username,
                     content,
+                    content_html: message.content_html.clone(),
                     message_type,
+                    attachment_ids: Vec::new(),
+                    attachments: attachments.iter().map(|a| a.to_info()).collect(),
+                    poll: None,
+                    ttl_seconds: None,
+                    expires_at: message.expires_at.map(|t| t.timestamp()),
+                    reference: None,
                 };
                 println!("准备广播消息到房间: {}", room_id);
 
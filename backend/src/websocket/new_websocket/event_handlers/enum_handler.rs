@@ -7,6 +7,12 @@ pub enum MessageEventHandlerEnum {
     JoinRoom(JoinRoomHandler),
     LeaveRoom(LeaveRoomHandler),
     Error(ErrorHandler),
+    Typing(TypingHandler),
+    Presence(PresenceHandler),
+    Report(ReportHandler),
+    Poll(PollHandler),
+    Pin(PinHandler),
+    Forward(ForwardHandler),
 }
 
 impl MessageEventHandlerEnum {
@@ -20,6 +26,12 @@ impl MessageEventHandlerEnum {
             MessageEventHandlerEnum::JoinRoom(handler) => handler.handle(message, context).await,
             MessageEventHandlerEnum::LeaveRoom(handler) => handler.handle(message, context).await,
             MessageEventHandlerEnum::Error(handler) => handler.handle(message, context).await,
+            MessageEventHandlerEnum::Typing(handler) => handler.handle(message, context).await,
+            MessageEventHandlerEnum::Presence(handler) => handler.handle(message, context).await,
+            MessageEventHandlerEnum::Report(handler) => handler.handle(message, context).await,
+            MessageEventHandlerEnum::Poll(handler) => handler.handle(message, context).await,
+            MessageEventHandlerEnum::Pin(handler) => handler.handle(message, context).await,
+            MessageEventHandlerEnum::Forward(handler) => handler.handle(message, context).await,
         }
     }
 
@@ -29,9 +41,18 @@ impl MessageEventHandlerEnum {
             MessageEventHandlerEnum::JoinRoom(handler) => handler.supported_message_type(),
             MessageEventHandlerEnum::LeaveRoom(handler) => handler.supported_message_type(),
             MessageEventHandlerEnum::Error(handler) => handler.supported_message_type(),
+            MessageEventHandlerEnum::Typing(handler) => handler.supported_message_type(),
+            MessageEventHandlerEnum::Presence(handler) => handler.supported_message_type(),
+            MessageEventHandlerEnum::Report(handler) => handler.supported_message_type(),
+            MessageEventHandlerEnum::Poll(handler) => handler.supported_message_type(),
+            MessageEventHandlerEnum::Pin(handler) => handler.supported_message_type(),
+            MessageEventHandlerEnum::Forward(handler) => handler.supported_message_type(),
         }
     }
 }
 
 // 重新导出事件处理器类型
-use super::{ChatMessageHandler, ErrorHandler, JoinRoomHandler, LeaveRoomHandler};
+use super::{
+    ChatMessageHandler, ErrorHandler, ForwardHandler, JoinRoomHandler, LeaveRoomHandler,
+    PinHandler, PollHandler, PresenceHandler, ReportHandler, TypingHandler,
+};
@@ -0,0 +1,85 @@
+use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::database::UserRepository;
+use crate::forwarding::{ForwardError, ForwardService};
+use crate::models::{ForwardMessage, QuoteMessage};
+use crate::websocket::WebSocketMessage;
+use std::sync::Arc;
+
+/// 转发事件处理器，处理forward_message和quote_message
+///
+/// 新消息由ForwardService通过事件总线广播到目标房间，这里只回复操作结果。
+pub struct ForwardHandler {
+    user_repo: Arc<UserRepository>,
+    forward_service: ForwardService,
+    message_type: &'static str,
+}
+
+impl ForwardHandler {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        forward_service: ForwardService,
+        message_type: &'static str,
+    ) -> Self {
+        Self {
+            user_repo,
+            forward_service,
+            message_type,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageEventHandler for ForwardHandler {
+    async fn handle(
+        &self,
+        message: WebSocketMessage,
+        _context: &MessageContext,
+    ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(user_id) = message.sender_user_id() else {
+            return Ok(MessageResult::NoOp);
+        };
+        let Some(user) = self.user_repo.find_by_id(user_id).await? else {
+            return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                message: "用户不存在".to_string(),
+            }));
+        };
+
+        let result = match message {
+            WebSocketMessage::ForwardMessage {
+                message_id,
+                room_id,
+                ..
+            } => self
+                .forward_service
+                .forward(&user, &message_id, ForwardMessage { room_id })
+                .await
+                .map(|_| "消息已转发"),
+            WebSocketMessage::QuoteMessage {
+                message_id,
+                room_id,
+                content,
+                ..
+            } => self
+                .forward_service
+                .quote(&user, &message_id, QuoteMessage { room_id, content })
+                .await
+                .map(|_| "回复已发送"),
+            _ => return Ok(MessageResult::NoOp),
+        };
+
+        let response = match result {
+            Ok(message) => WebSocketMessage::Success {
+                message: message.to_string(),
+            },
+            Err(ForwardError::Database(e)) => return Err(e.into()),
+            Err(e) => WebSocketMessage::Error {
+                message: e.to_string(),
+            },
+        };
+        Ok(MessageResult::SendResponse(response))
+    }
+
+    fn supported_message_type(&self) -> &'static str {
+        self.message_type
+    }
+}
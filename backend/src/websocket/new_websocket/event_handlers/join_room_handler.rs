@@ -1,5 +1,8 @@
 use super::{MessageContext, MessageEventHandler, MessageResult};
-use crate::database::UserRepository;
+use crate::audit::AuditLogger;
+use crate::database::{RoomRepository, UserRepository};
+use crate::models::{AuditAction, AuditEntry};
+use crate::pins::PinService;
 use crate::redis::SessionManager;
 use crate::websocket::WebSocketMessage;
 use std::sync::Arc;
@@ -7,14 +10,26 @@ use std::sync::Arc;
 /// 加入房间消息事件处理器
 pub struct JoinRoomHandler {
     user_repo: Arc<UserRepository>,
+    room_repo: Arc<RoomRepository>,
     session_manager: Arc<SessionManager>,
+    audit_logger: AuditLogger,
+    pin_service: PinService,
 }
 
 impl JoinRoomHandler {
-    pub fn new(user_repo: Arc<UserRepository>, session_manager: Arc<SessionManager>) -> Self {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        room_repo: Arc<RoomRepository>,
+        session_manager: Arc<SessionManager>,
+        audit_logger: AuditLogger,
+        pin_service: PinService,
+    ) -> Self {
         Self {
             user_repo,
+            room_repo,
             session_manager,
+            audit_logger,
+            pin_service,
         }
     }
 }
@@ -35,11 +50,39 @@ impl MessageEventHandler for JoinRoomHandler {
 
             // 验证用户
             if let Some(user) = self.user_repo.find_by_id(&uid).await? {
-                // 将用户添加到房间的Redis列表中
-                if let Err(e) = self.session_manager.add_user_to_room(&uid, &room_id).await {
+                // 私有房间只允许创建者和已有成员加入，检查通过后才记录成员关系
+                let Some(room) = self.room_repo.find_by_id(&room_id).await? else {
+                    return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                        message: "房间不存在".to_string(),
+                    }));
+                };
+                if !self.room_repo.can_join(&uid, &room).await? {
+                    return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                        message: "没有权限加入该房间".to_string(),
+                    }));
+                }
+
+                // 将用户添加到房间的Redis列表中（按连接记录，支持多设备）
+                if let Err(e) = self
+                    .session_manager
+                    .join_room_connection(&uid, &room_id, &context.connection_id)
+                    .await
+                {
                     eprintln!("添加用户到房间失败: {}", e);
                 }
 
+                // 记录房间成员关系
+                if let Err(e) = self.room_repo.add_member(&room_id, &uid).await {
+                    eprintln!("保存房间成员失败: {}", e);
+                }
+                self.audit_logger.record(
+                    AuditEntry::new(AuditAction::MemberJoined)
+                        .actor(&uid)
+                        .target("room", &room_id)
+                        .ip(context.client_ip.as_deref())
+                        .details(serde_json::json!({ "via": "websocket" })),
+                );
+
                 // 广播用户加入房间的消息
                 let user_online_msg = WebSocketMessage::UserOnline {
                     user_id: uid.clone(),
@@ -55,7 +98,22 @@ impl MessageEventHandler for JoinRoomHandler {
                     eprintln!("广播用户加入房间消息失败: {}", e);
                 }
 
-                return Ok(MessageResult::SetRoomReceiver(receiver));
+                drop(broadcast_handler);
+
+                // 当前置顶消息随加入结果发送给加入者
+                let pins = match self.pin_service.room_pins(&room_id).await {
+                    Ok(pins) => pins,
+                    Err(e) => {
+                        eprintln!("获取置顶消息失败: {}", e);
+                        Vec::new()
+                    }
+                };
+
+                return Ok(MessageResult::JoinedRoom {
+                    room_id,
+                    receiver,
+                    pins,
+                });
             }
         }
         Ok(MessageResult::NoOp)
@@ -1,4 +1,6 @@
 use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::audit::AuditLogger;
+use crate::models::{AuditAction, AuditEntry};
 use crate::redis::SessionManager;
 use crate::websocket::WebSocketMessage;
 use std::sync::Arc;
@@ -6,11 +8,15 @@ use std::sync::Arc;
 /// 离开房间消息事件处理器
 pub struct LeaveRoomHandler {
     session_manager: Arc<SessionManager>,
+    audit_logger: AuditLogger,
 }
 
 impl LeaveRoomHandler {
-    pub fn new(session_manager: Arc<SessionManager>) -> Self {
-        Self { session_manager }
+    pub fn new(session_manager: Arc<SessionManager>, audit_logger: AuditLogger) -> Self {
+        Self {
+            session_manager,
+            audit_logger,
+        }
     }
 }
 
@@ -28,28 +34,42 @@ impl MessageEventHandler for LeaveRoomHandler {
         {
             println!("用户 {} 离开房间: {}", uid, room_id);
 
-            // 从房间的Redis列表中移除用户
-            if let Err(e) = self
+            // 从房间的Redis列表中移除当前连接，用户的其他设备仍在房间时不算离开
+            let left = match self
                 .session_manager
-                .remove_user_from_room(&uid, &room_id)
+                .leave_room_connection(&uid, &room_id, &context.connection_id)
                 .await
             {
-                eprintln!("从房间移除用户失败: {}", e);
-            }
+                Ok(left) => left,
+                Err(e) => {
+                    eprintln!("从房间移除用户失败: {}", e);
+                    true
+                }
+            };
 
             // 广播用户离开房间的消息
-            let user_offline_msg = WebSocketMessage::UserOffline {
-                user_id: uid.clone(),
-            };
+            if left {
+                self.audit_logger.record(
+                    AuditEntry::new(AuditAction::MemberLeft)
+                        .actor(&uid)
+                        .target("room", &room_id)
+                        .ip(context.client_ip.as_deref())
+                        .details(serde_json::json!({ "via": "websocket" })),
+                );
+
+                let user_offline_msg = WebSocketMessage::UserOffline {
+                    user_id: uid.clone(),
+                };
 
-            let mut broadcast_handler = context.broadcast_handler.lock().await;
-            if let Some(room_tx) = broadcast_handler.get_room_channel(&room_id) {
-                if let Err(e) = room_tx.send(user_offline_msg) {
-                    eprintln!("广播用户离开房间消息失败: {}", e);
+                let broadcast_handler = context.broadcast_handler.lock().await;
+                if let Some(room_tx) = broadcast_handler.get_room_channel(&room_id) {
+                    if let Err(e) = room_tx.send(user_offline_msg) {
+                        eprintln!("广播用户离开房间消息失败: {}", e);
+                    }
                 }
             }
 
-            return Ok(MessageResult::ClearCurrentRoom);
+            return Ok(MessageResult::LeftRoom(room_id));
         }
         Ok(MessageResult::NoOp)
     }
@@ -18,6 +18,8 @@ pub trait MessageEventHandler: Send + Sync {
 
 /// 消息处理上下文
 pub struct MessageContext {
+    pub connection_id: String,
+    pub client_ip: Option<String>,
     pub user_id: Option<String>,
     pub current_room: Option<String>,
     pub broadcast_handler: Arc<Mutex<crate::websocket::BroadcastHandler>>,
@@ -35,6 +37,14 @@ pub enum MessageResult {
     ClearCurrentRoom,
     /// 设置房间接收器
     SetRoomReceiver(tokio::sync::broadcast::Receiver<WebSocketMessage>),
+    /// 已加入房间：记录房间并切换接收器，再把当前置顶消息发送给加入者
+    JoinedRoom {
+        room_id: String,
+        receiver: tokio::sync::broadcast::Receiver<WebSocketMessage>,
+        pins: Vec<crate::models::PinInfo>,
+    },
+    /// 已离开房间
+    LeftRoom(String),
     /// 发送响应消息
     SendResponse(WebSocketMessage),
 }
@@ -42,6 +52,8 @@ pub enum MessageResult {
 impl MessageContext {
     pub fn new(broadcast_handler: Arc<Mutex<crate::websocket::BroadcastHandler>>) -> Self {
         Self {
+            connection_id: String::new(),
+            client_ip: None,
             user_id: None,
             current_room: None,
             broadcast_handler,
@@ -6,14 +6,26 @@
 pub mod chat_message_handler;
 pub mod enum_handler;
 pub mod error_handler;
+pub mod forward_handler;
 pub mod join_room_handler;
 pub mod leave_room_handler;
 pub mod message_handler;
+pub mod pin_handler;
+pub mod poll_handler;
+pub mod presence_handler;
+pub mod report_handler;
+pub mod typing_handler;
 
 // 重新导出主要的类型和trait
 pub use chat_message_handler::ChatMessageHandler;
 pub use enum_handler::MessageEventHandlerEnum;
 pub use error_handler::ErrorHandler;
+pub use forward_handler::ForwardHandler;
 pub use join_room_handler::JoinRoomHandler;
 pub use leave_room_handler::LeaveRoomHandler;
 pub use message_handler::{MessageContext, MessageEventHandler, MessageResult};
+pub use pin_handler::PinHandler;
+pub use poll_handler::PollHandler;
+pub use presence_handler::PresenceHandler;
+pub use report_handler::ReportHandler;
+pub use typing_handler::TypingHandler;
@@ -0,0 +1,76 @@
+use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::database::UserRepository;
+use crate::pins::{PinError, PinService};
+use crate::websocket::WebSocketMessage;
+use std::sync::Arc;
+
+/// 置顶事件处理器，处理pin_message和unpin_message
+///
+/// 置顶变化由PinService通过事件总线广播，这里只回复操作结果。
+pub struct PinHandler {
+    user_repo: Arc<UserRepository>,
+    pin_service: PinService,
+    message_type: &'static str,
+}
+
+impl PinHandler {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        pin_service: PinService,
+        message_type: &'static str,
+    ) -> Self {
+        Self {
+            user_repo,
+            pin_service,
+            message_type,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageEventHandler for PinHandler {
+    async fn handle(
+        &self,
+        message: WebSocketMessage,
+        context: &MessageContext,
+    ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(user_id) = message.sender_user_id() else {
+            return Ok(MessageResult::NoOp);
+        };
+        let Some(user) = self.user_repo.find_by_id(user_id).await? else {
+            return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                message: "用户不存在".to_string(),
+            }));
+        };
+        let ip = context.client_ip.as_deref();
+
+        let result = match message {
+            WebSocketMessage::PinMessage { message_id, .. } => self
+                .pin_service
+                .pin(&user, &message_id, ip)
+                .await
+                .map(|_| "消息已置顶"),
+            WebSocketMessage::UnpinMessage { message_id, .. } => self
+                .pin_service
+                .unpin(&user, &message_id, ip)
+                .await
+                .map(|_| "已取消置顶"),
+            _ => return Ok(MessageResult::NoOp),
+        };
+
+        let response = match result {
+            Ok(message) => WebSocketMessage::Success {
+                message: message.to_string(),
+            },
+            Err(PinError::Database(e)) => return Err(e.into()),
+            Err(e) => WebSocketMessage::Error {
+                message: e.to_string(),
+            },
+        };
+        Ok(MessageResult::SendResponse(response))
+    }
+
+    fn supported_message_type(&self) -> &'static str {
+        self.message_type
+    }
+}
@@ -0,0 +1,118 @@
+use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::database::UserRepository;
+use crate::models::{CreatePoll, MessageType};
+use crate::polls::{PollError, PollService};
+use crate::websocket::WebSocketMessage;
+use std::sync::Arc;
+
+/// 投票事件处理器，处理create_poll、vote_poll和close_poll
+///
+/// 票数变化由PollService通过事件总线广播，这里只回复操作结果。
+pub struct PollHandler {
+    user_repo: Arc<UserRepository>,
+    poll_service: PollService,
+    message_type: &'static str,
+}
+
+impl PollHandler {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        poll_service: PollService,
+        message_type: &'static str,
+    ) -> Self {
+        Self {
+            user_repo,
+            poll_service,
+            message_type,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageEventHandler for PollHandler {
+    async fn handle(
+        &self,
+        message: WebSocketMessage,
+        _context: &MessageContext,
+    ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(user_id) = message.sender_user_id() else {
+            return Ok(MessageResult::NoOp);
+        };
+        let Some(user) = self.user_repo.find_by_id(user_id).await? else {
+            return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                message: "用户不存在".to_string(),
+            }));
+        };
+
+        let result = match message {
+            WebSocketMessage::CreatePoll {
+                room_id,
+                question,
+                options,
+                multi_choice,
+                anonymous,
+                closes_at,
+                ..
+            } => {
+                let req = CreatePoll {
+                    question,
+                    options,
+                    multi_choice,
+                    anonymous,
+                    closes_at,
+                };
+                // 房间广播不回显给发起人，直接把投票消息回复给发起人
+                self.poll_service
+                    .create(&user, &room_id, req)
+                    .await
+                    .map(|(message, poll)| WebSocketMessage::ChatMessage {
+                        message_id: Some(message.id.clone()),
+                        room_id,
+                        user_id: user.id.clone(),
+                        username: user.username.clone(),
+                        content_html: Some(message.rendered_content()),
+                        content: message.content,
+                        message_type: MessageType::Poll.to_string(),
+                        attachment_ids: Vec::new(),
+                        attachments: Vec::new(),
+                        poll: Some(poll),
+                        ttl_seconds: None,
+                        expires_at: message.expires_at.map(|t| t.timestamp()),
+                        reference: None,
+                    })
+            }
+            WebSocketMessage::VotePoll {
+                poll_id,
+                option_ids,
+                ..
+            } => self
+                .poll_service
+                .vote(&user, &poll_id, option_ids)
+                .await
+                .map(|_| WebSocketMessage::Success {
+                    message: "投票成功".to_string(),
+                }),
+            WebSocketMessage::ClosePoll { poll_id, .. } => self
+                .poll_service
+                .close(&user, &poll_id)
+                .await
+                .map(|_| WebSocketMessage::Success {
+                    message: "投票已结束".to_string(),
+                }),
+            _ => return Ok(MessageResult::NoOp),
+        };
+
+        let response = match result {
+            Ok(response) => response,
+            Err(PollError::Database(e)) => return Err(e.into()),
+            Err(e) => WebSocketMessage::Error {
+                message: e.to_string(),
+            },
+        };
+        Ok(MessageResult::SendResponse(response))
+    }
+
+    fn supported_message_type(&self) -> &'static str {
+        self.message_type
+    }
+}
@@ -0,0 +1,64 @@
+use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::models::PresenceStatus;
+use crate::websocket::{PresenceTracker, WebSocketMessage};
+
+/// 在线状态事件处理器，处理heartbeat和set_presence
+pub struct PresenceHandler {
+    presence_tracker: PresenceTracker,
+    message_type: &'static str,
+}
+
+impl PresenceHandler {
+    pub fn new(presence_tracker: PresenceTracker, message_type: &'static str) -> Self {
+        Self {
+            presence_tracker,
+            message_type,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageEventHandler for PresenceHandler {
+    async fn handle(
+        &self,
+        message: WebSocketMessage,
+        context: &MessageContext,
+    ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        // 以连接认证的用户为准，忽略帧中的user_id
+        let Some(user_id) = context.user_id.as_deref() else {
+            return Ok(MessageResult::NoOp);
+        };
+
+        match message {
+            WebSocketMessage::Heartbeat { .. } => {
+                if let Err(e) = self
+                    .presence_tracker
+                    .touch(user_id, &context.connection_id)
+                    .await
+                {
+                    eprintln!("处理心跳失败: {}", e);
+                }
+            }
+            WebSocketMessage::SetPresence { status, .. } => {
+                let Some(status) = PresenceStatus::parse(&status) else {
+                    return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                        message: format!("无效的在线状态: {}", status),
+                    }));
+                };
+
+                if let Err(e) = self.presence_tracker.set_status(user_id, status).await {
+                    eprintln!("设置在线状态失败: {}", e);
+                    return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                        message: "设置在线状态失败".to_string(),
+                    }));
+                }
+            }
+            _ => {}
+        }
+        Ok(MessageResult::NoOp)
+    }
+
+    fn supported_message_type(&self) -> &'static str {
+        self.message_type
+    }
+}
@@ -0,0 +1,68 @@
+use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::database::UserRepository;
+use crate::models::SubmitReport;
+use crate::moderation::{ReportError, ReportService};
+use crate::websocket::WebSocketMessage;
+use std::sync::Arc;
+
+/// 举报事件处理器
+pub struct ReportHandler {
+    user_repo: Arc<UserRepository>,
+    report_service: ReportService,
+}
+
+impl ReportHandler {
+    pub fn new(user_repo: Arc<UserRepository>, report_service: ReportService) -> Self {
+        Self {
+            user_repo,
+            report_service,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageEventHandler for ReportHandler {
+    async fn handle(
+        &self,
+        message: WebSocketMessage,
+        _context: &MessageContext,
+    ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        let WebSocketMessage::SubmitReport {
+            user_id,
+            target_type,
+            message_id,
+            target_user_id,
+            reason,
+        } = message
+        else {
+            return Ok(MessageResult::NoOp);
+        };
+
+        let Some(reporter) = self.user_repo.find_by_id(&user_id).await? else {
+            return Ok(MessageResult::SendResponse(WebSocketMessage::Error {
+                message: "用户不存在".to_string(),
+            }));
+        };
+
+        let req = SubmitReport {
+            target_type,
+            message_id,
+            user_id: target_user_id,
+            reason,
+        };
+        let response = match self.report_service.submit(&reporter, req).await {
+            Ok(_) => WebSocketMessage::Success {
+                message: "举报已提交，我们会尽快处理".to_string(),
+            },
+            Err(ReportError::Database(e)) => return Err(e.into()),
+            Err(e) => WebSocketMessage::Error {
+                message: e.to_string(),
+            },
+        };
+        Ok(MessageResult::SendResponse(response))
+    }
+
+    fn supported_message_type(&self) -> &'static str {
+        "submit_report"
+    }
+}
@@ -0,0 +1,65 @@
+use super::{MessageContext, MessageEventHandler, MessageResult};
+use crate::database::UserRepository;
+use crate::websocket::{TypingTracker, WebSocketMessage};
+use std::sync::Arc;
+
+/// 输入状态事件处理器，处理typing_start和typing_stop
+///
+/// 输入状态只通过事件总线广播，不会写入数据库。用户ID和用户名以连接身份为准，
+/// 忽略消息中携带的值，避免冒充其他用户。
+pub struct TypingHandler {
+    user_repo: Arc<UserRepository>,
+    typing_tracker: TypingTracker,
+    message_type: &'static str,
+}
+
+impl TypingHandler {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        typing_tracker: TypingTracker,
+        message_type: &'static str,
+    ) -> Self {
+        Self {
+            user_repo,
+            typing_tracker,
+            message_type,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageEventHandler for TypingHandler {
+    async fn handle(
+        &self,
+        message: WebSocketMessage,
+        context: &MessageContext,
+    ) -> Result<MessageResult, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(user_id) = context.user_id.as_deref() else {
+            return Ok(MessageResult::NoOp);
+        };
+
+        let result = match message {
+            WebSocketMessage::TypingStart { room_id, .. } => {
+                let Some(user) = self.user_repo.find_by_id(user_id).await? else {
+                    return Ok(MessageResult::NoOp);
+                };
+                self.typing_tracker
+                    .start_typing(&room_id, &user.id, &user.username)
+                    .await
+            }
+            WebSocketMessage::TypingStop { room_id, .. } => {
+                self.typing_tracker.stop_typing(&room_id, user_id).await
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            eprintln!("更新输入状态失败: {}", e);
+        }
+        Ok(MessageResult::NoOp)
+    }
+
+    fn supported_message_type(&self) -> &'static str {
+        self.message_type
+    }
+}
@@ -1,24 +1,72 @@
 use super::{CommandProcessor, EventHandlerFactory};
-use crate::database::{DbPool, MessageRepository, UserRepository};
+use crate::audit::AuditLogger;
+use crate::commands::CommandRegistry;
+use crate::database::{
+    AttachmentRepository, DbPool, MessageRepository, ModerationRepository, RoomRepository,
+    UserRepository,
+};
+use crate::forwarding::ForwardService;
 use crate::grpc::auth::AuthService;
-use crate::redis::SessionManager;
-use crate::websocket::{BroadcastHandler, ConnectionState, WebSocketMessage};
+use crate::moderation::{ContentFilter, ReportService, SendGuard};
+use crate::pins::PinService;
+use crate::polls::PollService;
+use crate::redis::{RateLimiter, SessionManager};
+use crate::unfurl::LinkUnfurler;
+use crate::websocket::{
+    BroadcastHandler, ConnectionState, PresenceTracker, RoomEventBus, TypingTracker,
+    WebSocketMessage,
+};
 use futures_util::{SinkExt, StreamExt};
 use std::sync::Arc;
-use tokio_tungstenite::WebSocketStream;
+use std::time::Duration;
+use tokio::net::TcpStream;
 use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::{StatusCode, header};
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::{WebSocketStream, accept_hdr_async};
+
+/// 服务端发送Ping的间隔
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+/// 超过该时间没有收到任何数据（包括Pong）则认为连接已断开
+const PONG_TIMEOUT: Duration = Duration::from_secs(50);
+/// 超过该时间没有业务消息则关闭连接
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
 
 /// 重构后的WebSocket处理器，使用事件处理器+命令模式
 pub struct WebSocketHandler {
     event_handler_factory: Arc<EventHandlerFactory>,
     broadcast_handler: Arc<tokio::sync::Mutex<BroadcastHandler>>,
     command_processor: Arc<CommandProcessor>,
+    presence_tracker: PresenceTracker,
     auth_service: AuthService,
+    user_repo: Arc<UserRepository>,
 }
 
 impl WebSocketHandler {
-    pub fn new(pool: DbPool, session_manager: SessionManager) -> Self {
+    pub fn new(
+        pool: DbPool,
+        session_manager: SessionManager,
+        broadcast_handler: Arc<tokio::sync::Mutex<BroadcastHandler>>,
+        typing_tracker: TypingTracker,
+        presence_tracker: PresenceTracker,
+        link_unfurler: LinkUnfurler,
+        content_filter: ContentFilter,
+        send_guard: SendGuard,
+        rate_limiter: RateLimiter,
+        report_service: ReportService,
+        audit_logger: AuditLogger,
+        poll_service: PollService,
+        pin_service: PinService,
+        forward_service: ForwardService,
+        event_bus: RoomEventBus,
+        command_registry: CommandRegistry,
+    ) -> Self {
         let message_repo = Arc::new(MessageRepository::new(pool.clone()));
+        let room_repo = Arc::new(RoomRepository::new(pool.clone()));
+        let attachment_repo = Arc::new(AttachmentRepository::new(pool.clone()));
+        let moderation_repo = Arc::new(ModerationRepository::new(pool.clone()));
         let user_repo = Arc::new(UserRepository::new(pool));
         let auth_service = AuthService::new(
             std::env::var("JWT_SECRET").unwrap_or_else(|_| "your-secret-key".to_string()),
@@ -28,44 +76,112 @@ impl WebSocketHandler {
         let event_handler_factory = Arc::new(EventHandlerFactory::new(
             user_repo.clone(),
             message_repo,
+            room_repo,
+            attachment_repo,
             session_manager_arc.clone(),
+            typing_tracker,
+            presence_tracker.clone(),
+            link_unfurler,
+            content_filter,
+            send_guard,
+            report_service,
+            audit_logger,
+            poll_service,
+            pin_service,
+            forward_service,
+            moderation_repo,
+            event_bus,
+            command_registry,
         ));
-        let broadcast_handler = Arc::new(tokio::sync::Mutex::new(BroadcastHandler::new()));
         let command_processor = Arc::new(CommandProcessor::new(
             event_handler_factory.clone(),
             broadcast_handler.clone(),
+            rate_limiter,
         ));
 
         Self {
             event_handler_factory,
             broadcast_handler,
             command_processor,
+            presence_tracker,
             auth_service,
+            user_repo,
         }
     }
 
     pub async fn handle_connection(
         &self,
-        stream: WebSocketStream<tokio::net::TcpStream>,
+        stream: TcpStream,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client_ip = stream.peer_addr().ok().map(|addr| addr.ip().to_string());
+        let (stream, user_id) = self.accept(stream).await?;
         let (mut ws_sender, mut ws_receiver) = stream.split();
+
+        // 已封禁或不存在的用户不能建立连接
+        let allowed = self
+            .user_repo
+            .find_by_id(&user_id)
+            .await?
+            .is_some_and(|user| !user.is_banned());
+        if !allowed {
+            let close_frame = CloseFrame {
+                code: CloseCode::Policy,
+                reason: "forbidden".into(),
+            };
+            let _ = ws_sender.send(WsMessage::Close(Some(close_frame))).await;
+            return Ok(());
+        }
+
         let mut connection_state = ConnectionState::new();
+        connection_state.client_ip = client_ip;
+        self.identify_connection(user_id, &mut connection_state)
+            .await;
 
-        // 主消息处理循环
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        // 第一次tick立即完成，跳过
+        ping_interval.tick().await;
+
+        // 主消息处理循环，任何退出路径都会走到下面的清理逻辑
         loop {
             tokio::select! {
                 // 处理从客户端接收的消息
                 msg = ws_receiver.next() => {
-                    if let Some(msg) = msg {
-                        if let Err(e) = self.handle_websocket_message(
-                            msg?,
-                            &mut ws_sender,
-                            &mut connection_state,
-                        ).await {
-                            println!("处理WebSocket消息失败: {}", e);
+                    match msg {
+                        Some(Ok(msg)) => {
+                            if let Err(e) = self.handle_websocket_message(
+                                msg,
+                                &mut ws_sender,
+                                &mut connection_state,
+                            ).await {
+                                println!("处理WebSocket消息失败: {}", e);
+                                break;
+                            }
+                        }
+                        Some(Err(e)) => {
+                            println!("读取WebSocket消息失败: {}", e);
                             break;
                         }
-                    } else {
+                        None => break,
+                    }
+                }
+
+                // 定期发送Ping，并检查心跳和空闲超时
+                _ = ping_interval.tick() => {
+                    if connection_state.is_unresponsive(PONG_TIMEOUT) {
+                        println!("WebSocket连接心跳超时");
+                        break;
+                    }
+                    if connection_state.is_idle(IDLE_TIMEOUT) {
+                        println!("WebSocket连接空闲超时");
+                        let close_frame = CloseFrame {
+                            code: CloseCode::Away,
+                            reason: "idle timeout".into(),
+                        };
+                        let _ = ws_sender.send(WsMessage::Close(Some(close_frame))).await;
+                        break;
+                    }
+                    if let Err(e) = ws_sender.send(WsMessage::Ping(Vec::new())).await {
+                        println!("发送Ping失败: {}", e);
                         break;
                     }
                 }
@@ -85,6 +201,19 @@ impl WebSocketHandler {
                                 break;
                             }
                         }
+
+                        // 被移出房间时按离开房间处理，不再接收该房间的消息
+                        if let WebSocketMessage::UserKicked { room_id, user_id, .. } = &msg {
+                            if connection_state.get_user_id().as_deref() == Some(user_id.as_str()) {
+                                if let Err(e) = self
+                                    .command_processor
+                                    .leave_room(room_id.clone(), user_id.clone(), &mut connection_state)
+                                    .await
+                                {
+                                    println!("被移出后离开房间失败: {}", e);
+                                }
+                            }
+                        }
                     } else {
                         // 广播通道关闭，重新获取接收器
                         if let Some(room_id) = connection_state.get_current_room() {
@@ -95,12 +224,135 @@ impl WebSocketHandler {
                         }
                     }
                 }
+
+                // 处理发给当前用户的消息（如联系人在线状态）
+                user_msg = async {
+                    if let Some(ref mut receiver) = connection_state.user_receiver {
+                        receiver.recv().await
+                    } else {
+                        std::future::pending().await
+                    }
+                } => {
+                    if let Ok(msg) = user_msg {
+                        if let Err(e) = self.send_message_to_client(&mut ws_sender, &msg).await {
+                            println!("发送用户消息失败: {}", e);
+                            break;
+                        }
+                    }
+                }
             }
         }
 
+        self.cleanup_connection(&mut ws_sender, &mut connection_state)
+            .await;
+
         Ok(())
     }
 
+    /// 连接结束时的清理：离开所有房间、更新在线状态并关闭连接
+    async fn cleanup_connection(
+        &self,
+        ws_sender: &mut futures_util::stream::SplitSink<
+            WebSocketStream<tokio::net::TcpStream>,
+            WsMessage,
+        >,
+        connection_state: &mut ConnectionState,
+    ) {
+        if let Some(user_id) = connection_state.get_user_id().clone() {
+            // 复用离开房间的处理逻辑，广播user_offline；不经过限流，连接已关闭也不回复客户端
+            let rooms: Vec<String> = connection_state.joined_rooms.iter().cloned().collect();
+            for room_id in rooms {
+                if let Err(e) = self
+                    .command_processor
+                    .leave_room(room_id, user_id.clone(), connection_state)
+                    .await
+                {
+                    println!("断开连接时离开房间失败: {}", e);
+                }
+            }
+
+            if let Err(e) = self
+                .presence_tracker
+                .disconnect(&user_id, &connection_state.connection_id)
+                .await
+            {
+                println!("更新离线状态失败: {}", e);
+            }
+        }
+
+        let _ = ws_sender.close().await;
+    }
+
+    /// 完成握手，连接身份只取自握手时校验的JWT，令牌无效时返回401
+    async fn accept(
+        &self,
+        stream: TcpStream,
+    ) -> Result<(WebSocketStream<TcpStream>, String), Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut user_id = None;
+        let stream = accept_hdr_async(stream, |request: &Request, response: Response| {
+            match self.token_user_id(request) {
+                Some(id) => {
+                    user_id = Some(id);
+                    Ok(response)
+                }
+                None => {
+                    let mut error = ErrorResponse::new(Some("未登录或令牌无效".to_string()));
+                    *error.status_mut() = StatusCode::UNAUTHORIZED;
+                    Err(error)
+                }
+            }
+        })
+        .await?;
+
+        let user_id = user_id.ok_or("未登录或令牌无效")?;
+        Ok((stream, user_id))
+    }
+
+    /// 浏览器无法设置握手请求头，令牌放在查询参数token中，其他客户端也可以使用Authorization头
+    fn token_user_id(&self, request: &Request) -> Option<String> {
+        let token = request
+            .uri()
+            .query()
+            .and_then(|query| {
+                query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("token="))
+            })
+            .or_else(|| {
+                request
+                    .headers()
+                    .get(header::AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "))
+            })?;
+
+        self.auth_service
+            .verify_token(token)
+            .ok()
+            .map(|claims| claims.user_id)
+    }
+
+    /// 登记连接所属用户：订阅用户通道并登记在线连接
+    async fn identify_connection(&self, user_id: String, connection_state: &mut ConnectionState) {
+        {
+            let mut broadcast_handler = self.broadcast_handler.lock().await;
+            let receiver = broadcast_handler
+                .get_or_create_user_channel(&user_id)
+                .subscribe();
+            connection_state.set_user_receiver(receiver);
+        }
+
+        if let Err(e) = self
+            .presence_tracker
+            .touch(&user_id, &connection_state.connection_id)
+            .await
+        {
+            println!("登记在线连接失败: {}", e);
+        }
+        connection_state.set_user_id(user_id);
+    }
+
     /// 处理WebSocket消息 - 现在变得非常简洁
     async fn handle_websocket_message(
         &self,
@@ -111,13 +363,26 @@ impl WebSocketHandler {
         >,
         connection_state: &mut ConnectionState,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        connection_state.mark_received();
+
         match msg {
             WsMessage::Text(text) => {
+                connection_state.mark_activity();
                 println!("收到WebSocket文本消息: {}", text);
 
                 let ws_msg: WebSocketMessage = serde_json::from_str(&text)?;
                 println!("成功解析WebSocket消息: {:?}", ws_msg);
 
+                // 帧中的user_id只能是握手时认证的用户
+                if ws_msg.sender_user_id().is_some_and(|user_id| {
+                    Some(user_id) != connection_state.get_user_id().as_deref()
+                }) {
+                    let error = WebSocketMessage::Error {
+                        message: "用户身份与连接不一致".to_string(),
+                    };
+                    return self.send_message_to_client(ws_sender, &error).await;
+                }
+
                 // 使用命令处理器处理消息
                 self.command_processor
                     .process_message(ws_msg, ws_sender, connection_state)
@@ -127,6 +392,7 @@ impl WebSocketHandler {
                 println!("WebSocket连接关闭");
                 return Err("连接关闭".into());
             }
+            // Ping由tungstenite自动回复Pong，Pong只需要记录收到时间
             _ => {}
         }
         Ok(())
@@ -1,9 +1,13 @@
 use super::message_handlers::MessageHandlers;
+use crate::audit::AuditLogger;
 use crate::database::{DbPool, MessageRepository, UserRepository};
 use crate::grpc::auth::AuthService;
-use crate::redis::SessionManager;
+use crate::moderation::{ContentFilter, ReportService, SendGuard};
+use crate::polls::PollService;
+use crate::redis::{RateLimiter, SessionManager};
+use crate::unfurl::LinkUnfurler;
 use crate::websocket::WebSocketMessage;
-use crate::websocket::{BroadcastHandler, ConnectionState};
+use crate::websocket::{BroadcastHandler, ConnectionState, PresenceTracker, TypingTracker};
 use futures_util::{SinkExt, StreamExt};
 use std::sync::Arc;
 use tokio_tungstenite::tungstenite::Message as WsMessage;
@@ -18,7 +22,20 @@ pub struct WebSocketHandler {
 }
 
 impl WebSocketHandler {
-    pub fn new(pool: DbPool, session_manager: SessionManager) -> Self {
+    pub fn new(
+        pool: DbPool,
+        session_manager: SessionManager,
+        broadcast_handler: Arc<tokio::sync::Mutex<BroadcastHandler>>,
+        _typing_tracker: TypingTracker,
+        _presence_tracker: PresenceTracker,
+        _link_unfurler: LinkUnfurler,
+        _content_filter: ContentFilter,
+        _send_guard: SendGuard,
+        _rate_limiter: RateLimiter,
+        _report_service: ReportService,
+        _audit_logger: AuditLogger,
+        _poll_service: PollService,
+    ) -> Self {
         let message_repo = Arc::new(MessageRepository::new(pool.clone()));
         let user_repo = Arc::new(UserRepository::new(pool));
         let auth_service = AuthService::new(
@@ -30,7 +47,7 @@ impl WebSocketHandler {
             user_repo,
             session_manager: Arc::new(session_manager),
             auth_service,
-            broadcast_handler: Arc::new(tokio::sync::Mutex::new(BroadcastHandler::new())),
+            broadcast_handler,
         }
     }
 
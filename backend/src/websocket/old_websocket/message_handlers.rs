@@ -40,6 +40,7 @@ impl MessageHandlers {
             content,
             message_type,
             username,
+            ..
         } = msg
         {
             println!(
@@ -76,11 +77,19 @@ impl MessageHandlers {
 
                 // 广播消息到房间
                 let broadcast_msg = WebSocketMessage::ChatMessage {
+                    message_id: None,
                     room_id: room_id.clone(),
                     user_id: uid,
                     username,
+                    content_html: Some(crate::models::render_markdown(&content)),
                     content,
                     message_type,
+                    attachment_ids: Vec::new(),
+                    attachments: Vec::new(),
+                    poll: None,
+                    ttl_seconds: None,
+                    expires_at: None,
+                    reference: None,
                 };
                 println!("准备广播消息到房间: {}", room_id);
 
@@ -0,0 +1,176 @@
+use crate::database::UserRepository;
+use crate::models::PresenceStatus;
+use crate::redis::{PresenceManager, SessionManager};
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+type PresenceResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// 在线状态跟踪器，根据连接和心跳维护用户在线状态，并把变化通知到房间和联系人
+#[derive(Clone)]
+pub struct PresenceTracker {
+    presence_manager: PresenceManager,
+    session_manager: SessionManager,
+    user_repo: Arc<UserRepository>,
+    event_bus: RoomEventBus,
+}
+
+impl PresenceTracker {
+    pub fn new(
+        presence_manager: PresenceManager,
+        session_manager: SessionManager,
+        user_repo: Arc<UserRepository>,
+        event_bus: RoomEventBus,
+    ) -> Self {
+        Self {
+            presence_manager,
+            session_manager,
+            user_repo,
+            event_bus,
+        }
+    }
+
+    /// 连接建立或收到心跳时调用
+    pub async fn touch(&self, user_id: &str, connection_id: &str) -> PresenceResult<()> {
+        if self
+            .presence_manager
+            .touch_connection(user_id, connection_id)
+            .await?
+        {
+            self.user_repo.set_online_status(user_id, true).await?;
+            self.session_manager.set_user_online(user_id).await?;
+
+            let status = self.current_status(user_id).await?;
+            self.publish_presence(user_id, status, None).await?;
+        }
+        Ok(())
+    }
+
+    /// 连接断开时调用，最后一个连接断开后用户变为离线
+    pub async fn disconnect(&self, user_id: &str, connection_id: &str) -> PresenceResult<()> {
+        if self
+            .presence_manager
+            .remove_connection(user_id, connection_id)
+            .await?
+        {
+            self.go_offline(user_id).await?;
+        }
+        Ok(())
+    }
+
+    /// 用户主动设置在线状态
+    pub async fn set_status(&self, user_id: &str, status: PresenceStatus) -> PresenceResult<()> {
+        self.presence_manager
+            .set_status(user_id, status.as_str())
+            .await?;
+        self.user_repo
+            .set_presence_status(user_id, status.as_str())
+            .await?;
+
+        if self.presence_manager.is_online(user_id).await? {
+            self.publish_presence(user_id, status, None).await?;
+        }
+        Ok(())
+    }
+
+    /// 定期清理心跳超时的连接，处理未正常断开的客户端
+    pub async fn run_expiry_sweeper(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+
+            match self.presence_manager.take_expired().await {
+                Ok(offline_users) => {
+                    for user_id in offline_users {
+                        if let Err(e) = self.go_offline(&user_id).await {
+                            eprintln!("更新用户离线状态失败: {}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("清理过期连接失败: {}", e),
+            }
+        }
+    }
+
+    async fn go_offline(&self, user_id: &str) -> PresenceResult<()> {
+        let now = chrono::Utc::now();
+        self.user_repo.mark_offline(user_id, now).await?;
+        self.session_manager.set_user_offline(user_id).await?;
+
+        let status = self.current_status(user_id).await?;
+        // 隐身用户不暴露最后在线时间
+        let last_seen_at = (status != PresenceStatus::Invisible).then(|| now.timestamp());
+
+        let update = WebSocketMessage::PresenceUpdate {
+            user_id: user_id.to_string(),
+            status: "offline".to_string(),
+            last_seen_at,
+        };
+        self.deliver(user_id, update.clone(), update).await
+    }
+
+    async fn current_status(&self, user_id: &str) -> PresenceResult<PresenceStatus> {
+        if let Some(status) = self.presence_manager.get_status(user_id).await? {
+            return Ok(PresenceStatus::from(Some(status)));
+        }
+
+        let status = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .map(|user| user.presence())
+            .unwrap_or(PresenceStatus::Online);
+        Ok(status)
+    }
+
+    async fn publish_presence(
+        &self,
+        user_id: &str,
+        status: PresenceStatus,
+        last_seen_at: Option<i64>,
+    ) -> PresenceResult<()> {
+        let public_update = WebSocketMessage::PresenceUpdate {
+            user_id: user_id.to_string(),
+            status: status.visible_status().to_string(),
+            last_seen_at,
+        };
+        // 用户自己的其他设备看到真实状态
+        let own_update = WebSocketMessage::PresenceUpdate {
+            user_id: user_id.to_string(),
+            status: status.as_str().to_string(),
+            last_seen_at,
+        };
+        self.deliver(user_id, public_update, own_update).await
+    }
+
+    /// 把状态变化发送到用户所在的房间、不在这些房间中的联系人以及用户自己
+    async fn deliver(
+        &self,
+        user_id: &str,
+        public_update: WebSocketMessage,
+        own_update: WebSocketMessage,
+    ) -> PresenceResult<()> {
+        let rooms = self.session_manager.get_user_rooms(user_id).await?;
+
+        let mut reached: HashSet<String> = HashSet::new();
+        for room_id in &rooms {
+            reached.extend(self.session_manager.get_room_users(room_id).await?);
+            self.event_bus
+                .publish(room_id, public_update.clone())
+                .await?;
+        }
+
+        for contact_id in self.user_repo.get_contact_ids(user_id).await? {
+            if !reached.contains(&contact_id) {
+                self.event_bus
+                    .publish_to_user(&contact_id, public_update.clone())
+                    .await?;
+            }
+        }
+
+        self.event_bus.publish_to_user(user_id, own_update).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use crate::websocket::{BroadcastHandler, WebSocketMessage};
+use futures_util::StreamExt;
+use redis::{AsyncCommands, Client, RedisResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+const ROOM_EVENTS_CHANNEL: &str = "room_events";
+
+/// 事件的投递目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+enum EventTarget {
+    Room(String),
+    User(String),
+}
+
+/// 跨实例传递的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RoomEventEnvelope {
+    target: EventTarget,
+    message: WebSocketMessage,
+}
+
+/// 房间事件总线，通过Redis发布订阅把事件分发到所有实例的本地广播通道
+#[derive(Clone)]
+pub struct RoomEventBus {
+    client: Client,
+}
+
+impl RoomEventBus {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// 发布房间事件，所有实例（包括当前实例）都会收到
+    pub async fn publish(&self, room_id: &str, message: WebSocketMessage) -> RedisResult<()> {
+        self.publish_envelope(RoomEventEnvelope {
+            target: EventTarget::Room(room_id.to_string()),
+            message,
+        })
+        .await
+    }
+
+    /// 发布只发给指定用户的事件，该用户在任何实例上的连接都会收到
+    pub async fn publish_to_user(
+        &self,
+        user_id: &str,
+        message: WebSocketMessage,
+    ) -> RedisResult<()> {
+        self.publish_envelope(RoomEventEnvelope {
+            target: EventTarget::User(user_id.to_string()),
+            message,
+        })
+        .await
+    }
+
+    async fn publish_envelope(&self, envelope: RoomEventEnvelope) -> RedisResult<()> {
+        let mut conn = self.client.get_async_connection().await?;
+
+        let payload = serde_json::to_string(&envelope).map_err(|e| {
+            redis::RedisError::from((
+                redis::ErrorKind::TypeError,
+                "JSON serialization failed",
+                e.to_string(),
+            ))
+        })?;
+
+        conn.publish::<_, _, ()>(ROOM_EVENTS_CHANNEL, payload)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 订阅总线并把事件转发到本地广播通道，连接断开后自动重连
+    pub async fn run_relay(self, broadcast_handler: Arc<Mutex<BroadcastHandler>>) {
+        loop {
+            if let Err(e) = self.relay_once(&broadcast_handler).await {
+                eprintln!("房间事件总线连接失败: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn relay_once(
+        &self,
+        broadcast_handler: &Arc<Mutex<BroadcastHandler>>,
+    ) -> RedisResult<()> {
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(ROOM_EVENTS_CHANNEL).await?;
+
+        let mut messages = pubsub.on_message();
+        while let Some(msg) = messages.next().await {
+            let payload: String = msg.get_payload()?;
+            match serde_json::from_str::<RoomEventEnvelope>(&payload) {
+                Ok(envelope) => {
+                    let broadcast_handler = broadcast_handler.lock().await;
+                    match &envelope.target {
+                        EventTarget::Room(room_id) => {
+                            broadcast_handler.broadcast_to_room(room_id, &envelope.message)
+                        }
+                        EventTarget::User(user_id) => {
+                            broadcast_handler.broadcast_to_user(user_id, &envelope.message)
+                        }
+                    }
+                }
+                Err(e) => eprintln!("解析房间事件失败: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
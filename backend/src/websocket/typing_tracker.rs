@@ -0,0 +1,91 @@
+use crate::redis::TypingManager;
+use crate::websocket::{RoomEventBus, WebSocketMessage};
+use redis::RedisResult;
+use std::time::Duration;
+
+/// 输入状态跟踪器，负责节流、过期以及把输入事件发布到房间
+#[derive(Clone)]
+pub struct TypingTracker {
+    typing_manager: TypingManager,
+    event_bus: RoomEventBus,
+}
+
+impl TypingTracker {
+    pub fn new(typing_manager: TypingManager, event_bus: RoomEventBus) -> Self {
+        Self {
+            typing_manager,
+            event_bus,
+        }
+    }
+
+    /// 用户开始输入或刷新输入状态，被节流的请求直接忽略
+    pub async fn start_typing(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        username: &str,
+    ) -> RedisResult<()> {
+        if !self
+            .typing_manager
+            .try_acquire_throttle(room_id, user_id)
+            .await?
+        {
+            return Ok(());
+        }
+
+        // 只有新进入输入状态时才广播，刷新只延长过期时间
+        if self.typing_manager.mark_typing(room_id, user_id).await? {
+            self.event_bus
+                .publish(
+                    room_id,
+                    WebSocketMessage::TypingStart {
+                        room_id: room_id.to_string(),
+                        user_id: user_id.to_string(),
+                        username: username.to_string(),
+                    },
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// 用户停止输入
+    pub async fn stop_typing(&self, room_id: &str, user_id: &str) -> RedisResult<()> {
+        if self.typing_manager.clear_typing(room_id, user_id).await? {
+            self.publish_stop(room_id, user_id).await?;
+        }
+        Ok(())
+    }
+
+    /// 定期清理过期的输入状态并广播停止事件
+    pub async fn run_expiry_sweeper(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+
+            match self.typing_manager.take_expired().await {
+                Ok(expired) => {
+                    for (room_id, user_id) in expired {
+                        if let Err(e) = self.publish_stop(&room_id, &user_id).await {
+                            eprintln!("广播输入状态过期失败: {}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("清理过期输入状态失败: {}", e),
+            }
+        }
+    }
+
+    async fn publish_stop(&self, room_id: &str, user_id: &str) -> RedisResult<()> {
+        self.event_bus
+            .publish(
+                room_id,
+                WebSocketMessage::TypingStop {
+                    room_id: room_id.to_string(),
+                    user_id: user_id.to_string(),
+                },
+            )
+            .await
+    }
+}
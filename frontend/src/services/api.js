@@ -65,10 +65,19 @@ export const userApi = {
   },
 
   // 更新用户信息
-  updateProfile: (userId, username, avatar) => {
+  updateProfile: (userId, username) => {
     return api.put(`/users/${userId}`, {
       username,
-      avatar,
+    })
+  },
+
+  // 上传头像，服务端裁剪为正方形并返回更新后的用户信息
+  uploadAvatar: (file) => {
+    const formData = new FormData()
+    formData.append('file', file)
+    return api.post('/users/avatar', formData, {
+      headers: { 'Content-Type': 'multipart/form-data' },
+      timeout: 60000,
     })
   },
 }
@@ -80,15 +89,12 @@ export const chatApi = {
     return api.post('/chat/messages', messageData)
   },
 
-  // 获取消息列表
-  getMessages: (roomId, limit = null, beforeTimestamp = null) => {
-    const params = {}
+  // 获取消息列表，cursor为 { before } / { after } / { around } 之一，不传时返回最新消息
+  getMessages: (roomId, limit = null, cursor = {}) => {
+    const params = { ...cursor }
     if (limit !== null) {
       params.limit = limit
     }
-    if (beforeTimestamp) {
-      params.before_timestamp = beforeTimestamp
-    }
     return api.get(`/chat/rooms/${roomId}/messages`, { params })
   },
 
@@ -113,4 +119,23 @@ export const chatApi = {
   },
 }
 
+// 附件相关API
+export const attachmentApi = {
+  // 上传附件，返回的附件ID在发送消息时通过 attachment_ids 引用
+  upload: (roomId, file) => {
+    const formData = new FormData()
+    formData.append('room_id', roomId)
+    formData.append('file', file)
+    return api.post('/attachments', formData, {
+      headers: { 'Content-Type': 'multipart/form-data' },
+      timeout: 60000,
+    })
+  },
+
+  // 下载附件（需要携带认证信息，因此以Blob形式获取）
+  download: (attachmentId) => {
+    return api.get(`/attachments/${attachmentId}`, { responseType: 'blob' })
+  },
+}
+
 export default api
@@ -13,7 +13,7 @@ export const useChatStore = defineStore('chat', () => {
     console.log('添加消息到聊天列表:', message)
     const formattedMessage = {
       ...message,
-      id: message.id || `temp_${Date.now()}_${Math.random()}`, // 确保有ID字段
+      id: message.id || message.message_id || `temp_${Date.now()}_${Math.random()}`, // 确保有ID字段
       timestamp: message.timestamp ? dayjs(message.timestamp * 1000).format('HH:mm:ss') : dayjs().format('HH:mm:ss')
     }
     console.log('格式化后的消息:', formattedMessage)
@@ -32,6 +32,17 @@ export const useChatStore = defineStore('chat', () => {
     }
   }
 
+  const updateMessage = (messageId, changes) => {
+    const target = messages.value.find(msg => msg.id === messageId)
+    if (target) {
+      Object.assign(target, changes)
+    }
+  }
+
+  const removeMessage = (messageId) => {
+    messages.value = messages.value.filter(msg => msg.id !== messageId)
+  }
+
   const setMessages = (messageList) => {
     console.log('从API获取的消息列表（已按时间正序排列）:', messageList.map(msg => ({
       content: msg.content,
@@ -86,10 +97,10 @@ export const useChatStore = defineStore('chat', () => {
   const loadMessages = async () => {
     loading.value = true
     try {
-      console.log('正在加载房间消息:', currentRoom.value, '无数量限制')
+      console.log('正在加载房间最新消息:', currentRoom.value)
       const response = await chatApi.getMessages(currentRoom.value)
       console.log('收到消息响应:', response.data)
-      setMessages(response.data)
+      setMessages(response.data.data?.messages || [])
       console.log('设置后的消息列表:', messages.value)
       return { success: true }
     } catch (error) {
@@ -155,6 +166,8 @@ export const useChatStore = defineStore('chat', () => {
     loading,
     addMessage,
     removeTempMessage,
+    updateMessage,
+    removeMessage,
     setMessages,
     setOnlineUsers,
     setCurrentRoom,
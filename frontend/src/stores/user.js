@@ -74,10 +74,10 @@ export const useUserStore = defineStore('user', () => {
     clearUser()
   }
 
-  const updateProfile = async (username, avatar) => {
+  const updateProfile = async (username) => {
     loading.value = true
     try {
-      const response = await userApi.updateProfile(user.value.id, username, avatar)
+      const response = await userApi.updateProfile(user.value.id, username)
       if (response.data.success) {
         setUser(response.data.user)
         return { success: true, message: response.data.message }
@@ -94,6 +94,23 @@ export const useUserStore = defineStore('user', () => {
     }
   }
 
+  const uploadAvatar = async (file) => {
+    try {
+      const response = await userApi.uploadAvatar(file)
+      if (response.data.success) {
+        setUser(response.data.data)
+        return { success: true, message: response.data.message }
+      } else {
+        return { success: false, message: response.data.message }
+      }
+    } catch (error) {
+      return {
+        success: false,
+        message: error.response?.data?.message || '头像上传失败，请重试'
+      }
+    }
+  }
+
   return {
     user,
     token,
@@ -105,6 +122,7 @@ export const useUserStore = defineStore('user', () => {
     login,
     register,
     logout,
-    updateProfile
+    updateProfile,
+    uploadAvatar
   }
 })
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
+import { ElMessage } from 'element-plus'
 import { useUserStore } from './user'
 import { useChatStore } from './chat'
 
@@ -7,6 +8,30 @@ export const useWebSocketStore = defineStore('websocket', () => {
   const socket = ref(null)
   const connected = ref(false)
   const connecting = ref(false)
+  let heartbeatTimer = null
+
+  // 心跳间隔需小于服务端的连接存活时间（60秒）
+  const HEARTBEAT_INTERVAL = 25000
+
+  const startHeartbeat = () => {
+    stopHeartbeat()
+    heartbeatTimer = setInterval(() => {
+      const userStore = useUserStore()
+      if (userStore.user) {
+        sendMessage({
+          type: 'heartbeat',
+          user_id: userStore.user.id
+        })
+      }
+    }, HEARTBEAT_INTERVAL)
+  }
+
+  const stopHeartbeat = () => {
+    if (heartbeatTimer) {
+      clearInterval(heartbeatTimer)
+      heartbeatTimer = null
+    }
+  }
 
   const connect = async () => {
     if (connecting.value || connected.value) return
@@ -17,8 +42,8 @@ export const useWebSocketStore = defineStore('websocket', () => {
     connecting.value = true
 
     try {
-      // 使用原生WebSocket连接
-      const wsUrl = `ws://localhost:8301`
+      // 使用原生WebSocket连接，登录令牌在握手时校验
+      const wsUrl = `ws://localhost:8301?token=${encodeURIComponent(userStore.token)}`
       socket.value = new WebSocket(wsUrl)
 
       socket.value.onopen = () => {
@@ -28,6 +53,7 @@ export const useWebSocketStore = defineStore('websocket', () => {
         
         // 加入默认房间
         joinRoom('general')
+        startHeartbeat()
       }
 
       socket.value.onmessage = (event) => {
@@ -40,6 +66,7 @@ export const useWebSocketStore = defineStore('websocket', () => {
       }
 
       socket.value.onclose = () => {
+        stopHeartbeat()
         connected.value = false
         connecting.value = false
         console.log('WebSocket disconnected')
@@ -64,6 +91,7 @@ export const useWebSocketStore = defineStore('websocket', () => {
   }
 
   const disconnect = () => {
+    stopHeartbeat()
     if (socket.value) {
       socket.value.close()
       socket.value = null
@@ -137,6 +165,18 @@ export const useWebSocketStore = defineStore('websocket', () => {
         // 添加正式消息
         chatStore.addMessage(message)
         break
+      case 'message_updated':
+        // 链接预览等异步生成的内容
+        chatStore.updateMessage(message.message_id, {
+          link_previews: message.link_previews
+        })
+        break
+      case 'poll_updated':
+        // 投票票数变化或投票结束
+        chatStore.updateMessage(message.poll.message_id, {
+          poll: message.poll
+        })
+        break
       case 'user_online':
         console.log('用户上线:', message)
         // 更新在线用户列表
@@ -147,11 +187,26 @@ export const useWebSocketStore = defineStore('websocket', () => {
         // 更新在线用户列表
         chatStore.getOnlineUsers()
         break
+      case 'presence_update':
+        console.log('在线状态变化:', message)
+        chatStore.getOnlineUsers()
+        break
       case 'success':
         console.log('Success:', message.message)
         break
+      case 'message_deleted':
+        chatStore.removeMessage(message.message_id)
+        break
+      case 'report_resolved':
+        ElMessage.info(message.message)
+        break
+      case 'moderation_alert':
+        // 只有版主和管理员会收到
+        ElMessage.warning(`${message.username} 已被自动禁言: ${message.reason}`)
+        break
       case 'error':
         console.error('Error:', message.message)
+        ElMessage.error(message.message)
         break
       default:
         console.log('Unknown message type:', message.type)